@@ -0,0 +1,103 @@
+//! Headless benchmark of resource node queries at 100k nodes: the `NodeIndex` against
+//! scanning every node, the way gathering used to.
+//!
+//! Run with `cargo bench --bench spatial_index`.
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+use bevy_game_demo::components::definitions::ResourceRegistry;
+use bevy_game_demo::components::time_of_day::TimeOfDay;
+use bevy_game_demo::components::types::{Gatherable, Position, ResourceNode, ResourceType, WorldSeed};
+use bevy_game_demo::components::weather::{Weather, WeatherRegistry};
+use bevy_game_demo::systems::placement::world_rng;
+use bevy_game_demo::systems::resources::{handle_resource_respawn, GATHER_FACING_COS};
+use bevy_game_demo::systems::spatial::{sync_node_index, NodeIndex};
+
+const NODE_COUNT: usize = 100_000;
+const QUERY_COUNT: usize = 10_000;
+/// Half the side of the square the nodes are scattered over.
+const HALF_EXTENT: f32 = 500.0;
+const RADIUS: f32 = 2.0;
+
+fn main() {
+    let mut rng = world_rng(WorldSeed(42));
+
+    let mut app = App::new();
+    // Respawning runs every tick over every node, so it must leave standing nodes unchanged
+    app.init_resource::<NodeIndex>()
+        .init_resource::<Time>()
+        .init_resource::<TimeOfDay>()
+        .insert_resource(Weather::new(0))
+        .init_resource::<WeatherRegistry>()
+        .init_resource::<ResourceRegistry>()
+        .init_resource::<RapierContext>()
+        .add_systems(Update, (handle_resource_respawn, sync_node_index).chain());
+    for _ in 0..NODE_COUNT {
+        let position = random_point(&mut rng);
+        app.world.spawn((
+            Position { value: position },
+            Transform::from_translation(position),
+            Collider::ball(0.5),
+            ResourceNode,
+            Gatherable { resource_type: ResourceType::WOOD, health: 100, respawn_timer: None },
+        ));
+    }
+
+    let start = Instant::now();
+    app.update();
+    println!("index {} nodes: {:?}", NODE_COUNT, start.elapsed());
+
+    let start = Instant::now();
+    app.update();
+    println!("respawn and sync with nothing changed: {:?}", start.elapsed());
+
+    let queries: Vec<(Vec3, Vec3)> = (0..QUERY_COUNT)
+        .map(|_| (random_point(&mut rng), random_direction(&mut rng)))
+        .collect();
+    let index = app.world.resource::<NodeIndex>();
+    let nodes: Vec<(Entity, Vec3)> = app
+        .world
+        .iter_entities()
+        .filter_map(|entity| entity.get::<Position>().map(|position| (entity.id(), position.value)))
+        .collect();
+
+    report("index nearest in radius", &queries, |(center, _)| {
+        index.nearest_in_radius(center, RADIUS, |_| true)
+    });
+    report("index nearest in cone", &queries, |(center, forward)| {
+        index.nearest_in_cone(center, forward, GATHER_FACING_COS, RADIUS, |_| true)
+    });
+    report("scan nearest in radius", &queries, |(center, _)| {
+        nodes
+            .iter()
+            .copied()
+            .filter(|(_, position)| position.distance(center) <= RADIUS)
+            .min_by(|(_, a), (_, b)| a.distance_squared(center).total_cmp(&b.distance_squared(center)))
+    });
+}
+
+/// Times `query` over every query point and prints the average cost per query.
+fn report(name: &str, queries: &[(Vec3, Vec3)], mut query: impl FnMut((Vec3, Vec3)) -> Option<(Entity, Vec3)>) {
+    let start = Instant::now();
+    let mut found = 0;
+    for &point in queries {
+        found += black_box(query(point)).is_some() as usize;
+    }
+    let per_query = start.elapsed() / queries.len() as u32;
+    println!("{name}: {} per query ({found} of {} found a node)", format_duration(per_query), queries.len());
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.2}us", duration.as_secs_f64() * 1e6)
+}
+
+fn random_point(rng: &mut impl Rng) -> Vec3 {
+    Vec3::new(rng.gen_range(-HALF_EXTENT..HALF_EXTENT), 0.0, rng.gen_range(-HALF_EXTENT..HALF_EXTENT))
+}
+
+fn random_direction(rng: &mut impl Rng) -> Vec3 {
+    let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+    Vec3::new(angle.cos(), 0.0, angle.sin())
+}
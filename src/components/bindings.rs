@@ -0,0 +1,179 @@
+//! Input actions and their key and mouse bindings, loaded from `*.bindings.ron` config files.
+use std::collections::HashMap;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::components::config::{FromConfig, RonAsset};
+
+/// Path of the input bindings file, relative to the assets folder.
+pub const BINDINGS_PATH: &str = "config/default.bindings.ron";
+
+/// Something the player can do, independent of the key or button that does it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    MoveForward,
+    MoveBackward,
+    MoveLeft,
+    MoveRight,
+    /// Hit the node in front of the player.
+    Gather,
+    /// Gather the node under the mouse cursor.
+    Pick,
+    /// Walk to the spot under the mouse cursor, around obstacles.
+    WalkTo,
+    /// Talk to the closest NPC in reach.
+    Talk,
+    /// Give the closest worker in reach the next job.
+    AssignJob,
+    /// Held while moving the mouse to orbit the camera.
+    RotateCamera,
+    ZoomIn,
+    ZoomOut,
+    /// Switch to the next camera mode (orbit, first person, top-down).
+    CycleCamera,
+    /// Craft the recipe in this slot (0 is the first recipe in the recipes file).
+    Craft(usize),
+    QuickSave,
+    QuickLoad,
+    /// Start or stop recording input for replay.
+    ToggleRecording,
+}
+
+impl Action {
+    /// Whether the action is handled by the gameplay systems in the fixed schedule, so
+    /// each press must reach exactly one fixed tick (see `TickActions`).
+    pub fn is_per_tick(&self) -> bool {
+        matches!(
+            self,
+            Action::Gather | Action::Pick | Action::WalkTo | Action::Talk | Action::AssignJob | Action::Craft(_)
+        )
+    }
+}
+
+/// A key, mouse button or gamepad button that triggers an action.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    /// A button on the active gamepad.
+    Gamepad(GamepadButtonType),
+}
+
+/// Asset: Bindings and the stick deadzone from one bindings file.
+/// Settings left out keep their defaults. Camera sensitivities live in the camera
+/// settings file (see `CameraSettings`).
+#[derive(Asset, TypePath, Clone, Debug, Serialize, Deserialize)]
+pub struct InputBindings {
+    /// Share of a stick's travel that is ignored around its center.
+    #[serde(default)]
+    pub stick_deadzone: Option<f32>,
+    /// Bindings per action. Actions left out keep their default bindings.
+    #[serde(default)]
+    pub bindings: HashMap<Action, Vec<Binding>>,
+}
+
+impl RonAsset for InputBindings {
+    const EXTENSION: &'static str = "bindings.ron";
+}
+
+/// Resource: The bindings currently in use. Starts with the built-in defaults,
+/// is overlaid with the bindings file once it loads, and can be changed at runtime.
+#[derive(Resource, Clone, Debug)]
+pub struct ActionMap {
+    bindings: HashMap<Action, Vec<Binding>>,
+    /// Share of a stick's travel that is ignored around its center.
+    pub stick_deadzone: f32,
+}
+
+impl Default for ActionMap {
+    fn default() -> Self {
+        use Binding::{Gamepad, Key, Mouse};
+        let mut bindings = HashMap::from([
+            (Action::MoveForward, vec![Key(KeyCode::KeyW)]),
+            (Action::MoveBackward, vec![Key(KeyCode::KeyS)]),
+            (Action::MoveLeft, vec![Key(KeyCode::KeyA)]),
+            (Action::MoveRight, vec![Key(KeyCode::KeyD)]),
+            (Action::Gather, vec![Key(KeyCode::KeyE), Gamepad(GamepadButtonType::South)]),
+            (Action::Pick, vec![Mouse(MouseButton::Left)]),
+            (Action::WalkTo, vec![Mouse(MouseButton::Middle)]),
+            (Action::Talk, vec![Key(KeyCode::KeyF), Gamepad(GamepadButtonType::West)]),
+            (Action::AssignJob, vec![Key(KeyCode::KeyJ), Gamepad(GamepadButtonType::North)]),
+            (Action::RotateCamera, vec![Mouse(MouseButton::Right), Mouse(MouseButton::Left)]),
+            (Action::ZoomIn, vec![Key(KeyCode::Equal)]),
+            (Action::ZoomOut, vec![Key(KeyCode::Minus)]),
+            (Action::CycleCamera, vec![Key(KeyCode::KeyV), Gamepad(GamepadButtonType::Select)]),
+            (Action::QuickSave, vec![Key(KeyCode::F5)]),
+            (Action::QuickLoad, vec![Key(KeyCode::F9)]),
+            (Action::ToggleRecording, vec![Key(KeyCode::F6)]),
+        ]);
+        let digits = [
+            KeyCode::Digit1,
+            KeyCode::Digit2,
+            KeyCode::Digit3,
+            KeyCode::Digit4,
+            KeyCode::Digit5,
+            KeyCode::Digit6,
+            KeyCode::Digit7,
+            KeyCode::Digit8,
+            KeyCode::Digit9,
+        ];
+        for (slot, digit) in digits.into_iter().enumerate() {
+            bindings.insert(Action::Craft(slot), vec![Key(digit)]);
+        }
+        Self {
+            bindings,
+            stick_deadzone: 0.2,
+        }
+    }
+}
+
+impl ActionMap {
+    /// Overlays `config`, replacing the bindings of every action it lists.
+    pub fn apply(&mut self, config: &InputBindings) {
+        for (action, bindings) in &config.bindings {
+            self.bindings.insert(*action, bindings.clone());
+        }
+        if let Some(deadzone) = config.stick_deadzone {
+            self.stick_deadzone = deadzone;
+        }
+    }
+
+    /// Every action with at least one binding.
+    pub fn actions(&self) -> impl Iterator<Item = Action> + '_ {
+        self.bindings.iter().filter(|(_, bindings)| !bindings.is_empty()).map(|(action, _)| *action)
+    }
+
+    /// The bindings that trigger `action`.
+    pub fn bindings(&self, action: Action) -> &[Binding] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Replaces every binding of `action` with `binding`.
+    pub fn rebind(&mut self, action: Action, binding: Binding) {
+        self.bindings.insert(action, vec![binding]);
+    }
+
+    /// Adds `binding` to `action`, keeping its other bindings.
+    pub fn bind(&mut self, action: Action, binding: Binding) {
+        let bindings = self.bindings.entry(action).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    /// Removes every binding of `action`.
+    pub fn unbind(&mut self, action: Action) {
+        self.bindings.remove(&action);
+    }
+}
+
+impl FromConfig for ActionMap {
+    type Config = InputBindings;
+    const PATH: &'static str = BINDINGS_PATH;
+
+    /// The built-in defaults overlaid with `config`.
+    fn from_config(config: &InputBindings) -> Self {
+        let mut map = Self::default();
+        map.apply(config);
+        map
+    }
+}
@@ -0,0 +1,158 @@
+//! Camera feel (sensitivity, axis inversion, pitch and zoom limits, smoothing), loaded
+//! from `*.camera.ron` config files so each player can tune it.
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::components::config::{FromConfig, RonAsset};
+
+/// Path of the camera settings file, relative to the assets folder.
+pub const CAMERA_SETTINGS_PATH: &str = "config/default.camera.ron";
+
+/// Asset: Camera settings from one settings file. Settings left out keep their defaults.
+#[derive(Asset, TypePath, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CameraConfig {
+    #[serde(default)]
+    pub mouse_sensitivity: Option<f32>,
+    #[serde(default)]
+    pub stick_rotate_speed: Option<f32>,
+    #[serde(default)]
+    pub stick_zoom_speed: Option<f32>,
+    #[serde(default)]
+    pub invert_x: Option<bool>,
+    #[serde(default)]
+    pub invert_y: Option<bool>,
+    #[serde(default)]
+    pub min_pitch: Option<f32>,
+    #[serde(default)]
+    pub max_pitch: Option<f32>,
+    #[serde(default)]
+    pub zoom_step: Option<f32>,
+    #[serde(default)]
+    pub min_distance: Option<f32>,
+    #[serde(default)]
+    pub max_distance: Option<f32>,
+    #[serde(default)]
+    pub top_down_zoom_step: Option<f32>,
+    #[serde(default)]
+    pub min_top_down_height: Option<f32>,
+    #[serde(default)]
+    pub max_top_down_height: Option<f32>,
+    #[serde(default)]
+    pub smoothing: Option<f32>,
+}
+
+impl RonAsset for CameraConfig {
+    const EXTENSION: &'static str = "camera.ron";
+}
+
+/// Resource: The camera settings in use. Starts with the built-in defaults and is
+/// overlaid with the settings file once it loads.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct CameraSettings {
+    /// Camera turn in radians per pixel of mouse movement, for both yaw and pitch.
+    pub mouse_sensitivity: f32,
+    /// Camera turn in radians per second at full right stick.
+    pub stick_rotate_speed: f32,
+    /// Camera zoom in wheel notches per second at full right stick.
+    pub stick_zoom_speed: f32,
+    /// Turn the other way for sideways mouse and stick movement.
+    pub invert_x: bool,
+    /// Tilt the other way for vertical mouse movement.
+    pub invert_y: bool,
+    /// Lowest pitch in radians. Negative looks up from below the player's center.
+    pub min_pitch: f32,
+    /// Highest pitch in radians. Must stay short of straight down.
+    pub max_pitch: f32,
+    /// Orbit distance change per wheel notch.
+    pub zoom_step: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    /// Top-down height change per wheel notch.
+    pub top_down_zoom_step: f32,
+    pub min_top_down_height: f32,
+    pub max_top_down_height: f32,
+    /// How quickly the camera catches up with where it should be; higher is snappier.
+    pub smoothing: f32,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self {
+            mouse_sensitivity: 0.01,
+            stick_rotate_speed: 2.5,
+            stick_zoom_speed: 4.0,
+            invert_x: false,
+            invert_y: false,
+            min_pitch: -0.3,
+            max_pitch: 1.3,
+            zoom_step: 0.5,
+            min_distance: 2.0,
+            max_distance: 10.0,
+            top_down_zoom_step: 1.5,
+            min_top_down_height: 6.0,
+            max_top_down_height: 40.0,
+            smoothing: 8.0,
+        }
+    }
+}
+
+impl CameraSettings {
+    /// Overlays every setting `config` lists. A pair of limits that would end up with
+    /// its lowest above its highest is left as it was, with a warning.
+    pub fn apply(&mut self, config: &CameraConfig) {
+        let overlay = |value: &mut f32, setting: Option<f32>| {
+            if let Some(setting) = setting {
+                *value = setting;
+            }
+        };
+        let overlay_limits = |name: &str, min: &mut f32, max: &mut f32, settings: (Option<f32>, Option<f32>)| {
+            let (new_min, new_max) = (settings.0.unwrap_or(*min), settings.1.unwrap_or(*max));
+            if new_min > new_max {
+                eprintln!("Ignoring camera {name} limits {new_min}..{new_max}: the lowest is above the highest");
+                return;
+            }
+            (*min, *max) = (new_min, new_max);
+        };
+        overlay(&mut self.mouse_sensitivity, config.mouse_sensitivity);
+        overlay(&mut self.stick_rotate_speed, config.stick_rotate_speed);
+        overlay(&mut self.stick_zoom_speed, config.stick_zoom_speed);
+        overlay_limits("pitch", &mut self.min_pitch, &mut self.max_pitch, (config.min_pitch, config.max_pitch));
+        overlay(&mut self.zoom_step, config.zoom_step);
+        overlay_limits(
+            "distance",
+            &mut self.min_distance,
+            &mut self.max_distance,
+            (config.min_distance, config.max_distance),
+        );
+        overlay(&mut self.top_down_zoom_step, config.top_down_zoom_step);
+        overlay_limits(
+            "top-down height",
+            &mut self.min_top_down_height,
+            &mut self.max_top_down_height,
+            (config.min_top_down_height, config.max_top_down_height),
+        );
+        overlay(&mut self.smoothing, config.smoothing);
+        if let Some(invert) = config.invert_x {
+            self.invert_x = invert;
+        }
+        if let Some(invert) = config.invert_y {
+            self.invert_y = invert;
+        }
+    }
+
+    /// `1.0`, or `-1.0` when `invert` is set.
+    pub fn axis_sign(invert: bool) -> f32 {
+        if invert { -1.0 } else { 1.0 }
+    }
+}
+
+impl FromConfig for CameraSettings {
+    type Config = CameraConfig;
+    const PATH: &'static str = CAMERA_SETTINGS_PATH;
+
+    /// The built-in defaults overlaid with `config`.
+    fn from_config(config: &CameraConfig) -> Self {
+        let mut settings = Self::default();
+        settings.apply(config);
+        settings
+    }
+}
@@ -0,0 +1,71 @@
+//! RON config and data files loaded as assets (resource definitions, recipes, bindings,
+//! camera settings, skill curve, quests, weather), and the resources built from them.
+use std::marker::PhantomData;
+use bevy::asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use bevy::utils::BoxedFuture;
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+
+/// Asset: A config or data file written in RON.
+pub trait RonAsset: Asset + DeserializeOwned {
+    /// End of the file names [`RonAssetLoader`] loads this asset from (e.g. `recipes.ron`).
+    const EXTENSION: &'static str;
+
+    /// Parses the asset from RON text.
+    fn from_ron(text: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(text)
+    }
+}
+
+/// Resource: Built from a [`RonAsset`] file, and built again whenever the file
+/// finishes loading or is edited.
+pub trait FromConfig: Resource {
+    type Config: RonAsset;
+    /// Path of the file, relative to the assets folder.
+    const PATH: &'static str;
+
+    /// Builds the resource from a loaded file.
+    fn from_config(config: &Self::Config) -> Self;
+}
+
+/// Errors from loading a RON asset file.
+#[derive(Debug, Error)]
+pub enum RonAssetLoaderError {
+    #[error("could not read file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse file: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+/// Loads a [`RonAsset`] from files ending in its [`RonAsset::EXTENSION`].
+pub struct RonAssetLoader<T>(PhantomData<fn() -> T>);
+
+impl<T> Default for RonAssetLoader<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: RonAsset> AssetLoader for RonAssetLoader<T> {
+    type Asset = T;
+    type Settings = ();
+    type Error = RonAssetLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut text = String::new();
+            reader.read_to_string(&mut text).await?;
+            Ok(T::from_ron(&text)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        std::slice::from_ref(&T::EXTENSION)
+    }
+}
@@ -0,0 +1,181 @@
+//! Data-driven resource node definitions, loaded from `*.resources.ron` asset files.
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::Collider;
+use serde::Deserialize;
+use crate::components::config::{FromConfig, RonAsset};
+use crate::components::equipment::ToolRequirement;
+use crate::components::skills::SkillRule;
+use crate::components::time_of_day::DayPeriod;
+use crate::components::inventory::{Inventory, DEFAULT_STACK_SIZE};
+use crate::components::types::{ResourceType, YieldRule};
+
+/// Path of the resource definitions file, relative to the assets folder.
+pub const RESOURCE_DEFINITIONS_PATH: &str = "data/default.resources.ron";
+
+/// Collision shape of a resource node.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub enum ColliderShape {
+    Cylinder { half_height: f32, radius: f32 },
+    Cuboid { half_extents: Vec3 },
+    Ball { radius: f32 },
+}
+
+impl ColliderShape {
+    /// Builds the Rapier collider for this shape.
+    pub fn collider(&self) -> Collider {
+        match *self {
+            ColliderShape::Cylinder { half_height, radius } => Collider::cylinder(half_height, radius),
+            ColliderShape::Cuboid { half_extents } => {
+                Collider::cuboid(half_extents.x, half_extents.y, half_extents.z)
+            }
+            ColliderShape::Ball { radius } => Collider::ball(radius),
+        }
+    }
+
+    /// Radius of the circle around the shape's base, seen from above.
+    pub fn footprint_radius(&self) -> f32 {
+        match *self {
+            ColliderShape::Cylinder { radius, .. } | ColliderShape::Ball { radius } => radius,
+            ColliderShape::Cuboid { half_extents } => half_extents.xz().length(),
+        }
+    }
+}
+
+/// Everything the game needs to know about one kind of resource node.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct ResourceDefinition {
+    pub id: ResourceType,
+    /// Name shown in the HUD and logs.
+    pub display_name: String,
+    /// Scene path of the node's model, relative to the assets folder.
+    pub model: String,
+    pub collider: ColliderShape,
+    /// Health a node spawns and respawns with.
+    pub health: u32,
+    pub yield_rule: YieldRule,
+    /// Resource gathering the node gives, if not its own (e.g. fallen logs give wood).
+    #[serde(default)]
+    pub drops: Option<ResourceType>,
+    /// Seconds a depleted node stays gone.
+    pub respawn_seconds: f32,
+    /// Whether a depleted node grows back at all; nodes that do not are removed.
+    #[serde(default = "respawns_by_default")]
+    pub respawns: bool,
+    /// Part of the day the node can grow back in, if only one. Nodes whose respawn
+    /// time runs out outside it wait for it to come round.
+    #[serde(default)]
+    pub respawn_during: Option<DayPeriod>,
+    /// Share of the world's nodes of this type, relative to the other types' weights.
+    pub spawn_weight: f32,
+    /// Most of this resource one inventory slot holds.
+    #[serde(default = "default_stack_size")]
+    pub stack_size: u32,
+    /// Tool that gathers this resource faster, and whether it is needed at all.
+    #[serde(default)]
+    pub tool: Option<ToolRequirement>,
+    /// Skill gathering this resource trains, and the level it takes.
+    #[serde(default)]
+    pub skill: Option<SkillRule>,
+    /// Node that storms can knock this one down into, if they can.
+    #[serde(default)]
+    pub falls_into: Option<ResourceType>,
+}
+
+fn default_stack_size() -> u32 {
+    DEFAULT_STACK_SIZE
+}
+
+fn respawns_by_default() -> bool {
+    true
+}
+
+/// Asset: All resource definitions from one definitions file.
+#[derive(Asset, TypePath, Clone, Debug, Deserialize)]
+pub struct ResourceDefinitions {
+    pub resources: Vec<ResourceDefinition>,
+}
+
+impl RonAsset for ResourceDefinitions {
+    const EXTENSION: &'static str = "resources.ron";
+}
+
+/// Resource: The resource definitions currently in use, in file order.
+/// Empty until the definitions file has loaded.
+#[derive(Resource, Default)]
+pub struct ResourceRegistry {
+    definitions: Vec<ResourceDefinition>,
+}
+
+impl ResourceRegistry {
+    pub fn new(definitions: ResourceDefinitions) -> Self {
+        Self {
+            definitions: definitions.resources,
+        }
+    }
+
+    /// Whether any definitions have been loaded.
+    pub fn is_loaded(&self) -> bool {
+        !self.definitions.is_empty()
+    }
+
+    /// Returns the definition for a resource type.
+    pub fn get(&self, resource_type: ResourceType) -> Option<&ResourceDefinition> {
+        self.definitions.iter().find(|definition| definition.id == resource_type)
+    }
+
+    /// Returns the definition for a resource type, for tweaking at runtime.
+    pub fn get_mut(&mut self, resource_type: ResourceType) -> Option<&mut ResourceDefinition> {
+        self.definitions.iter_mut().find(|definition| definition.id == resource_type)
+    }
+
+    /// Iterates over all definitions in file order.
+    pub fn iter(&self) -> impl Iterator<Item = &ResourceDefinition> {
+        self.definitions.iter()
+    }
+
+    /// Returns the display name of a resource type, or its id if it has no definition.
+    pub fn display_name(&self, resource_type: ResourceType) -> &str {
+        self.get(resource_type)
+            .map_or(resource_type.id(), |definition| definition.display_name.as_str())
+    }
+
+    /// Gives `inventory` the stack size of every defined resource.
+    pub fn apply_stack_sizes(&self, inventory: &mut Inventory) {
+        for definition in &self.definitions {
+            inventory.set_stack_size(definition.id, definition.stack_size);
+        }
+    }
+
+    /// Splits `total` nodes between the resource types by spawn weight.
+    pub fn spawn_counts(&self, total: u32) -> Vec<(&ResourceDefinition, u32)> {
+        let total_weight: f32 = self.definitions.iter().map(|d| d.spawn_weight.max(0.0)).sum();
+        if total_weight <= 0.0 {
+            return Vec::new();
+        }
+
+        // Round down each share, then hand the leftover nodes to the largest remainders
+        let shares: Vec<f32> = self
+            .definitions
+            .iter()
+            .map(|d| total as f32 * d.spawn_weight.max(0.0) / total_weight)
+            .collect();
+        let mut counts: Vec<u32> = shares.iter().map(|share| share.floor() as u32).collect();
+        let mut by_remainder: Vec<usize> = (0..shares.len()).collect();
+        by_remainder.sort_by(|&a, &b| (shares[b] - shares[b].floor()).total_cmp(&(shares[a] - shares[a].floor())));
+        let leftover = total.saturating_sub(counts.iter().sum());
+        for &index in by_remainder.iter().take(leftover as usize) {
+            counts[index] += 1;
+        }
+
+        self.definitions.iter().zip(counts).collect()
+    }
+}
+
+impl FromConfig for ResourceRegistry {
+    type Config = ResourceDefinitions;
+    const PATH: &'static str = RESOURCE_DEFINITIONS_PATH;
+
+    fn from_config(config: &ResourceDefinitions) -> Self {
+        Self::new(config.clone())
+    }
+}
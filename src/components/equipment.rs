@@ -0,0 +1,181 @@
+//! Gathering tools, their tiers and wear, and the equipment the player carries them in.
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// What a tool is for. Each resource definition names the kind of tool that works on it.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ToolKind {
+    Axe,
+    Pickaxe,
+}
+
+impl ToolKind {
+    /// Name shown in logs and the HUD.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ToolKind::Axe => "axe",
+            ToolKind::Pickaxe => "pickaxe",
+        }
+    }
+}
+
+/// Material a tool is made of, from worst to best. Better tiers hit harder, recover
+/// faster and last longer.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ToolTier {
+    Stone,
+    Iron,
+}
+
+impl ToolTier {
+    /// Name shown in logs and the HUD.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ToolTier::Stone => "stone",
+            ToolTier::Iron => "iron",
+        }
+    }
+
+    /// Damage dealt to a node's health per hit.
+    pub fn damage(&self) -> u32 {
+        match self {
+            ToolTier::Stone => 75,
+            ToolTier::Iron => 100,
+        }
+    }
+
+    /// Seconds before the next hit after a hit with a tool of this tier.
+    pub fn cooldown_seconds(&self) -> f32 {
+        match self {
+            ToolTier::Stone => 0.8,
+            ToolTier::Iron => 0.6,
+        }
+    }
+
+    /// Hits a new tool of this tier lasts.
+    pub fn max_durability(&self) -> u32 {
+        match self {
+            ToolTier::Stone => 40,
+            ToolTier::Iron => 100,
+        }
+    }
+}
+
+/// One tool. It loses a point of durability on every hit it lands and breaks at zero.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tool {
+    pub kind: ToolKind,
+    pub tier: ToolTier,
+    /// Hits left before the tool breaks.
+    pub durability: u32,
+}
+
+impl Tool {
+    /// A new, unworn tool.
+    pub fn new(kind: ToolKind, tier: ToolTier) -> Self {
+        Self { kind, tier, durability: tier.max_durability() }
+    }
+
+    /// Uses up one hit. Returns whether that broke the tool.
+    pub fn wear(&mut self) -> bool {
+        self.durability = self.durability.saturating_sub(1);
+        self.durability == 0
+    }
+}
+
+/// The tool a resource is gathered with, from its resource definition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub struct ToolRequirement {
+    /// Kind of tool that speeds up gathering this resource.
+    pub kind: ToolKind,
+    /// Worst tier that can gather it at all. Without one, bare hands work too.
+    #[serde(default)]
+    pub min_tier: Option<ToolTier>,
+}
+
+impl ToolRequirement {
+    /// Whether `tool` (or bare hands, for `None`) can gather the resource.
+    pub fn is_met_by(&self, tool: Option<&Tool>) -> bool {
+        match self.min_tier {
+            Some(min_tier) => tool.is_some_and(|tool| tool.kind == self.kind && tool.tier >= min_tier),
+            None => true,
+        }
+    }
+}
+
+/// Component: The tools a character has equipped, at most one of each kind, and the
+/// spares they carry. Gathering picks the tool of the kind the node needs, so there is
+/// no switching between them.
+#[derive(Component, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Equipment {
+    tools: Vec<Tool>,
+    /// Tools carried but not equipped, taken up when the equipped tool of their kind breaks.
+    #[serde(default)]
+    spares: Vec<Tool>,
+}
+
+impl Equipment {
+    /// Equips `tool`, returning the tool of the same kind it replaces, if any.
+    pub fn equip(&mut self, tool: Tool) -> Option<Tool> {
+        let old = self.unequip(tool.kind);
+        self.tools.push(tool);
+        old
+    }
+
+    /// Equips `tool` if nothing of its kind is equipped or it is of a better tier, keeping
+    /// the tool it replaces as a spare; otherwise keeps `tool` as a spare. Returns whether
+    /// `tool` was equipped.
+    pub fn receive(&mut self, tool: Tool) -> bool {
+        let upgrade = self.tool(tool.kind).is_none_or(|equipped| equipped.tier < tool.tier);
+        if upgrade {
+            let replaced = self.equip(tool);
+            self.spares.extend(replaced);
+        } else {
+            self.spares.push(tool);
+        }
+        upgrade
+    }
+
+    /// Equips the best spare of `kind` if no tool of that kind is equipped, and returns it.
+    pub fn equip_spare(&mut self, kind: ToolKind) -> Option<&Tool> {
+        if self.tool(kind).is_some() {
+            return None;
+        }
+        let best = self
+            .spares
+            .iter()
+            .enumerate()
+            .filter(|(_, spare)| spare.kind == kind)
+            .max_by_key(|(_, spare)| (spare.tier, spare.durability))
+            .map(|(index, _)| index)?;
+        let spare = self.spares.remove(best);
+        self.tools.push(spare);
+        self.tools.last()
+    }
+
+    /// Takes off the tool of `kind`, if one is equipped.
+    pub fn unequip(&mut self, kind: ToolKind) -> Option<Tool> {
+        let index = self.tools.iter().position(|tool| tool.kind == kind)?;
+        Some(self.tools.remove(index))
+    }
+
+    /// The equipped tool of `kind`.
+    pub fn tool(&self, kind: ToolKind) -> Option<&Tool> {
+        self.tools.iter().find(|tool| tool.kind == kind)
+    }
+
+    /// The equipped tool of `kind`, for wearing it down.
+    pub fn tool_mut(&mut self, kind: ToolKind) -> Option<&mut Tool> {
+        self.tools.iter_mut().find(|tool| tool.kind == kind)
+    }
+
+    /// Every equipped tool, in the order they were equipped.
+    pub fn tools(&self) -> &[Tool] {
+        &self.tools
+    }
+
+    /// Every spare tool, in the order they were received.
+    pub fn spares(&self) -> &[Tool] {
+        &self.spares
+    }
+}
@@ -0,0 +1,186 @@
+//! Slot-based item containers with per-item stack sizes and all-or-nothing changes.
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use crate::components::types::ResourceType;
+
+/// Number of slots in the player's inventory unless the embedding app inserts its own.
+pub const DEFAULT_SLOT_COUNT: usize = 12;
+/// Stack size of items that have none of their own.
+pub const DEFAULT_STACK_SIZE: u32 = 10;
+
+/// Some amount of one item, filling one slot.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ItemStack {
+    pub item: ResourceType,
+    pub amount: u32,
+}
+
+/// Why an inventory change was refused. A refused change leaves the inventory untouched.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum InventoryError {
+    #[error("no room for {amount} {} (room for {room})", item.id())]
+    NoRoom { item: ResourceType, amount: u32, room: u32 },
+    #[error("needs {needed} {}, have {available}", item.id())]
+    NotEnough { item: ResourceType, needed: u32, available: u32 },
+}
+
+/// A fixed number of slots, each empty or holding one stack. Adding fills the
+/// item's partly filled stacks first and overflows into new stacks in empty slots.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Inventory {
+    slots: Vec<Option<ItemStack>>,
+    stack_sizes: HashMap<ResourceType, u32>,
+    default_stack_size: u32,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self::new(DEFAULT_SLOT_COUNT, DEFAULT_STACK_SIZE)
+    }
+}
+
+impl Inventory {
+    /// Creates an empty inventory. Items without a stack size of their own stack up
+    /// to `default_stack_size`.
+    pub fn new(slot_count: usize, default_stack_size: u32) -> Self {
+        Self {
+            slots: vec![None; slot_count],
+            stack_sizes: HashMap::new(),
+            default_stack_size: default_stack_size.max(1),
+        }
+    }
+
+    /// The slots in order, `None` for empty ones.
+    pub fn slots(&self) -> &[Option<ItemStack>] {
+        &self.slots
+    }
+
+    /// Replaces every slot, e.g. when loading a save. The slot count becomes the
+    /// number of slots given.
+    pub fn set_slots(&mut self, slots: Vec<Option<ItemStack>>) {
+        self.slots = slots;
+    }
+
+    /// Most of `item` one slot holds.
+    pub fn stack_size(&self, item: ResourceType) -> u32 {
+        self.stack_sizes.get(&item).copied().unwrap_or(self.default_stack_size)
+    }
+
+    /// Sets how much of `item` one slot holds. Stacks already bigger than that keep
+    /// their contents but take no more.
+    pub fn set_stack_size(&mut self, item: ResourceType, size: u32) {
+        self.stack_sizes.insert(item, size.max(1));
+    }
+
+    /// Total amount of `item` across all slots.
+    pub fn count(&self, item: ResourceType) -> u32 {
+        self.stacks().filter(|stack| stack.item == item).map(|stack| stack.amount).sum()
+    }
+
+    /// Total amount of every item held.
+    pub fn totals(&self) -> HashMap<ResourceType, u32> {
+        let mut totals = HashMap::new();
+        for stack in self.stacks() {
+            *totals.entry(stack.item).or_insert(0) += stack.amount;
+        }
+        totals
+    }
+
+    /// Number of slots holding a stack.
+    pub fn used_slots(&self) -> usize {
+        self.stacks().count()
+    }
+
+    /// How much more of `item` fits, topping off its stacks and filling empty slots.
+    pub fn room_for(&self, item: ResourceType) -> u32 {
+        let stack_size = self.stack_size(item);
+        self.slots
+            .iter()
+            .map(|slot| match slot {
+                None => stack_size,
+                Some(stack) if stack.item == item => stack_size.saturating_sub(stack.amount),
+                Some(_) => 0,
+            })
+            .sum()
+    }
+
+    /// Adds all of `amount`, or nothing if it does not all fit.
+    pub fn try_add(&mut self, item: ResourceType, amount: u32) -> Result<(), InventoryError> {
+        let room = self.room_for(item);
+        if amount > room {
+            return Err(InventoryError::NoRoom { item, amount, room });
+        }
+        self.add_up_to(item, amount);
+        Ok(())
+    }
+
+    /// Adds as much of `amount` as fits and returns how much that was.
+    pub fn add_up_to(&mut self, item: ResourceType, amount: u32) -> u32 {
+        let stack_size = self.stack_size(item);
+        let mut left = amount;
+        // Top off existing stacks before starting new ones
+        for stack in self.slots.iter_mut().flatten().filter(|stack| stack.item == item) {
+            let added = left.min(stack_size.saturating_sub(stack.amount));
+            stack.amount += added;
+            left -= added;
+        }
+        for slot in self.slots.iter_mut().filter(|slot| slot.is_none()) {
+            if left == 0 {
+                break;
+            }
+            let added = left.min(stack_size);
+            *slot = Some(ItemStack { item, amount: added });
+            left -= added;
+        }
+        amount - left
+    }
+
+    /// Removes all of `amount`, or nothing if there is not that much. Takes from the
+    /// last stacks first and empties slots that run out.
+    pub fn try_remove(&mut self, item: ResourceType, amount: u32) -> Result<(), InventoryError> {
+        let available = self.count(item);
+        if amount > available {
+            return Err(InventoryError::NotEnough { item, needed: amount, available });
+        }
+        let mut left = amount;
+        for slot in self.slots.iter_mut().rev() {
+            let Some(stack) = slot.as_mut().filter(|stack| stack.item == item) else {
+                continue;
+            };
+            let removed = left.min(stack.amount);
+            stack.amount -= removed;
+            left -= removed;
+            if stack.amount == 0 {
+                *slot = None;
+            }
+            if left == 0 {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves `amount` of `item` from this inventory to `other`, or nothing if this
+    /// one has too little or `other` too little room.
+    pub fn transfer_to(&mut self, other: &mut Inventory, item: ResourceType, amount: u32) -> Result<(), InventoryError> {
+        let available = self.count(item);
+        if amount > available {
+            return Err(InventoryError::NotEnough { item, needed: amount, available });
+        }
+        other.try_add(item, amount)?;
+        self.try_remove(item, amount)
+    }
+
+    /// Runs several changes as one: if `change` fails, the inventory is left as it was.
+    pub fn transaction<T, E>(&mut self, change: impl FnOnce(&mut Inventory) -> Result<T, E>) -> Result<T, E> {
+        let mut draft = self.clone();
+        let result = change(&mut draft)?;
+        *self = draft;
+        Ok(result)
+    }
+
+    fn stacks(&self) -> impl Iterator<Item = &ItemStack> {
+        self.slots.iter().flatten()
+    }
+}
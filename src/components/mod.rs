@@ -1 +1,13 @@
-pub mod types; 
\ No newline at end of file
+pub mod types;
+pub mod config;
+pub mod definitions;
+pub mod recipes;
+pub mod bindings;
+pub mod camera_settings;
+pub mod inventory;
+pub mod equipment;
+pub mod skills;
+pub mod quests;
+pub mod time_of_day;
+pub mod weather;
+pub mod workers;
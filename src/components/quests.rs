@@ -0,0 +1,202 @@
+//! Data-driven quests, loaded from `*.quests.ron` asset files, and the player's
+//! progress through them.
+use std::collections::BTreeMap;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::components::config::{FromConfig, RonAsset};
+use crate::components::equipment::{ToolKind, ToolTier};
+use crate::components::skills::Skill;
+use crate::components::types::ResourceType;
+
+/// Path of the quests file, relative to the assets folder.
+pub const QUESTS_PATH: &str = "data/default.quests.ron";
+
+/// One thing a quest asks the player to do.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub enum Objective {
+    /// Gather this many units of a resource after the quest starts.
+    Gather { resource: ResourceType, amount: u32 },
+    /// Finish crafting a recipe this many times after the quest starts.
+    Craft { recipe: String, count: u32 },
+    /// Come within `radius` of a spot on the ground.
+    Reach { position: Vec3, radius: f32 },
+    /// Talk to the NPC with this id.
+    TalkTo { npc: String },
+}
+
+impl Objective {
+    /// Progress at which the objective is done.
+    pub fn required(&self) -> u32 {
+        match self {
+            Objective::Gather { amount, .. } => *amount,
+            Objective::Craft { count, .. } => *count,
+            Objective::Reach { .. } | Objective::TalkTo { .. } => 1,
+        }
+    }
+
+    /// Progress after `activity`, starting from `progress`.
+    fn advance(&self, progress: u32, activity: &QuestActivity) -> u32 {
+        let gained = match (self, activity) {
+            (Objective::Gather { resource, .. }, QuestActivity::Gathered { resource: gathered, amount })
+                if resource == gathered => *amount,
+            (Objective::Craft { recipe, .. }, QuestActivity::Crafted { recipe: crafted }) if recipe == crafted => 1,
+            (Objective::Reach { position, radius }, QuestActivity::At { position: at })
+                if position.xz().distance(at.xz()) <= *radius => 1,
+            (Objective::TalkTo { npc }, QuestActivity::TalkedTo { npc: talked_to }) if npc == talked_to => 1,
+            _ => 0,
+        };
+        progress.saturating_add(gained).min(self.required())
+    }
+
+    /// Short description shown in the quest log.
+    pub fn describe(&self) -> String {
+        match self {
+            Objective::Gather { resource, amount } => format!("Gather {} {}", amount, resource.id()),
+            Objective::Craft { recipe, count } => format!("Craft {} {}", count, recipe),
+            Objective::Reach { position, .. } => format!("Reach ({:.0}, {:.0})", position.x, position.z),
+            Objective::TalkTo { npc } => format!("Talk to the {}", npc),
+        }
+    }
+}
+
+/// What the player gets for completing a quest.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub enum Reward {
+    /// Items added to the inventory; whatever does not fit is lost.
+    Items(ResourceType, u32),
+    /// XP in a skill.
+    Xp(Skill, u32),
+    /// A new tool, equipped straight away if it beats the equipped tool of its kind,
+    /// otherwise kept as a spare.
+    Tool(ToolKind, ToolTier),
+}
+
+/// One quest.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct QuestDefinition {
+    pub id: String,
+    /// Name shown in the quest log and logs.
+    pub title: String,
+    /// Ids of the quests that must be completed before this one starts.
+    #[serde(default)]
+    pub prerequisites: Vec<String>,
+    /// Everything the quest asks for; it completes once all are done, in any order.
+    pub objectives: Vec<Objective>,
+    #[serde(default)]
+    pub rewards: Vec<Reward>,
+}
+
+/// Asset: All quests from one quests file.
+#[derive(Asset, TypePath, Clone, Debug, Deserialize)]
+pub struct Quests {
+    pub quests: Vec<QuestDefinition>,
+}
+
+impl RonAsset for Quests {
+    const EXTENSION: &'static str = "quests.ron";
+}
+
+/// Resource: The quests currently in use, in file order.
+/// Empty until the quests file has loaded.
+#[derive(Resource, Default)]
+pub struct QuestBook {
+    quests: Vec<QuestDefinition>,
+}
+
+impl QuestBook {
+    pub fn new(quests: Quests) -> Self {
+        Self { quests: quests.quests }
+    }
+
+    /// Returns the quest with the given id.
+    pub fn get(&self, id: &str) -> Option<&QuestDefinition> {
+        self.quests.iter().find(|quest| quest.id == id)
+    }
+
+    /// Iterates over all quests in file order.
+    pub fn iter(&self) -> impl Iterator<Item = &QuestDefinition> {
+        self.quests.iter()
+    }
+}
+
+impl FromConfig for QuestBook {
+    type Config = Quests;
+    const PATH: &'static str = QUESTS_PATH;
+
+    fn from_config(config: &Quests) -> Self {
+        Self::new(config.clone())
+    }
+}
+
+/// Something the player did that can count toward quest objectives.
+#[derive(Clone, Debug, PartialEq)]
+pub enum QuestActivity<'a> {
+    Gathered { resource: ResourceType, amount: u32 },
+    Crafted { recipe: &'a str },
+    TalkedTo { npc: &'a str },
+    /// The player is standing here.
+    At { position: Vec3 },
+}
+
+/// Where the player is with one quest.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum QuestState {
+    /// Started; progress per objective, in the quest's objective order.
+    Active { progress: Vec<u32> },
+    Completed,
+}
+
+/// Resource: The player's quests by id. Quests missing from the log have not started.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct QuestLog {
+    quests: BTreeMap<String, QuestState>,
+}
+
+impl QuestLog {
+    /// State of the quest with the given id, `None` if it has not started.
+    pub fn state(&self, id: &str) -> Option<&QuestState> {
+        self.quests.get(id)
+    }
+
+    /// Whether the quest with the given id is completed.
+    pub fn is_completed(&self, id: &str) -> bool {
+        self.state(id) == Some(&QuestState::Completed)
+    }
+
+    /// Starts every quest in `book` that has not started and whose prerequisites are
+    /// all completed. Returns the ids of the quests started, in file order.
+    pub fn start_available(&mut self, book: &QuestBook) -> Vec<String> {
+        let mut started = Vec::new();
+        for quest in book.iter() {
+            if self.quests.contains_key(&quest.id) || !quest.prerequisites.iter().all(|id| self.is_completed(id)) {
+                continue;
+            }
+            let progress = vec![0; quest.objectives.len()];
+            self.quests.insert(quest.id.clone(), QuestState::Active { progress });
+            started.push(quest.id.clone());
+        }
+        started
+    }
+
+    /// Counts `activity` toward the objectives of every active quest and completes
+    /// the quests whose objectives are all done. Returns the ids of the quests
+    /// completed, in file order.
+    pub fn record(&mut self, book: &QuestBook, activity: &QuestActivity) -> Vec<String> {
+        let mut completed = Vec::new();
+        for quest in book.iter() {
+            let Some(QuestState::Active { progress }) = self.quests.get_mut(&quest.id) else {
+                continue;
+            };
+            // Quests edited while active start their new objectives from scratch
+            progress.resize(quest.objectives.len(), 0);
+            for (objective, progress) in quest.objectives.iter().zip(progress.iter_mut()) {
+                *progress = objective.advance(*progress, activity);
+            }
+            if quest.objectives.iter().zip(progress.iter()).all(|(objective, progress)| *progress >= objective.required()) {
+                self.quests.insert(quest.id.clone(), QuestState::Completed);
+                completed.push(quest.id.clone());
+            }
+        }
+        completed
+    }
+}
@@ -0,0 +1,77 @@
+//! Data-driven crafting recipes, loaded from `*.recipes.ron` asset files.
+use bevy::prelude::*;
+use serde::Deserialize;
+use crate::components::config::{FromConfig, RonAsset};
+use crate::components::types::ResourceType;
+
+/// Path of the recipes file, relative to the assets folder.
+pub const RECIPES_PATH: &str = "data/default.recipes.ron";
+
+/// One way of turning inventory contents into other inventory contents.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Recipe {
+    pub id: String,
+    /// Name shown in the HUD and logs, also used for the recipe's outputs.
+    pub display_name: String,
+    /// Resources taken from the inventory when the craft starts.
+    pub inputs: Vec<(ResourceType, u32)>,
+    /// Resources added to the inventory when the craft completes.
+    pub outputs: Vec<(ResourceType, u32)>,
+    /// Seconds the craft takes once it reaches the front of the queue.
+    pub craft_seconds: f32,
+    /// Kind of `CraftingStation` the player must stand next to, if any.
+    #[serde(default)]
+    pub station: Option<String>,
+}
+
+/// Asset: All recipes from one recipes file.
+#[derive(Asset, TypePath, Clone, Debug, Deserialize)]
+pub struct Recipes {
+    pub recipes: Vec<Recipe>,
+}
+
+impl RonAsset for Recipes {
+    const EXTENSION: &'static str = "recipes.ron";
+}
+
+/// Resource: The recipes currently in use, in file order.
+/// Empty until the recipes file has loaded.
+#[derive(Resource, Default)]
+pub struct RecipeBook {
+    recipes: Vec<Recipe>,
+}
+
+impl RecipeBook {
+    pub fn new(recipes: Recipes) -> Self {
+        Self {
+            recipes: recipes.recipes,
+        }
+    }
+
+    /// Returns the recipe with the given id.
+    pub fn get(&self, id: &str) -> Option<&Recipe> {
+        self.recipes.iter().find(|recipe| recipe.id == id)
+    }
+
+    /// Iterates over all recipes in file order.
+    pub fn iter(&self) -> impl Iterator<Item = &Recipe> {
+        self.recipes.iter()
+    }
+
+    /// Returns the display name of the first recipe that makes `resource_type`, if any.
+    pub fn output_name(&self, resource_type: ResourceType) -> Option<&str> {
+        self.recipes
+            .iter()
+            .find(|recipe| recipe.outputs.iter().any(|(output, _)| *output == resource_type))
+            .map(|recipe| recipe.display_name.as_str())
+    }
+}
+
+impl FromConfig for RecipeBook {
+    type Config = Recipes;
+    const PATH: &'static str = RECIPES_PATH;
+
+    fn from_config(config: &Recipes) -> Self {
+        Self::new(config.clone())
+    }
+}
@@ -0,0 +1,198 @@
+//! Gathering skills, the XP they have earned and the level curve, loaded from
+//! `*.skills.ron` files, that turns XP into levels and levels into bonuses.
+use std::collections::BTreeMap;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::components::config::{FromConfig, RonAsset};
+
+/// Path of the skill curve file, relative to the assets folder.
+pub const SKILL_CURVE_PATH: &str = "data/default.skills.ron";
+
+/// A gathering skill. Each resource definition names the skill gathering it trains.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Skill {
+    Woodcutting,
+    Mining,
+}
+
+impl Skill {
+    /// Name shown in logs and the HUD.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Skill::Woodcutting => "Woodcutting",
+            Skill::Mining => "Mining",
+        }
+    }
+}
+
+/// The skill a resource trains, from its resource definition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub struct SkillRule {
+    pub skill: Skill,
+    /// XP earned by every successful hit.
+    pub xp: u32,
+    /// Lowest skill level that can gather the resource at all.
+    #[serde(default = "first_level")]
+    pub min_level: u32,
+}
+
+fn first_level() -> u32 {
+    1
+}
+
+/// Component: XP earned in each skill. Levels are worked out from XP with the
+/// [`SkillCurve`], so changing the curve re-levels everyone.
+#[derive(Component, Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Skills {
+    xp: BTreeMap<Skill, u32>,
+}
+
+impl Skills {
+    /// Total XP earned in `skill`.
+    pub fn xp(&self, skill: Skill) -> u32 {
+        self.xp.get(&skill).copied().unwrap_or(0)
+    }
+
+    /// Current level in `skill`.
+    pub fn level(&self, skill: Skill, curve: &SkillCurve) -> u32 {
+        curve.level_for(self.xp(skill))
+    }
+
+    /// Adds `amount` XP to `skill` and returns the new level if it went up.
+    pub fn add_xp(&mut self, skill: Skill, amount: u32, curve: &SkillCurve) -> Option<u32> {
+        let before = self.level(skill, curve);
+        let xp = self.xp.entry(skill).or_insert(0);
+        *xp = xp.saturating_add(amount);
+        let after = self.level(skill, curve);
+        (after > before).then_some(after)
+    }
+
+    /// Every skill with XP, in skill order.
+    pub fn iter(&self) -> impl Iterator<Item = (Skill, u32)> + '_ {
+        self.xp.iter().map(|(skill, xp)| (*skill, *xp))
+    }
+}
+
+/// Asset: Skill curve settings from one curve file. Settings left out keep their defaults.
+#[derive(Asset, TypePath, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SkillCurveConfig {
+    #[serde(default)]
+    pub base_xp: Option<u32>,
+    #[serde(default)]
+    pub growth: Option<f32>,
+    #[serde(default)]
+    pub max_level: Option<u32>,
+    #[serde(default)]
+    pub cooldown_reduction_per_level: Option<f32>,
+    #[serde(default)]
+    pub min_cooldown_factor: Option<f32>,
+    #[serde(default)]
+    pub levels_per_yield_bonus: Option<u32>,
+}
+
+impl RonAsset for SkillCurveConfig {
+    const EXTENSION: &'static str = "skills.ron";
+}
+
+/// Resource: How much XP each level takes and what levels are worth. Starts with
+/// the built-in defaults and is overlaid with the curve file once it loads.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct SkillCurve {
+    /// XP needed to go from level 1 to level 2.
+    pub base_xp: u32,
+    /// How much more XP each level needs than the one before.
+    pub growth: f32,
+    pub max_level: u32,
+    /// Share of the gathering cooldown each level above 1 takes off.
+    pub cooldown_reduction_per_level: f32,
+    /// Smallest share of the gathering cooldown that levels can bring it down to.
+    pub min_cooldown_factor: f32,
+    /// Levels between each extra unit of yield on hits that yield anything.
+    pub levels_per_yield_bonus: u32,
+}
+
+impl Default for SkillCurve {
+    fn default() -> Self {
+        Self {
+            base_xp: 50,
+            growth: 1.5,
+            max_level: 20,
+            cooldown_reduction_per_level: 0.03,
+            min_cooldown_factor: 0.5,
+            levels_per_yield_bonus: 5,
+        }
+    }
+}
+
+impl SkillCurve {
+    /// Overlays every setting `config` lists.
+    pub fn apply(&mut self, config: &SkillCurveConfig) {
+        if let Some(base_xp) = config.base_xp {
+            self.base_xp = base_xp;
+        }
+        if let Some(growth) = config.growth {
+            self.growth = growth;
+        }
+        if let Some(max_level) = config.max_level {
+            self.max_level = max_level.max(1);
+        }
+        if let Some(reduction) = config.cooldown_reduction_per_level {
+            self.cooldown_reduction_per_level = reduction;
+        }
+        if let Some(factor) = config.min_cooldown_factor {
+            self.min_cooldown_factor = factor;
+        }
+        if let Some(levels) = config.levels_per_yield_bonus {
+            self.levels_per_yield_bonus = levels;
+        }
+    }
+
+    /// XP needed to go from `level` to the next one, or `None` at the top level.
+    pub fn xp_to_next(&self, level: u32) -> Option<u32> {
+        (level < self.max_level).then(|| (self.base_xp as f32 * self.growth.powi(level as i32 - 1)).round() as u32)
+    }
+
+    /// Total XP needed to reach `level` from nothing.
+    pub fn xp_for_level(&self, level: u32) -> u32 {
+        (1..level.min(self.max_level)).filter_map(|level| self.xp_to_next(level)).sum()
+    }
+
+    /// Level reached with `xp` total XP.
+    pub fn level_for(&self, xp: u32) -> u32 {
+        let mut level = 1;
+        let mut needed = 0u32;
+        while let Some(step) = self.xp_to_next(level) {
+            needed = needed.saturating_add(step);
+            if xp < needed {
+                break;
+            }
+            level += 1;
+        }
+        level
+    }
+
+    /// Share of the gathering cooldown left at `level`.
+    pub fn cooldown_factor(&self, level: u32) -> f32 {
+        (1.0 - self.cooldown_reduction_per_level * level.saturating_sub(1) as f32).max(self.min_cooldown_factor)
+    }
+
+    /// Extra units added to every hit that yields anything at `level`.
+    pub fn yield_bonus(&self, level: u32) -> u32 {
+        if self.levels_per_yield_bonus == 0 {
+            return 0;
+        }
+        level.saturating_sub(1) / self.levels_per_yield_bonus
+    }
+}
+
+impl FromConfig for SkillCurve {
+    type Config = SkillCurveConfig;
+    const PATH: &'static str = SKILL_CURVE_PATH;
+
+    /// The built-in defaults overlaid with `config`.
+    fn from_config(config: &SkillCurveConfig) -> Self {
+        let mut curve = Self::default();
+        curve.apply(config);
+        curve
+    }
+}
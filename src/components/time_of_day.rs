@@ -0,0 +1,114 @@
+//! The in-game clock: the hour of the day, how fast it runs and whether it is day or night.
+use std::f32::consts::TAU;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Real seconds one in-game day takes unless the embedding app inserts its own clock.
+pub const DEFAULT_DAY_LENGTH_SECONDS: f32 = 600.0;
+/// Hour a new game starts at.
+pub const START_HOUR: f32 = 8.0;
+/// Hour the sun rises; day starts.
+pub const DAWN_HOUR: f32 = 6.0;
+/// Hour the sun sets; night starts.
+pub const DUSK_HOUR: f32 = 18.0;
+
+/// Half of the day.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DayPeriod {
+    /// From dawn to dusk.
+    Day,
+    /// From dusk to dawn.
+    Night,
+}
+
+/// Resource: The in-game clock. Advances with the fixed timestep unless paused.
+///
+/// Insert one before adding the game plugin to change the day length or starting
+/// hour; otherwise the default clock is used.
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct TimeOfDay {
+    /// Days passed since the game started.
+    day: u32,
+    /// Hour of the day, from 0 up to 24.
+    hour: f32,
+    /// Real seconds one in-game day takes.
+    pub day_length_seconds: f32,
+    /// Stops the clock, e.g. to look at one time of day.
+    pub paused: bool,
+}
+
+impl Default for TimeOfDay {
+    fn default() -> Self {
+        Self::new(START_HOUR)
+    }
+}
+
+impl TimeOfDay {
+    /// A clock on day 0 at `hour`, with the default day length.
+    pub fn new(hour: f32) -> Self {
+        let mut time = Self { day: 0, hour: 0.0, day_length_seconds: DEFAULT_DAY_LENGTH_SECONDS, paused: false };
+        time.set_hour(hour);
+        time
+    }
+
+    /// Hour of the day, from 0 up to 24.
+    pub fn hour(&self) -> f32 {
+        self.hour
+    }
+
+    /// Days passed since the game started.
+    pub fn day(&self) -> u32 {
+        self.day
+    }
+
+    /// Jumps to `hour` of the current day, wrapping hours outside 0 to 24 into it.
+    pub fn set_hour(&mut self, hour: f32) {
+        self.hour = hour.rem_euclid(24.0);
+    }
+
+    /// Moves the clock on by `hours`, counting the days that pass.
+    pub fn advance_hours(&mut self, hours: f32) {
+        let total = self.hour + hours.max(0.0);
+        self.day += (total / 24.0).floor() as u32;
+        self.hour = total.rem_euclid(24.0);
+    }
+
+    /// Moves the clock on by `seconds` of real time at the current day length.
+    /// Does nothing while paused.
+    pub fn advance(&mut self, seconds: f32) {
+        if !self.paused && self.day_length_seconds > 0.0 {
+            self.advance_hours(seconds / self.day_length_seconds * 24.0);
+        }
+    }
+
+    /// Whether it is day or night.
+    pub fn period(&self) -> DayPeriod {
+        if (DAWN_HOUR..DUSK_HOUR).contains(&self.hour) {
+            DayPeriod::Day
+        } else {
+            DayPeriod::Night
+        }
+    }
+
+    /// Whether it is between dusk and dawn.
+    pub fn is_night(&self) -> bool {
+        self.period() == DayPeriod::Night
+    }
+
+    /// Angle of the sun around the sky in radians: 0 at dawn, a quarter turn at noon.
+    pub fn sun_angle(&self) -> f32 {
+        let day_hours = DUSK_HOUR - DAWN_HOUR;
+        // Day and night each take half a turn, however long they are
+        if self.hour >= DAWN_HOUR && self.hour < DUSK_HOUR {
+            (self.hour - DAWN_HOUR) / day_hours * TAU / 2.0
+        } else {
+            let since_dusk = (self.hour - DUSK_HOUR).rem_euclid(24.0);
+            TAU / 2.0 + since_dusk / (24.0 - day_hours) * TAU / 2.0
+        }
+    }
+
+    /// How much daylight there is, from 0 at night to 1 at noon.
+    pub fn daylight(&self) -> f32 {
+        self.sun_angle().sin().max(0.0)
+    }
+}
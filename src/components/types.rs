@@ -1,13 +1,21 @@
 //! Shared components and helper types for the resource gathering game.
 use bevy::prelude::*;
-use std::collections::HashMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, OnceLock};
+use crate::components::inventory::Inventory;
 
 /// Marker component for the player entity.
 #[derive(Component)]
 pub struct Player {
     pub speed: f32,
     pub gathering_range: f32,
+    /// Counts down from the last gather hit. Its duration is set by whatever made that hit.
     pub gathering_cooldown: Timer,
+    /// Damage dealt to a node's health per bare-handed gather action.
+    pub gather_damage: u32,
+    /// Seconds between bare-handed gather actions.
+    pub gather_seconds: f32,
 }
 
 /// Stores the world position for entities.
@@ -24,44 +32,130 @@ pub struct Gatherable {
     pub respawn_timer: Option<Timer>,
 }
 
+impl Gatherable {
+    /// Whether the node has been gathered and is waiting to respawn.
+    pub fn is_depleted(&self) -> bool {
+        self.respawn_timer.is_some()
+    }
+}
+
 /// Marker for resource node entities.
 #[derive(Component)]
 pub struct ResourceNode;
 
+/// A workbench, furnace or similar that recipes can require the player to stand next to.
+#[derive(Component, Clone, Debug)]
+pub struct CraftingStation {
+    /// Kind of station, matched against a recipe's `station`.
+    pub kind: String,
+}
+
+/// A character the player can talk to.
+#[derive(Component, Clone, Debug)]
+pub struct Npc {
+    /// Id that quest objectives refer to.
+    pub id: String,
+    /// Name shown in logs.
+    pub name: String,
+}
+
 /// Marker for the inventory UI text entity.
 #[derive(Component)]
 pub struct InventoryText;
 
-/// Resource: Player's inventory and stack size limit.
-#[derive(Resource)]
-pub struct PlayerInventory {
-    pub resources: HashMap<ResourceType, u32>,
-    pub max_stack_size: u32,
+/// Resource: The player's inventory.
+///
+/// Insert one before adding the game plugin to change the slot count; otherwise
+/// the default inventory is used.
+#[derive(Resource, Clone, Debug, Default, Deref, DerefMut)]
+pub struct PlayerInventory(pub Inventory);
+
+/// Resource: Seed the world is generated from. The same seed always produces the same map.
+///
+/// Insert it before adding the game plugin to pin the map; otherwise a random seed is used.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WorldSeed(pub u64);
+
+impl WorldSeed {
+    /// Reads the seed from a `--seed <n>` or `--seed=<n>` command line argument.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Option<Self> {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let value = match arg.strip_prefix("--seed") {
+                Some("") => args.next()?,
+                Some(rest) => match rest.strip_prefix('=') {
+                    Some(value) => value.to_owned(),
+                    None => continue,
+                },
+                None => continue,
+            };
+            return value.parse().ok().map(WorldSeed);
+        }
+        None
+    }
+}
+
+/// When gathering a node pays out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum YieldRule {
+    /// Award this many units on every hit.
+    PerHit(u32),
+    /// Award this many units on the hit that depletes the node.
+    OnDepletion(u32),
 }
 
 /// Resource: Handles to all loaded game assets.
 #[derive(Resource)]
 pub struct GameAssets {
     pub player_model: Handle<Scene>,
-    pub tree_models: Vec<Handle<Scene>>,
-    pub rock_model: Handle<Scene>,
+    /// Scene for each resource type, loaded from its definition's model path.
+    pub resource_models: HashMap<ResourceType, Handle<Scene>>,
 }
 
-/// Enum for all resource types in the game.
-#[derive(Component, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum ResourceType {
-    Wood,
-    Stone,
-    Ore,
-}
+/// Identifies a kind of resource by the `id` used in the resource definitions file.
+///
+/// The set of resource types is data-driven (see `ResourceRegistry`); the
+/// constants below are the ids of the kinds shipped with the game.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ResourceType(&'static str);
 
 impl ResourceType {
-    /// Returns the display name for each resource type.
-    pub fn get_name(&self) -> &'static str {
-        match self {
-            ResourceType::Wood => "Wood",
-            ResourceType::Stone => "Stone",
-            ResourceType::Ore => "Ore",
-        }
+    pub const WOOD: ResourceType = ResourceType("wood");
+    pub const STONE: ResourceType = ResourceType("stone");
+    pub const ORE: ResourceType = ResourceType("ore");
+
+    /// Returns the resource type with the given id.
+    pub fn new(id: &str) -> Self {
+        ResourceType(intern(id))
+    }
+
+    /// Returns the id of this resource type.
+    pub fn id(&self) -> &'static str {
+        self.0
+    }
+}
+
+impl Serialize for ResourceType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0)
     }
-} 
\ No newline at end of file
+}
+
+impl<'de> Deserialize<'de> for ResourceType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let id = String::deserialize(deserializer)?;
+        Ok(ResourceType::new(&id))
+    }
+}
+
+/// Returns a `'static` copy of `id`, leaking each distinct id only once.
+fn intern(id: &str) -> &'static str {
+    static IDS: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    let mut ids = IDS.get_or_init(Default::default).lock().unwrap();
+    if let Some(interned) = ids.get(id) {
+        return interned;
+    }
+    let interned: &'static str = Box::leak(id.to_owned().into_boxed_str());
+    ids.insert(interned);
+    interned
+}
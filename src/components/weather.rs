@@ -0,0 +1,214 @@
+//! Weather: the kinds of weather and their effects, loaded from `*.weather.ron` asset
+//! files, and the seeded state machine that moves between them.
+use std::collections::HashMap;
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use crate::components::config::{FromConfig, RonAsset};
+use crate::components::types::ResourceType;
+
+/// Path of the weather file, relative to the assets folder.
+pub const WEATHER_PATH: &str = "data/default.weather.ron";
+/// Seconds of clear weather a new game starts with.
+pub const START_CLEAR_SECONDS: f32 = 120.0;
+
+/// A kind of weather.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WeatherKind {
+    Clear,
+    Rain,
+    Storm,
+    Fog,
+}
+
+impl WeatherKind {
+    /// Name shown in logs and the HUD.
+    pub fn name(&self) -> &'static str {
+        match self {
+            WeatherKind::Clear => "Clear",
+            WeatherKind::Rain => "Rain",
+            WeatherKind::Storm => "Storm",
+            WeatherKind::Fog => "Fog",
+        }
+    }
+}
+
+/// How a kind of weather changes the world. Every field defaults to no change.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct WeatherEffects {
+    /// Factor on the player's walking speed.
+    pub move_speed: f32,
+    /// Factor on how fast depleted nodes of each listed type count down to respawning.
+    pub regrowth: HashMap<ResourceType, f32>,
+    /// Average number of nodes knocked down per minute, among the types that can fall.
+    pub knockdowns_per_minute: f32,
+    /// Distance at which fog hides everything, if there is fog.
+    pub fog_distance: Option<f32>,
+    /// Factor on the sun and ambient light.
+    pub light: f32,
+}
+
+impl Default for WeatherEffects {
+    fn default() -> Self {
+        Self {
+            move_speed: 1.0,
+            regrowth: HashMap::new(),
+            knockdowns_per_minute: 0.0,
+            fog_distance: None,
+            light: 1.0,
+        }
+    }
+}
+
+impl WeatherEffects {
+    /// Regrowth factor for depleted nodes of `resource`.
+    pub fn regrowth(&self, resource: ResourceType) -> f32 {
+        self.regrowth.get(&resource).copied().unwrap_or(1.0)
+    }
+}
+
+/// One kind of weather: how long it lasts, what follows it and what it does.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct WeatherDefinition {
+    pub kind: WeatherKind,
+    /// Shortest and longest time the weather lasts, in seconds.
+    pub min_seconds: f32,
+    pub max_seconds: f32,
+    /// Weather that can follow, each with its weight relative to the others.
+    /// Weather with nothing to follow it repeats.
+    #[serde(default)]
+    pub next: Vec<(WeatherKind, f32)>,
+    #[serde(default)]
+    pub effects: WeatherEffects,
+}
+
+/// Asset: All weather from one weather file.
+#[derive(Asset, TypePath, Clone, Debug, Deserialize)]
+pub struct WeatherDefinitions {
+    pub weather: Vec<WeatherDefinition>,
+}
+
+impl RonAsset for WeatherDefinitions {
+    const EXTENSION: &'static str = "weather.ron";
+}
+
+/// Resource: The weather definitions currently in use.
+/// Empty until the weather file has loaded, which leaves the weather clear.
+#[derive(Resource, Default)]
+pub struct WeatherRegistry {
+    definitions: Vec<WeatherDefinition>,
+}
+
+impl WeatherRegistry {
+    pub fn new(definitions: WeatherDefinitions) -> Self {
+        Self { definitions: definitions.weather }
+    }
+
+    /// Returns the definition of a kind of weather.
+    pub fn get(&self, kind: WeatherKind) -> Option<&WeatherDefinition> {
+        self.definitions.iter().find(|definition| definition.kind == kind)
+    }
+
+    /// Returns the definition of a kind of weather, for tweaking at runtime.
+    pub fn get_mut(&mut self, kind: WeatherKind) -> Option<&mut WeatherDefinition> {
+        self.definitions.iter_mut().find(|definition| definition.kind == kind)
+    }
+
+    /// Effects of a kind of weather; no effects if it has no definition.
+    pub fn effects(&self, kind: WeatherKind) -> WeatherEffects {
+        self.get(kind).map(|definition| definition.effects.clone()).unwrap_or_default()
+    }
+}
+
+impl FromConfig for WeatherRegistry {
+    type Config = WeatherDefinitions;
+    const PATH: &'static str = WEATHER_PATH;
+
+    fn from_config(config: &WeatherDefinitions) -> Self {
+        Self::new(config.clone())
+    }
+}
+
+/// Resource: The current weather and the seeded random draws that decide what comes next.
+///
+/// Every draw comes from the seed and the number of draws made so far, so the same
+/// seed always gives the same weather, and a saved game carries on exactly as it would have.
+/// Insert one before adding the game plugin to pick the seed; otherwise it is
+/// derived from the world seed.
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Weather {
+    kind: WeatherKind,
+    /// Seconds until the weather changes.
+    remaining_seconds: f32,
+    seed: u64,
+    /// Draws made so far.
+    draws: u64,
+}
+
+impl Weather {
+    /// Clear weather for [`START_CLEAR_SECONDS`], with draws from `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self { kind: WeatherKind::Clear, remaining_seconds: START_CLEAR_SECONDS, seed, draws: 0 }
+    }
+
+    pub fn kind(&self) -> WeatherKind {
+        self.kind
+    }
+
+    /// Seconds until the weather changes.
+    pub fn remaining_seconds(&self) -> f32 {
+        self.remaining_seconds
+    }
+
+    /// Switches to `kind` for `seconds`.
+    pub fn set(&mut self, kind: WeatherKind, seconds: f32) {
+        self.kind = kind;
+        self.remaining_seconds = seconds;
+    }
+
+    /// Random generator for the next draw.
+    pub fn rng(&mut self) -> ChaCha8Rng {
+        // Spread consecutive draws far apart in seed space
+        let rng = ChaCha8Rng::seed_from_u64(self.seed ^ self.draws.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        self.draws += 1;
+        rng
+    }
+
+    /// Counts `seconds` down and, once the weather has run its course, moves on to a
+    /// weighted random pick of what follows it. Returns the new weather if it changed
+    /// kind. Weather without a definition stays until set.
+    pub fn advance(&mut self, seconds: f32, registry: &WeatherRegistry) -> Option<WeatherKind> {
+        self.remaining_seconds -= seconds;
+        if self.remaining_seconds > 0.0 {
+            return None;
+        }
+        let definition = registry.get(self.kind)?;
+        let mut rng = self.rng();
+        let next = pick_weighted(&mut rng, &definition.next).unwrap_or(self.kind);
+        let duration = registry.get(next).map_or(0.0, |next| {
+            rng.gen_range(next.min_seconds..=next.max_seconds.max(next.min_seconds))
+        });
+        let changed = next != self.kind;
+        self.set(next, duration);
+        changed.then_some(next)
+    }
+}
+
+/// Picks an entry with probability proportional to its weight. `None` if no weight is positive.
+fn pick_weighted(rng: &mut impl Rng, choices: &[(WeatherKind, f32)]) -> Option<WeatherKind> {
+    let total: f32 = choices.iter().map(|(_, weight)| weight.max(0.0)).sum();
+    if total <= 0.0 {
+        return None;
+    }
+    let mut roll = rng.gen_range(0.0..total);
+    for &(kind, weight) in choices {
+        let weight = weight.max(0.0);
+        if roll < weight {
+            return Some(kind);
+        }
+        roll -= weight;
+    }
+    choices.iter().rev().find(|(_, weight)| *weight > 0.0).map(|(kind, _)| *kind)
+}
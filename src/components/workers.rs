@@ -0,0 +1,118 @@
+//! Worker NPCs that gather on their own, and the stockpiles they bring their loads to.
+use bevy::prelude::*;
+use crate::components::inventory::Inventory;
+use crate::components::types::ResourceType;
+
+/// Units a worker carries before heading back to a stockpile.
+pub const WORKER_LOAD_SIZE: u32 = 10;
+/// Slots in a stockpile.
+pub const STOCKPILE_SLOTS: usize = 24;
+/// Units of one item a stockpile slot holds.
+pub const STOCKPILE_STACK_SIZE: u32 = 100;
+/// Seconds a worker with a job waits after being stopped (e.g. by a full stockpile)
+/// before it tries again.
+pub const WORKER_RETRY_SECONDS: f32 = 5.0;
+
+/// What a worker is doing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WorkerState {
+    /// No job, or nothing it can do about it until its retry cooldown runs out.
+    Idle,
+    /// Looking for the nearest free node of its job's type.
+    Seek,
+    /// Walking to a node.
+    MoveTo(Entity),
+    /// Hitting a node until it is depleted or the load is full.
+    Gather(Entity),
+    /// Bringing its load to the nearest stockpile.
+    Return,
+}
+
+impl WorkerState {
+    /// Name shown in the HUD.
+    pub fn name(&self) -> &'static str {
+        match self {
+            WorkerState::Idle => "idle",
+            WorkerState::Seek => "looking for work",
+            WorkerState::MoveTo(_) => "walking",
+            WorkerState::Gather(_) => "gathering",
+            WorkerState::Return => "hauling",
+        }
+    }
+
+    /// The node the worker is heading for or hitting, if any.
+    pub fn target(&self) -> Option<Entity> {
+        match *self {
+            WorkerState::MoveTo(node) | WorkerState::Gather(node) => Some(node),
+            _ => None,
+        }
+    }
+}
+
+/// A worker NPC: gathers nodes of its job's type by the same rules as the player
+/// and hauls what it carries to the nearest stockpile.
+#[derive(Component, Clone, Debug)]
+pub struct Worker {
+    /// Resource type the worker gathers, `None` for no job.
+    pub job: Option<ResourceType>,
+    pub state: WorkerState,
+    pub speed: f32,
+    pub gathering_range: f32,
+    /// Counts down from the last gather hit, like the player's.
+    pub gathering_cooldown: Timer,
+    /// Counts down from being stopped while it has a job; it goes back to work once finished.
+    pub retry_cooldown: Timer,
+    /// Damage dealt to a node's health per bare-handed hit.
+    pub gather_damage: u32,
+    /// Seconds between bare-handed hits.
+    pub gather_seconds: f32,
+    /// What the worker is carrying.
+    pub load: Inventory,
+}
+
+impl Default for Worker {
+    fn default() -> Self {
+        Self {
+            job: None,
+            state: WorkerState::Idle,
+            speed: 3.5,
+            gathering_range: 2.0,
+            gathering_cooldown: Timer::from_seconds(0.0, TimerMode::Once),
+            retry_cooldown: Timer::from_seconds(WORKER_RETRY_SECONDS, TimerMode::Once),
+            gather_damage: 50,
+            gather_seconds: 1.2,
+            load: Inventory::new(1, WORKER_LOAD_SIZE),
+        }
+    }
+}
+
+impl Worker {
+    /// Gives the worker `job` and sends it off to look for work, or idles it without one.
+    pub fn assign(&mut self, job: Option<ResourceType>) {
+        self.job = job;
+        self.state = if job.is_some() { WorkerState::Seek } else { WorkerState::Idle };
+    }
+
+    /// Stops the worker because something is in its way, until its retry cooldown runs out.
+    pub fn stop(&mut self) -> WorkerState {
+        self.retry_cooldown.reset();
+        WorkerState::Idle
+    }
+
+    /// Units the worker is carrying.
+    pub fn carried(&self) -> u32 {
+        self.load.totals().values().sum()
+    }
+}
+
+/// A place workers bring their loads to.
+#[derive(Component, Clone, Debug)]
+pub struct Stockpile {
+    pub inventory: Inventory,
+}
+
+impl Default for Stockpile {
+    fn default() -> Self {
+        Self { inventory: Inventory::new(STOCKPILE_SLOTS, STOCKPILE_STACK_SIZE) }
+    }
+}
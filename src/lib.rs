@@ -0,0 +1,145 @@
+//! 3D resource gathering game as a reusable Bevy library.
+//! Exposes [`GamePlugin`] for windowed apps and [`HeadlessGamePlugin`] for
+//! running the gameplay systems on `MinimalPlugins` without a window or renderer.
+// Bevy systems take one argument per system parameter
+#![allow(clippy::too_many_arguments)]
+pub mod components;
+pub mod systems;
+pub mod testing;
+
+use bevy::prelude::*;
+use bevy::scene::ScenePlugin;
+use bevy::time::Fixed;
+use bevy_rapier3d::prelude::*;
+use std::collections::HashMap;
+
+use components::types::{PlayerInventory, GameAssets, WorldSeed};
+use systems::{
+    configure_game_sets,
+    GameSet,
+    input::GameInputPlugin,
+    camera::GameCameraPlugin,
+    resources::GameResourcesPlugin,
+    entities::GameEntitiesPlugin,
+    ui::GameUiPlugin,
+    movement::GameMovementPlugin,
+    save::GameSavePlugin,
+    crafting::GameCraftingPlugin,
+    replay::GameReplayPlugin,
+    skills::GameSkillsPlugin,
+    npcs::GameNpcPlugin,
+    quests::GameQuestsPlugin,
+    daynight::GameDayNightPlugin,
+    weather::GameWeatherPlugin,
+    workers::GameWorkersPlugin,
+    navigation::GameNavigationPlugin,
+};
+
+/// Adds the full game on top of `DefaultPlugins`: physics, world, gameplay and HUD.
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        add_physics(app);
+        // .add_plugins(RapierDebugRenderPlugin::default()) // Disabled for performance
+        insert_game_resources(app);
+        configure_game_sets(app);
+        app.insert_resource(GameAssets {
+            player_model: Handle::default(),
+            resource_models: HashMap::new(),
+        });
+        app.add_plugins((
+            (
+                GameInputPlugin,
+                GameCameraPlugin,
+                GameResourcesPlugin,
+                GameEntitiesPlugin::default(),
+                GameUiPlugin,
+                GameMovementPlugin,
+                GameSavePlugin,
+                GameCraftingPlugin,
+                GameReplayPlugin,
+            ),
+            GameSkillsPlugin,
+            GameNpcPlugin,
+            GameQuestsPlugin,
+            GameDayNightPlugin,
+            GameWeatherPlugin,
+            GameWorkersPlugin,
+            GameNavigationPlugin,
+        ));
+    }
+}
+
+/// Adds the gameplay without a window or renderer, on top of `MinimalPlugins`.
+///
+/// Brings in the few non-rendering engine plugins the game and Rapier rely on
+/// (input, transforms, hierarchy, assets and scenes), spawns the world without
+/// meshes, materials or lights, and leaves out the HUD.
+pub struct HeadlessGamePlugin;
+
+impl Plugin for HeadlessGamePlugin {
+    fn build(&self, app: &mut App) {
+        add_headless_support(app);
+        add_physics(app);
+        insert_game_resources(app);
+        configure_game_sets(app);
+        app.add_plugins((
+            GameInputPlugin,
+            GameCameraPlugin,
+            GameResourcesPlugin,
+            GameEntitiesPlugin { headless: true },
+            GameMovementPlugin,
+            GameSavePlugin,
+            GameCraftingPlugin,
+            GameReplayPlugin,
+            GameSkillsPlugin,
+            GameNpcPlugin,
+            GameQuestsPlugin,
+            GameDayNightPlugin,
+            GameWeatherPlugin,
+            GameWorkersPlugin,
+            GameNavigationPlugin,
+        ));
+    }
+}
+
+/// Adds the non-rendering engine plugins the game and Rapier need on top of `MinimalPlugins`.
+pub(crate) fn add_headless_support(app: &mut App) {
+    app.add_plugins((
+        bevy::input::InputPlugin,
+        TransformPlugin,
+        HierarchyPlugin,
+        AssetPlugin::default(),
+        ScenePlugin,
+    ))
+    // Rapier's async collider systems read mesh assets even when unused
+    .init_asset::<Mesh>();
+}
+
+/// Adds Rapier, stepping once per fixed timestep right after movement, so physics
+/// advances in lockstep with the gameplay systems and input replays are exact.
+pub(crate) fn add_physics(app: &mut App) {
+    let timestep = app.world.resource::<Time<Fixed>>().timestep().as_secs_f32();
+    app.insert_resource(RapierConfiguration {
+        timestep_mode: TimestepMode::Fixed { dt: timestep, substeps: 1 },
+        ..default()
+    })
+    .add_plugins(RapierPhysicsPlugin::<NoUserData>::default().in_fixed_schedule());
+    // Gathering and the camera see this tick's positions
+    app.configure_sets(
+        FixedUpdate,
+        PhysicsSet::SyncBackend.after(GameSet::Movement).before(GameSet::Gathering),
+    );
+    app.configure_sets(FixedUpdate, PhysicsSet::Writeback.before(GameSet::Gathering));
+}
+
+/// Inserts the resources shared by the windowed and headless games.
+pub(crate) fn insert_game_resources(app: &mut App) {
+    // Keep an inventory set up by the embedding app
+    app.init_resource::<PlayerInventory>();
+    // Keep a seed chosen by the embedding app or command line
+    if !app.world.contains_resource::<WorldSeed>() {
+        app.insert_resource(WorldSeed(rand::random()));
+    }
+}
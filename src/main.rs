@@ -1,50 +1,53 @@
 //! Entry point for the 3D resource gathering game using Bevy.
-//! Sets up plugins, resources, and registers all systems.
-mod components;
-mod systems;
-
+//! Sets up the engine plugins and adds the game plugin.
 use bevy::prelude::*;
 use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
-use bevy_rapier3d::prelude::*;
-use std::collections::HashMap;
+use std::path::Path;
 
-use components::types::{PlayerInventory, GameAssets};
-use systems::{
-    input::register as register_input,
-    camera::register as register_camera,
-    resources::register as register_resources,
-    entities::register as register_entities,
-    ui::register as register_ui,
-    movement::register as register_movement,
-};
+use bevy_game_demo::components::types::WorldSeed;
+use bevy_game_demo::systems::replay::replay_file;
+use bevy_game_demo::GamePlugin;
 
 fn main() {
+    // Replay a recorded session headlessly instead of playing when started with --replay <file>
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(path) = args.iter().position(|arg| arg == "--replay").and_then(|index| args.get(index + 1)) {
+        std::process::exit(run_replay(Path::new(path)));
+    }
+
     // Create the Bevy app
     let mut app = App::new();
     // Add core plugins
     app.add_plugins(DefaultPlugins)
-        .add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
-        // .add_plugins(RapierDebugRenderPlugin::default()) // Disabled for performance
         // Add diagnostic plugins
         .add_plugins(FrameTimeDiagnosticsPlugin)
         .add_plugins(LogDiagnosticsPlugin::default());
-    // Insert game resources
-    app.insert_resource(PlayerInventory {
-        resources: HashMap::new(),
-        max_stack_size: 10,
-    })
-    .insert_resource(GameAssets {
-        player_model: Handle::default(),
-        tree_models: Vec::new(),
-        rock_model: Handle::default(),
-    });
-    // Register all system modules
-    register_input(&mut app);
-    register_camera(&mut app);
-    register_resources(&mut app);
-    register_entities(&mut app);
-    register_ui(&mut app);
-    register_movement(&mut app);
+    // Generate the same map every run when started with --seed <n>
+    if let Some(seed) = WorldSeed::from_args(args) {
+        app.insert_resource(seed);
+    }
+    // Add the game itself: physics, resources and all system modules
+    app.add_plugins(GamePlugin);
     // Run the game
     app.run();
 }
+
+/// Replays the recording at `path` and returns the process exit code: 0 if it reproduces.
+fn run_replay(path: &Path) -> i32 {
+    match replay_file(path) {
+        Ok(report) if report.matches() => {
+            println!("Replay matches the recording");
+            0
+        }
+        Ok(report) => {
+            println!("Replay does not match the recording");
+            println!("  expected: {:?}", report.expected);
+            println!("  actual:   {:?}", report.actual);
+            1
+        }
+        Err(err) => {
+            eprintln!("Could not replay {}: {}", path.display(), err);
+            2
+        }
+    }
+}
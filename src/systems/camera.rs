@@ -1,57 +1,202 @@
-//! Third-person camera system: follows and orbits the player, supports mouse-based rotation and zoom.
+//! Camera system: a third-person orbit that follows the player without clipping into the
+//! world, a first-person view and a top-down view with edge panning, with mouse-based
+//! rotation and zoom and smooth transitions between modes.
 use bevy::prelude::*;
 use bevy::input::mouse::MouseWheel;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::components::bindings::Action;
+use crate::components::camera_settings::CameraSettings;
 use crate::components::types::Player;
+use crate::systems::config::add_config;
+use crate::systems::input::Actions;
+use crate::systems::GameSet;
 
+/// How far in front of whatever blocks its view the camera stops.
+pub const CAMERA_COLLISION_MARGIN: f32 = 0.3;
+/// Closest the camera is pulled in towards the player when blocked.
+pub const CAMERA_MIN_DISTANCE: f32 = 0.5;
+/// Height of the first-person camera above the player's center.
+pub const FIRST_PERSON_EYE_HEIGHT: f32 = 0.4;
+/// Downward tilt of the top-down camera in radians. Short of straight down, so the
+/// view still has a forward direction for camera-relative movement.
+pub const TOP_DOWN_PITCH: f32 = 1.1;
+/// Width in pixels of the window border that pans the top-down camera.
+const EDGE_PAN_MARGIN: f32 = 20.0;
+/// Top-down panning speed, in camera heights per second.
+const EDGE_PAN_SPEED: f32 = 1.0;
+
+/// How the main camera frames the player.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CameraMode {
+    /// Orbits the player at `distance`, tilted down by `pitch`.
+    #[default]
+    Orbit,
+    /// Looks out from the player's head.
+    FirstPerson,
+    /// Looks down on `focus` from `top_down_height`.
+    TopDown,
+}
+
+impl CameraMode {
+    /// The mode the cycle camera action switches to from this one.
+    pub fn next(self) -> Self {
+        match self {
+            CameraMode::Orbit => CameraMode::FirstPerson,
+            CameraMode::FirstPerson => CameraMode::TopDown,
+            CameraMode::TopDown => CameraMode::Orbit,
+        }
+    }
+}
+
+/// The camera rig. `angle` is the yaw shared by every mode, so switching modes
+/// keeps the direction the player thinks of as forward. `pitch` tilts the orbit
+/// and first-person views down (positive) or up (negative).
 #[derive(Component)]
 pub struct MainCamera {
+    pub mode: CameraMode,
     pub distance: f32,
     pub angle: f32,
-    pub height: f32,
+    pub pitch: f32,
+    /// Ground point the top-down camera looks at. Set to the player when switching
+    /// to top-down, then moved by edge panning.
+    pub focus: Vec3,
+    pub top_down_height: f32,
+}
+
+impl Default for MainCamera {
+    fn default() -> Self {
+        Self {
+            mode: CameraMode::Orbit,
+            distance: 5.5,
+            angle: 0.0,
+            pitch: 0.45,
+            focus: Vec3::ZERO,
+            top_down_height: 15.0,
+        }
+    }
+}
+
+impl MainCamera {
+    /// Where the orbit camera sits relative to the player, with nothing in the way.
+    pub fn orbit_offset(&self) -> Vec3 {
+        let (sin, cos) = self.angle.sin_cos();
+        let (pitch_sin, pitch_cos) = self.pitch.sin_cos();
+        self.distance * Vec3::new(pitch_cos * sin, pitch_sin, pitch_cos * cos)
+    }
+
+    /// Where the camera heads in first-person and top-down modes. The rotation
+    /// depends only on `angle`, wherever the camera is panned to.
+    pub fn view(&self, player: Vec3) -> Option<Transform> {
+        let yaw = Quat::from_rotation_y(self.angle);
+        match self.mode {
+            CameraMode::Orbit => None,
+            CameraMode::FirstPerson => Some(
+                Transform::from_translation(player + Vec3::Y * FIRST_PERSON_EYE_HEIGHT)
+                    .with_rotation(yaw * Quat::from_rotation_x(-self.pitch)),
+            ),
+            CameraMode::TopDown => {
+                let rotation = yaw * Quat::from_rotation_x(-TOP_DOWN_PITCH);
+                let back = rotation * Vec3::Z * (self.top_down_height / TOP_DOWN_PITCH.sin());
+                Some(Transform::from_translation(self.focus + back).with_rotation(rotation))
+            }
+        }
+    }
+
+    /// Ground directions of screen right and screen up for this camera's yaw.
+    pub fn ground_axes(&self) -> (Vec3, Vec3) {
+        let (sin, cos) = self.angle.sin_cos();
+        (Vec3::new(cos, 0.0, -sin), Vec3::new(-sin, 0.0, -cos))
+    }
 }
 
-pub fn register(app: &mut App) {
-    // Run camera follow in FixedUpdate for smooth movement
-    app.add_systems(FixedUpdate, camera_follow)
-        // Keep camera control in Update for responsive input
-        .add_systems(Update, camera_control);
+/// Plugin for the camera modes, their mouse controls and the camera settings file.
+pub struct GameCameraPlugin;
+
+impl Plugin for GameCameraPlugin {
+    fn build(&self, app: &mut App) {
+        add_config::<CameraSettings>(app);
+        app.init_resource::<CameraSettings>();
+        // Run camera follow in FixedUpdate for smooth movement
+        app.add_systems(FixedUpdate, camera_follow.in_set(GameSet::Camera))
+            // Keep camera control in Update for responsive input
+            .add_systems(Update, camera_control);
+    }
 }
 
-/// Smoothly follows the player and orbits based on camera angle and distance.
+/// Smoothly moves the camera to where its mode puts it, so switching modes eases
+/// from one view to the other.
 fn camera_follow(
     time: Res<Time>,
-    player_query: Query<&Transform, With<Player>>,
+    settings: Res<CameraSettings>,
+    rapier_context: Res<RapierContext>,
+    player_query: Query<(Entity, &Transform), With<Player>>,
     mut camera_query: Query<(&mut Transform, &MainCamera), Without<Player>>,
 ) {
-    let (player_transform, (mut camera_transform, camera)) = match (player_query.get_single(), camera_query.get_single_mut()) {
+    let ((player, player_transform), (mut camera_transform, camera)) = match (player_query.get_single(), camera_query.get_single_mut()) {
         (Ok(p), Ok(c)) => (p, c),
         _ => return,
     };
 
-    let target_pos = player_transform.translation;
-    
-    // Cache sin/cos calculations
-    let angle_rad = camera.angle;
-    let (sin, cos) = angle_rad.sin_cos();
-    
-    // Calculate desired position in a single operation
-    let offset = Vec3::new(
-        camera.distance * sin,
-        camera.height,
-        camera.distance * cos,
-    );
-    let desired_pos = target_pos + offset;
-
     // Use a fixed lerp speed for consistency
-    let lerp_speed = 8.0 * time.delta_seconds();
+    let lerp_speed = (settings.smoothing * time.delta_seconds()).min(1.0);
+    match camera.view(player_transform.translation) {
+        Some(view) => {
+            camera_transform.translation = camera_transform.translation.lerp(view.translation, lerp_speed);
+            camera_transform.rotation = camera_transform.rotation.slerp(view.rotation, lerp_speed);
+        }
+        None => follow_orbit(&rapier_context, player, player_transform.translation, camera, &mut camera_transform, lerp_speed),
+    }
+}
+
+/// Orbits the player based on camera angle, pitch and distance. If a tree, rock or wall is
+/// between the player and that spot, the camera jumps in to just in front of it,
+/// then eases back out once the view is clear.
+fn follow_orbit(
+    rapier_context: &RapierContext,
+    player: Entity,
+    target_pos: Vec3,
+    camera: &MainCamera,
+    camera_transform: &mut Transform,
+    lerp_speed: f32,
+) {
+    let offset = camera.orbit_offset();
+    let (desired_pos, blocked) = match clear_camera_distance(rapier_context, player, target_pos, offset) {
+        Some(clear) => (target_pos + offset.normalize() * clear, true),
+        None => (target_pos + offset, false),
+    };
+
     camera_transform.translation = camera_transform.translation.lerp(desired_pos, lerp_speed);
+    // Never ease in through geometry
+    if blocked && camera_transform.translation.distance(target_pos) > desired_pos.distance(target_pos) {
+        camera_transform.translation = desired_pos;
+    }
     camera_transform.look_at(target_pos, Vec3::Y);
 }
 
-/// Handles mouse input for camera rotation and zoom.
+/// Casts from the player towards the camera's desired spot, ignoring the player's own
+/// collider and sensors. Returns how far from the player the camera can sit if
+/// something is in the way.
+fn clear_camera_distance(rapier_context: &RapierContext, player: Entity, target: Vec3, offset: Vec3) -> Option<f32> {
+    let length = offset.length();
+    if length <= CAMERA_MIN_DISTANCE {
+        return None;
+    }
+    let filter = QueryFilter::new().exclude_rigid_body(player).exclude_sensors();
+    rapier_context
+        .cast_ray(target, offset / length, length, true, filter)
+        .map(|(_, toi)| (toi - CAMERA_COLLISION_MARGIN).max(CAMERA_MIN_DISTANCE))
+}
+
+/// Handles mouse and right stick input for camera rotation and zoom, switching
+/// camera modes and top-down edge panning.
 fn camera_control(
+    time: Res<Time>,
+    settings: Res<CameraSettings>,
     mut camera_query: Query<&mut MainCamera>,
-    mouse: Res<ButtonInput<MouseButton>>,
+    player_query: Query<&Transform, With<Player>>,
+    windows: Query<&Window>,
+    actions: Actions,
     mut scroll_evr: EventReader<MouseWheel>,
     mut motion_evr: EventReader<bevy::input::mouse::MouseMotion>,
 ) {
@@ -60,20 +205,79 @@ fn camera_control(
         Err(_) => return,
     };
 
-    // Rotate camera with left or right mouse button
-    if mouse.pressed(MouseButton::Right) || mouse.pressed(MouseButton::Left) {
+    if actions.just_pressed(Action::CycleCamera) {
+        camera.mode = camera.mode.next();
+        if camera.mode == CameraMode::TopDown {
+            if let Ok(player_transform) = player_query.get_single() {
+                camera.focus = player_transform.translation;
+            }
+        }
+    }
+
+    // Turn and tilt the camera while the rotate action is held
+    let yaw_sign = CameraSettings::axis_sign(settings.invert_x);
+    let pitch_sign = CameraSettings::axis_sign(settings.invert_y);
+    if actions.pressed(Action::RotateCamera) {
         for ev in motion_evr.read() {
-            camera.angle -= ev.delta.x * 0.01;
+            camera.angle -= ev.delta.x * settings.mouse_sensitivity * yaw_sign;
+            camera.pitch += ev.delta.y * settings.mouse_sensitivity * pitch_sign;
         }
     }
 
-    // Process all scroll events at once
+    // Process all scroll events at once; each zoom key press counts as one wheel notch
     let mut total_scroll = 0.0;
     for ev in scroll_evr.read() {
         total_scroll += ev.y;
     }
+    if actions.just_pressed(Action::ZoomIn) {
+        total_scroll += 1.0;
+    }
+    if actions.just_pressed(Action::ZoomOut) {
+        total_scroll -= 1.0;
+    }
+
+    // The right stick orbits sideways and zooms when pushed up or down
+    let stick = actions.right_stick();
+    camera.angle -= stick.x * settings.stick_rotate_speed * yaw_sign * time.delta_seconds();
+    total_scroll += stick.y * settings.stick_zoom_speed * time.delta_seconds();
+    camera.pitch = camera.pitch.clamp(settings.min_pitch, settings.max_pitch);
     if total_scroll != 0.0 {
-        camera.distance = (camera.distance - total_scroll * 0.5).clamp(2.0, 10.0);
-        camera.height = (camera.height - total_scroll * 0.2).clamp(1.0, 5.0);
+        match camera.mode {
+            CameraMode::Orbit => {
+                camera.distance = (camera.distance - total_scroll * settings.zoom_step)
+                    .clamp(settings.min_distance, settings.max_distance);
+            }
+            CameraMode::TopDown => {
+                camera.top_down_height = (camera.top_down_height - total_scroll * settings.top_down_zoom_step)
+                    .clamp(settings.min_top_down_height, settings.max_top_down_height);
+            }
+            CameraMode::FirstPerson => {}
+        }
+    }
+
+    if camera.mode == CameraMode::TopDown {
+        if let Some(edge) = windows.get_single().ok().and_then(edge_pan_direction) {
+            let (right, up) = camera.ground_axes();
+            let pan = (right * edge.x + up * edge.y) * EDGE_PAN_SPEED * camera.top_down_height * time.delta_seconds();
+            camera.focus += pan;
+        }
+    }
+}
+
+/// Which way to pan when the cursor is at the window border (x = right, y = up).
+fn edge_pan_direction(window: &Window) -> Option<Vec2> {
+    let cursor = window.cursor_position()?;
+    let mut direction = Vec2::ZERO;
+    if cursor.x < EDGE_PAN_MARGIN {
+        direction.x -= 1.0;
+    } else if cursor.x > window.width() - EDGE_PAN_MARGIN {
+        direction.x += 1.0;
+    }
+    // Window coordinates grow downwards
+    if cursor.y < EDGE_PAN_MARGIN {
+        direction.y += 1.0;
+    } else if cursor.y > window.height() - EDGE_PAN_MARGIN {
+        direction.y -= 1.0;
     }
-} 
\ No newline at end of file
+    (direction != Vec2::ZERO).then_some(direction)
+}
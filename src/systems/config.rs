@@ -0,0 +1,63 @@
+//! Loads the config and data files at startup and swaps in the resources built from
+//! them whenever a file finishes loading or is edited.
+use bevy::asset::LoadState;
+use bevy::prelude::*;
+use crate::components::config::{FromConfig, RonAssetLoader};
+
+/// Registers `R`'s config asset and its loader, starts loading the file at startup
+/// and keeps `R` in sync with it.
+pub(crate) fn add_config<R: FromConfig>(app: &mut App) {
+    app.init_asset::<R::Config>()
+        .init_asset_loader::<RonAssetLoader<R::Config>>()
+        .init_resource::<ConfigHandles>()
+        .add_systems(Startup, load_config::<R>)
+        .add_systems(Update, sync_config::<R>);
+}
+
+/// Resource: Keeps every config file loaded, by path.
+#[derive(Resource, Default)]
+struct ConfigHandles(Vec<(&'static str, UntypedHandle)>);
+
+/// How far loading the config files has got.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ConfigLoadState {
+    /// Still waiting on the file at this path.
+    Loading(&'static str),
+    Loaded,
+    /// The file at this path could not be loaded.
+    Failed(&'static str),
+}
+
+/// How far loading the config files started at startup has got.
+pub(crate) fn config_load_state(world: &World) -> ConfigLoadState {
+    let asset_server = world.resource::<AssetServer>();
+    let mut state = ConfigLoadState::Loaded;
+    for (path, handle) in &world.resource::<ConfigHandles>().0 {
+        match asset_server.get_load_state(handle) {
+            Some(LoadState::Loaded) => {}
+            Some(LoadState::Failed) => return ConfigLoadState::Failed(path),
+            _ => state = ConfigLoadState::Loading(path),
+        }
+    }
+    state
+}
+
+/// Starts loading `R`'s config file.
+fn load_config<R: FromConfig>(asset_server: Res<AssetServer>, mut handles: ResMut<ConfigHandles>) {
+    handles.0.push((R::PATH, asset_server.load::<R::Config>(R::PATH).untyped()));
+}
+
+/// Builds `R` again whenever its config file finishes loading or is edited.
+pub fn sync_config<R: FromConfig>(
+    mut events: EventReader<AssetEvent<R::Config>>,
+    configs: Res<Assets<R::Config>>,
+    mut resource: ResMut<R>,
+) {
+    for event in events.read() {
+        if let AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } = event {
+            if let Some(loaded) = configs.get(*id) {
+                *resource = R::from_config(loaded);
+            }
+        }
+    }
+}
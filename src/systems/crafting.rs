@@ -0,0 +1,232 @@
+//! Crafting: turns inventory contents into other resources following the loaded recipes.
+//! Accepted crafts take their inputs straight away and then wait in a queue that
+//! completes one craft at a time.
+use std::collections::VecDeque;
+use bevy::prelude::*;
+use thiserror::Error;
+use crate::components::inventory::{Inventory, InventoryError};
+use crate::components::types::{CraftingStation, Player, PlayerInventory, Position, ResourceType};
+use crate::components::recipes::{Recipe, RecipeBook};
+use crate::components::bindings::Action;
+use crate::systems::config::add_config;
+use crate::systems::input::TickActions;
+use crate::systems::GameSet;
+
+/// How close the player must be to a station to use it.
+pub const STATION_RANGE: f32 = 3.0;
+
+/// Plugin for recipes, the crafting queue and the crafting hotkeys.
+pub struct GameCraftingPlugin;
+
+impl Plugin for GameCraftingPlugin {
+    fn build(&self, app: &mut App) {
+        add_config::<RecipeBook>(app);
+        app.init_resource::<RecipeBook>()
+            .init_resource::<CraftingQueue>()
+            .add_event::<CraftRequested>()
+            .add_event::<CraftStarted>()
+            .add_event::<CraftCompleted>()
+            .add_event::<CraftFailed>()
+            .add_systems(FixedUpdate, craft_hotkeys.in_set(GameSet::Input))
+            .add_systems(FixedUpdate, (start_crafts, advance_crafting_queue).chain().in_set(GameSet::Crafting));
+    }
+}
+
+/// Event: Asks to craft a recipe once.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct CraftRequested {
+    pub recipe: String,
+}
+
+/// Event: A craft was accepted; its inputs are taken and it is in the queue.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct CraftStarted {
+    pub recipe: String,
+}
+
+/// Event: A craft finished and its outputs were added to the inventory.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct CraftCompleted {
+    pub recipe: String,
+}
+
+/// Event: A craft was rejected, or could not hand over its outputs and was refunded.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct CraftFailed {
+    pub recipe: String,
+    pub reason: CraftError,
+}
+
+/// Why a craft failed.
+#[derive(Clone, Debug, PartialEq, Error)]
+pub enum CraftError {
+    #[error("there is no such recipe")]
+    UnknownRecipe,
+    #[error("needs {needed} {}, have {available}", resource.id())]
+    MissingInput { resource: ResourceType, needed: u32, available: u32 },
+    #[error("no room for more {}", resource.id())]
+    StackFull { resource: ResourceType },
+    #[error("must be next to a {station}")]
+    StationRequired { station: String },
+}
+
+impl From<InventoryError> for CraftError {
+    fn from(error: InventoryError) -> Self {
+        match error {
+            InventoryError::NoRoom { item, .. } => CraftError::StackFull { resource: item },
+            InventoryError::NotEnough { item, needed, available } => {
+                CraftError::MissingInput { resource: item, needed, available }
+            }
+        }
+    }
+}
+
+/// A queued craft.
+#[derive(Clone, Debug)]
+pub struct CraftJob {
+    pub recipe: String,
+    /// Counts down while the job is at the front of the queue.
+    pub timer: Timer,
+}
+
+/// Resource: Accepted crafts, oldest first. Only the front job makes progress.
+#[derive(Resource, Default)]
+pub struct CraftingQueue {
+    pub jobs: VecDeque<CraftJob>,
+}
+
+/// Requests the recipe whose craft action was pressed.
+fn craft_hotkeys(
+    actions: Res<TickActions>,
+    book: Res<RecipeBook>,
+    mut requests: EventWriter<CraftRequested>,
+) {
+    for (slot, recipe) in book.iter().enumerate() {
+        if actions.just_pressed(Action::Craft(slot)) {
+            requests.send(CraftRequested { recipe: recipe.id.clone() });
+        }
+    }
+}
+
+/// Checks each requested craft, takes its inputs and queues it.
+fn start_crafts(
+    mut requests: EventReader<CraftRequested>,
+    book: Res<RecipeBook>,
+    mut inventory: ResMut<PlayerInventory>,
+    mut queue: ResMut<CraftingQueue>,
+    player_query: Query<&Position, With<Player>>,
+    station_query: Query<(&Position, &CraftingStation)>,
+    mut started: EventWriter<CraftStarted>,
+    mut failed: EventWriter<CraftFailed>,
+) {
+    for request in requests.read() {
+        let checked = book.get(&request.recipe).ok_or(CraftError::UnknownRecipe).and_then(|recipe| {
+            check_station(recipe, player_query.get_single().ok(), &station_query)?;
+            // Take the inputs only if the outputs will fit afterwards
+            inventory.transaction(|inventory| {
+                take_inputs(recipe, inventory)?;
+                check_outputs(recipe, inventory, &queue, &book)
+            })?;
+            Ok(recipe)
+        });
+        let recipe = match checked {
+            Ok(recipe) => recipe,
+            Err(reason) => {
+                println!("Cannot craft {}: {}", request.recipe, reason);
+                failed.send(CraftFailed { recipe: request.recipe.clone(), reason });
+                continue;
+            }
+        };
+
+        queue.jobs.push_back(CraftJob {
+            recipe: recipe.id.clone(),
+            timer: Timer::from_seconds(recipe.craft_seconds, TimerMode::Once),
+        });
+        started.send(CraftStarted { recipe: recipe.id.clone() });
+    }
+}
+
+/// Advances the front job and hands over its outputs when it finishes.
+/// If gathering filled a stack in the meantime, the craft fails and its inputs are refunded.
+fn advance_crafting_queue(
+    time: Res<Time>,
+    book: Res<RecipeBook>,
+    mut inventory: ResMut<PlayerInventory>,
+    mut queue: ResMut<CraftingQueue>,
+    mut completed: EventWriter<CraftCompleted>,
+    mut failed: EventWriter<CraftFailed>,
+) {
+    let Some(job) = queue.jobs.front_mut() else {
+        return;
+    };
+    if !job.timer.tick(time.delta()).finished() {
+        return;
+    }
+    let job = queue.jobs.pop_front().unwrap();
+    // The recipe may have been edited out of the file since the job was queued
+    let Some(recipe) = book.get(&job.recipe) else {
+        failed.send(CraftFailed { recipe: job.recipe, reason: CraftError::UnknownRecipe });
+        return;
+    };
+
+    let handed_over = inventory.transaction(|inventory| {
+        recipe.outputs.iter().try_for_each(|&(resource, amount)| inventory.try_add(resource, amount))
+    });
+    if let Err(error) = handed_over {
+        // Refund whatever of the inputs still fits
+        for &(input, amount) in &recipe.inputs {
+            inventory.add_up_to(input, amount);
+        }
+        println!("Cannot finish {}: {}", recipe.display_name, error);
+        failed.send(CraftFailed { recipe: job.recipe, reason: error.into() });
+        return;
+    }
+    println!("Crafted {}!", recipe.display_name);
+    completed.send(CraftCompleted { recipe: job.recipe });
+}
+
+fn check_station(
+    recipe: &Recipe,
+    player_position: Option<&Position>,
+    station_query: &Query<(&Position, &CraftingStation)>,
+) -> Result<(), CraftError> {
+    let Some(station) = &recipe.station else {
+        return Ok(());
+    };
+    let near_station = player_position.is_some_and(|player| {
+        station_query.iter().any(|(position, candidate)| {
+            candidate.kind == *station && position.value.distance(player.value) <= STATION_RANGE
+        })
+    });
+    if near_station {
+        Ok(())
+    } else {
+        Err(CraftError::StationRequired { station: station.clone() })
+    }
+}
+
+fn take_inputs(recipe: &Recipe, inventory: &mut Inventory) -> Result<(), InventoryError> {
+    recipe
+        .inputs
+        .iter()
+        .try_for_each(|&(resource, needed)| inventory.try_remove(resource, needed))
+}
+
+/// Checks the outputs fit in the inventory (with this craft's inputs already taken)
+/// once the outputs of the crafts already queued are in.
+fn check_outputs(
+    recipe: &Recipe,
+    inventory: &Inventory,
+    queue: &CraftingQueue,
+    book: &RecipeBook,
+) -> Result<(), InventoryError> {
+    let mut preview = inventory.clone();
+    let queued = queue.jobs.iter().filter_map(|job| book.get(&job.recipe));
+    for &(resource, amount) in queued.flat_map(|queued| queued.outputs.iter()) {
+        preview.add_up_to(resource, amount);
+    }
+    recipe
+        .outputs
+        .iter()
+        .try_for_each(|&(resource, amount)| preview.try_add(resource, amount))
+}
@@ -0,0 +1,109 @@
+//! Day/night cycle: advances [`TimeOfDay`] every fixed tick and lights the world to
+//! match, moving the sun and changing its light, the ambient light and the sky.
+use bevy::prelude::*;
+use bevy::pbr::light_consts::lux;
+use crate::components::time_of_day::{DayPeriod, TimeOfDay};
+use crate::systems::GameSet;
+
+/// Sun illuminance at noon.
+const NOON_ILLUMINANCE: f32 = lux::AMBIENT_DAYLIGHT;
+/// Illuminance of the moonlit night, so the world never goes fully black.
+const NIGHT_ILLUMINANCE: f32 = lux::LIVING_ROOM;
+/// Ambient brightness at noon and at night.
+const DAY_AMBIENT: f32 = 150.0;
+const NIGHT_AMBIENT: f32 = 20.0;
+/// How far the sun's path leans toward the south, so noon shadows are not straight down.
+const SUN_PATH_TILT: f32 = 0.3;
+
+const NOON_SUN: Color = Color::rgb(1.0, 0.98, 0.92);
+const LOW_SUN: Color = Color::rgb(1.0, 0.6, 0.35);
+const MOONLIGHT: Color = Color::rgb(0.55, 0.65, 1.0);
+const DAY_SKY: Color = Color::rgb(0.5, 0.7, 0.95);
+const NIGHT_SKY: Color = Color::rgb(0.02, 0.03, 0.08);
+const DAY_AMBIENT_COLOR: Color = Color::WHITE;
+const NIGHT_AMBIENT_COLOR: Color = Color::rgb(0.5, 0.6, 1.0);
+
+/// Plugin for the in-game clock and the lighting that follows it.
+pub struct GameDayNightPlugin;
+
+impl Plugin for GameDayNightPlugin {
+    fn build(&self, app: &mut App) {
+        // Keep a clock set up by the embedding app
+        app.init_resource::<TimeOfDay>()
+            .add_event::<DayPeriodChanged>()
+            // Everything in the tick sees the same time
+            .add_systems(FixedUpdate, advance_time_of_day.before(GameSet::Input))
+            .add_systems(Update, light_the_world);
+    }
+}
+
+/// Marker for the directional light that plays the sun (and the moon).
+#[derive(Component)]
+pub struct Sun;
+
+/// Event: Day turned into night or night into day.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DayPeriodChanged {
+    pub period: DayPeriod,
+    /// Day count at the change.
+    pub day: u32,
+}
+
+/// Run condition that is true between dusk and dawn.
+pub fn is_night(time: Res<TimeOfDay>) -> bool {
+    time.is_night()
+}
+
+/// Run condition that is true between dawn and dusk.
+pub fn is_day(time: Res<TimeOfDay>) -> bool {
+    !time.is_night()
+}
+
+/// Moves the clock on by one fixed timestep and announces dawn and dusk.
+fn advance_time_of_day(time: Res<Time>, mut clock: ResMut<TimeOfDay>, mut changed: EventWriter<DayPeriodChanged>) {
+    let before = clock.period();
+    clock.advance(time.delta_seconds());
+    let period = clock.period();
+    if period != before {
+        println!("{} {} begins", if period == DayPeriod::Day { "Day" } else { "Night" }, clock.day());
+        changed.send(DayPeriodChanged { period, day: clock.day() });
+    }
+}
+
+/// Points the sun along its path and sets the sun, ambient light and sky for the hour.
+pub fn light_the_world(
+    clock: Res<TimeOfDay>,
+    mut sun_query: Query<(&mut DirectionalLight, &mut Transform), With<Sun>>,
+    ambient: Option<ResMut<AmbientLight>>,
+    clear_color: Option<ResMut<ClearColor>>,
+) {
+    let daylight = clock.daylight();
+    let angle = clock.sun_angle();
+    // Below the horizon the moon takes over from the opposite side of the sky
+    let height = angle.sin();
+    let direction = if height >= 0.0 {
+        Vec3::new(angle.cos(), height, SUN_PATH_TILT)
+    } else {
+        Vec3::new(-angle.cos(), -height, SUN_PATH_TILT)
+    };
+
+    for (mut light, mut transform) in &mut sun_query {
+        *transform = Transform::from_translation(direction.normalize()).looking_at(Vec3::ZERO, Vec3::Y);
+        light.illuminance = NIGHT_ILLUMINANCE + (NOON_ILLUMINANCE - NIGHT_ILLUMINANCE) * daylight;
+        light.color = if height >= 0.0 { mix(LOW_SUN, NOON_SUN, daylight) } else { MOONLIGHT };
+    }
+    if let Some(mut ambient) = ambient {
+        ambient.brightness = NIGHT_AMBIENT + (DAY_AMBIENT - NIGHT_AMBIENT) * daylight;
+        ambient.color = mix(NIGHT_AMBIENT_COLOR, DAY_AMBIENT_COLOR, daylight);
+    }
+    if let Some(mut clear_color) = clear_color {
+        clear_color.0 = mix(NIGHT_SKY, DAY_SKY, daylight);
+    }
+}
+
+/// Blends from `from` at `t` = 0 to `to` at `t` = 1.
+pub(crate) fn mix(from: Color, to: Color, t: f32) -> Color {
+    let [r0, g0, b0, a0] = from.as_rgba_f32();
+    let [r1, g1, b1, a1] = to.as_rgba_f32();
+    Color::rgba(r0 + (r1 - r0) * t, g0 + (g1 - g0) * t, b0 + (b1 - b0) * t, a0 + (a1 - a0) * t)
+}
@@ -1,15 +1,48 @@
-//! Spawns and sets up all entities: player, trees, rocks, ground, camera, and light.
+//! Spawns and sets up all entities: player, NPCs, workers, stockpile, furnace, trees, rocks, ground, camera, and light.
 //! Also contains component registration for the world.
 use bevy::prelude::*;
 use bevy::asset::LoadState;
 use bevy_rapier3d::prelude::*;
-use rand::Rng;
-use crate::components::types::{Player, Position, Gatherable, ResourceNode, GameAssets, ResourceType};
+use crate::components::types::{CraftingStation, Player, Position, Gatherable, Npc, ResourceNode, GameAssets, WorldSeed};
+use crate::components::definitions::{ResourceDefinition, ResourceRegistry};
+use crate::components::equipment::Equipment;
+use crate::components::skills::Skills;
+use crate::components::workers::{Stockpile, Worker};
 use crate::systems::camera::MainCamera;
+use crate::systems::daynight::Sun;
+use crate::systems::navigation::PathFollower;
+use crate::systems::placement::{scatter_points, world_rng, PlacementRules};
 
-pub fn register(app: &mut App) {
-    app.add_systems(Startup, setup)
-        .add_systems(Update, spawn_resources);
+/// Number of resource nodes scattered around the map, split between types by spawn weight.
+const RESOURCE_NODE_COUNT: u32 = 30;
+/// Where the guide stands, inside the zone around the spawn that is kept free of nodes.
+const GUIDE_POSITION: Vec3 = Vec3::new(3.0, 0.8, -2.0);
+/// Where the stockpile workers bring their loads to, on the other side of the spawn.
+const STOCKPILE_POSITION: Vec3 = Vec3::new(-3.0, 0.25, -2.0);
+/// Where the furnace for smelting recipes stands, beside the spawn and out of the way ahead.
+const FURNACE_POSITION: Vec3 = Vec3::new(3.0, 0.5, 2.0);
+/// Ids, names and spots of the workers the game starts with, next to the stockpile.
+const WORKERS: [(&str, &str, Vec3); 2] =
+    [("ada", "Ada", Vec3::new(-4.5, 0.8, -0.5)), ("bram", "Bram", Vec3::new(-1.5, 0.8, -0.5))];
+
+/// Plugin that spawns the world. When `headless` is set, only the gameplay
+/// entities (colliders, player, camera rig, resource nodes) are spawned,
+/// without meshes, materials, scenes or lights.
+#[derive(Default)]
+pub struct GameEntitiesPlugin {
+    pub headless: bool,
+}
+
+impl Plugin for GameEntitiesPlugin {
+    fn build(&self, app: &mut App) {
+        if self.headless {
+            app.add_systems(Startup, setup_headless)
+                .add_systems(Update, spawn_resources_headless);
+        } else {
+            app.add_systems(Startup, setup)
+                .add_systems(Update, (spawn_resources, add_colony_visuals));
+        }
+    }
 }
 
 /// Sets up the world: camera, light, ground, loads models, and spawns the player.
@@ -23,7 +56,7 @@ fn setup(
     // Spawn ground
     commands.spawn((
         PbrBundle {
-            mesh: meshes.add(Mesh::from(shape::Plane::from_size(100.0))),
+            mesh: meshes.add(Plane3d::default().mesh().size(100.0, 100.0)),
             material: materials.add(StandardMaterial {
                 base_color: Color::rgb(0.3, 0.5, 0.3),
                 ..default()
@@ -31,19 +64,21 @@ fn setup(
             transform: Transform::from_xyz(0.0, -0.5, 0.0),
             ..default()
         },
-        RigidBody::Fixed,
-        Collider::cuboid(50.0, 0.5, 50.0),
+        ground_components(),
     ));
 
-    // Add a directional light
-    commands.spawn(DirectionalLightBundle {
-        directional_light: DirectionalLight {
-            shadows_enabled: true,
+    // Add a directional light, moved around the sky by the day/night cycle
+    commands.spawn((
+        DirectionalLightBundle {
+            directional_light: DirectionalLight {
+                shadows_enabled: true,
+                ..default()
+            },
+            transform: Transform::from_xyz(4.0, 8.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
             ..default()
         },
-        transform: Transform::from_xyz(4.0, 8.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
-        ..default()
-    });
+        Sun,
+    ));
 
     // Spawn camera
     commands.spawn((
@@ -51,25 +86,48 @@ fn setup(
             transform: Transform::from_xyz(0.0, 5.0, 10.0).looking_at(Vec3::ZERO, Vec3::Y),
             ..default()
         },
-        MainCamera {
-            distance: 5.0,
-            angle: 0.0,
-            height: 2.5,
+        MainCamera::default(),
+    ));
+
+    // Spawn the guide, who hands out the first quests
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(Capsule3d::new(0.4, 0.8)),
+            material: materials.add(StandardMaterial {
+                base_color: Color::rgb(0.2, 0.4, 0.8),
+                ..default()
+            }),
+            transform: Transform::from_translation(GUIDE_POSITION),
+            ..default()
         },
+        guide_components(),
     ));
 
+    // Spawn the furnace that smelting recipes need
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(Cuboid::new(1.0, 1.0, 1.0)),
+            material: materials.add(StandardMaterial {
+                base_color: Color::rgb(0.35, 0.3, 0.3),
+                emissive: Color::rgb(0.6, 0.2, 0.0),
+                ..default()
+            }),
+            transform: Transform::from_translation(FURNACE_POSITION),
+            ..default()
+        },
+        furnace_components(),
+    ));
+
+    // Workers and their stockpile get their meshes from `add_colony_visuals`
+    spawn_colony(&mut commands);
+
     // Load models
     game_assets.player_model = asset_server.load("models/CharWalk.glb#Scene0");
-    game_assets.tree_models = vec![
-        asset_server.load("models/tree1.glb#Scene0"),
-        asset_server.load("models/tree2.glb#Scene0"),
-    ];
-    game_assets.rock_model = asset_server.load("models/rock1.glb#Scene0");
 
     // Spawn a simple player cube
     commands.spawn((
         PbrBundle {
-            mesh: meshes.add(Mesh::from(shape::Cube { size: 1.0 })),
+            mesh: meshes.add(Cuboid::new(1.0, 1.0, 1.0)),
             material: materials.add(StandardMaterial {
                 base_color: Color::rgb(0.8, 0.2, 0.2),
                 ..default()
@@ -77,11 +135,94 @@ fn setup(
             transform: Transform::from_xyz(0.0, 0.5, 0.0),
             ..default()
         },
+        player_components(),
+    ));
+}
+
+/// Sets up the world without any rendering: ground collider, camera rig, player, guide and furnace.
+fn setup_headless(mut commands: Commands) {
+    commands.spawn((
+        SpatialBundle::from_transform(Transform::from_xyz(0.0, -0.5, 0.0)),
+        ground_components(),
+    ));
+
+    commands.spawn((
+        SpatialBundle::from_transform(
+            Transform::from_xyz(0.0, 5.0, 10.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ),
+        MainCamera::default(),
+    ));
+
+    commands.spawn((
+        SpatialBundle::from_transform(Transform::from_xyz(0.0, 0.5, 0.0)),
+        player_components(),
+    ));
+
+    commands.spawn((SpatialBundle::from_transform(Transform::from_translation(GUIDE_POSITION)), guide_components()));
+    commands.spawn((SpatialBundle::from_transform(Transform::from_translation(FURNACE_POSITION)), furnace_components()));
+    spawn_colony(&mut commands);
+}
+
+/// Spawns the stockpile and the workers without visuals.
+fn spawn_colony(commands: &mut Commands) {
+    commands.spawn((
+        SpatialBundle::from_transform(Transform::from_translation(STOCKPILE_POSITION)),
+        stockpile_components(STOCKPILE_POSITION),
+    ));
+    for (id, name, position) in WORKERS {
+        commands.spawn((
+            SpatialBundle::from_transform(Transform::from_translation(position)),
+            worker_components(id, name, position),
+        ));
+    }
+}
+
+/// Gives newly spawned workers and stockpiles, including those spawned from a save, their meshes.
+fn add_colony_visuals(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    workers: Query<Entity, Added<Worker>>,
+    stockpiles: Query<Entity, Added<Stockpile>>,
+) {
+    for worker in &workers {
+        commands.entity(worker).insert((
+            meshes.add(Capsule3d::new(0.4, 0.8)),
+            materials.add(StandardMaterial {
+                base_color: Color::rgb(0.8, 0.6, 0.2),
+                ..default()
+            }),
+        ));
+    }
+    for stockpile in &stockpiles {
+        commands.entity(stockpile).insert((
+            meshes.add(Cuboid::new(1.5, 0.5, 1.5)),
+            materials.add(StandardMaterial {
+                base_color: Color::rgb(0.5, 0.35, 0.2),
+                ..default()
+            }),
+        ));
+    }
+}
+
+/// Physics components for the ground plane.
+pub(crate) fn ground_components() -> impl Bundle {
+    (RigidBody::Fixed, Collider::cuboid(50.0, 0.5, 50.0))
+}
+
+/// Gameplay and physics components for the player, without any visuals.
+pub(crate) fn player_components() -> impl Bundle {
+    (
         Player {
             speed: 5.0,
             gathering_range: 2.0,
             gathering_cooldown: Timer::from_seconds(1.0, TimerMode::Once),
+            gather_damage: 50,
+            gather_seconds: 1.0,
         },
+        Equipment::default(),
+        Skills::default(),
+        PathFollower::default(),
         Position { value: Vec3::ZERO },
         RigidBody::Dynamic,
         Collider::cuboid(0.5, 0.5, 0.5),
@@ -94,121 +235,151 @@ fn setup(
         },
         LockedAxes::ROTATION_LOCKED,
         GravityScale(1.0),
-    ));
+    )
+}
 
-    // Spawn resources
-    let mut rng = rand::thread_rng();
-    
-    // Spawn trees
-    for _ in 0..20 {
-        let x = rng.gen_range(-20.0..20.0);
-        let z = rng.gen_range(-20.0..20.0);
-        
-        commands.spawn((
-            SceneBundle {
-                scene: asset_server.load("models/tree1.glb#Scene0"),
-                transform: Transform::from_xyz(x, 0.0, z),
-                ..default()
-            },
-            Position { value: Vec3::new(x, 0.0, z) },
-            ResourceNode,
-            RigidBody::Fixed,
-            Collider::cylinder(1.0, 0.5),
-            Gatherable {
-                resource_type: ResourceType::Wood,
-                health: 100,
-                respawn_timer: None,
-            },
-        ));
+/// Gameplay and physics components for the furnace, without any visuals.
+fn furnace_components() -> impl Bundle {
+    (
+        CraftingStation { kind: "furnace".to_owned() },
+        Position { value: FURNACE_POSITION },
+        RigidBody::Fixed,
+        Collider::cuboid(0.5, 0.5, 0.5),
+    )
+}
+
+/// Gameplay and physics components for the guide NPC, without any visuals.
+fn guide_components() -> impl Bundle {
+    npc_components("guide", "the guide", GUIDE_POSITION)
+}
+
+/// Gameplay and physics components for an NPC at `position`, without any visuals.
+pub(crate) fn npc_components(id: &str, name: &str, position: Vec3) -> impl Bundle {
+    (
+        Npc { id: id.to_owned(), name: name.to_owned() },
+        Position { value: position },
+        RigidBody::Fixed,
+        Collider::capsule_y(0.4, 0.4),
+    )
+}
+
+/// Gameplay and physics components for a worker at `position`, without any visuals.
+/// Workers move themselves, so physics only follows them.
+pub(crate) fn worker_components(id: &str, name: &str, position: Vec3) -> impl Bundle {
+    (
+        Npc { id: id.to_owned(), name: name.to_owned() },
+        Worker::default(),
+        Equipment::default(),
+        Skills::default(),
+        PathFollower::default(),
+        Position { value: position },
+        RigidBody::KinematicPositionBased,
+        Collider::capsule_y(0.4, 0.4),
+    )
+}
+
+/// Gameplay and physics components for a stockpile at `position`, without any visuals.
+pub(crate) fn stockpile_components(position: Vec3) -> impl Bundle {
+    (
+        Stockpile::default(),
+        Position { value: position },
+        RigidBody::Fixed,
+        Collider::cuboid(0.75, 0.25, 0.75),
+    )
+}
+
+/// Gameplay and physics components for a resource node at `position`, without any visuals.
+pub(crate) fn resource_node_components(definition: &ResourceDefinition, position: Vec3) -> impl Bundle {
+    (
+        Position { value: position },
+        ResourceNode,
+        RigidBody::Fixed,
+        definition.collider.collider(),
+        Gatherable {
+            resource_type: definition.id,
+            health: definition.health,
+            respawn_timer: None,
+        },
+    )
+}
+
+/// Scatters resource nodes of every defined type around the map from the world seed. `visual`
+/// supplies the bundle that carries each node's transform (a scene when rendering, a bare
+/// spatial bundle headless).
+fn spawn_resource_nodes<B: Bundle>(
+    commands: &mut Commands,
+    registry: &ResourceRegistry,
+    seed: WorldSeed,
+    mut visual: impl FnMut(&ResourceDefinition, Transform) -> B,
+) {
+    let definitions: Vec<&ResourceDefinition> = registry
+        .spawn_counts(RESOURCE_NODE_COUNT)
+        .into_iter()
+        .flat_map(|(definition, count)| std::iter::repeat_n(definition, count as usize))
+        .collect();
+    let points = scatter_points(&mut world_rng(seed), definitions.len(), &PlacementRules::default());
+    if points.len() < definitions.len() {
+        eprintln!("Only found room for {} of {} resource nodes", points.len(), definitions.len());
     }
-    
-    // Spawn rocks
-    for _ in 0..10 {
-        let x = rng.gen_range(-20.0..20.0);
-        let z = rng.gen_range(-20.0..20.0);
-        
+
+    for (definition, point) in definitions.into_iter().zip(points) {
+        let position = Vec3::new(point.x, 0.0, point.y);
         commands.spawn((
-            SceneBundle {
-                scene: asset_server.load("models/rock1.glb#Scene0"),
-                transform: Transform::from_xyz(x, 0.0, z),
-                ..default()
-            },
-            Position { value: Vec3::new(x, 0.0, z) },
-            ResourceNode,
-            RigidBody::Fixed,
-            Collider::cylinder(0.5, 0.5),
-            Gatherable {
-                resource_type: ResourceType::Stone,
-                health: 100,
-                respawn_timer: None,
-            },
+            visual(definition, Transform::from_translation(position)),
+            resource_node_components(definition, position),
         ));
     }
 }
 
-/// Spawns resource nodes (trees and rocks) in a grid after assets are loaded.
-///
-/// This function is currently disabled and left in the codebase for future
-/// use when dynamic resource spawning is re-enabled.
+/// Spawns the resource nodes once their definitions and models are loaded.
 fn spawn_resources(
     mut commands: Commands,
-    game_assets: Res<GameAssets>,
+    mut game_assets: ResMut<GameAssets>,
     asset_server: Res<AssetServer>,
+    registry: Res<ResourceRegistry>,
+    seed: Res<WorldSeed>,
+    mut spawned: Local<bool>,
 ) {
-    // Check if assets are loaded
-    if game_assets.tree_models.is_empty() {
+    if *spawned || !registry.is_loaded() {
         return;
     }
 
-    // Check if any assets are still loading
-    for handle in &game_assets.tree_models {
-        if asset_server.get_load_state(handle) != Some(LoadState::Loaded) {
+    // Start loading each type's model
+    for definition in registry.iter() {
+        game_assets
+            .resource_models
+            .entry(definition.id)
+            .or_insert_with(|| asset_server.load(definition.model.clone()));
+    }
+
+    // Check if any models are still loading
+    for handle in game_assets.resource_models.values() {
+        if matches!(asset_server.get_load_state(handle), Some(LoadState::Loading) | Some(LoadState::NotLoaded)) {
             return;
         }
     }
-    if asset_server.get_load_state(&game_assets.rock_model) != Some(LoadState::Loaded) {
-        return;
-    }
 
-    // Keep resource spawning commented out
-    /*
-    let mut rng = rand::thread_rng();
-    for _ in 0..20 {
-        let x = rng.gen_range(-20.0..20.0);
-        let z = rng.gen_range(-20.0..20.0);
-        commands.spawn((
-            SceneBundle {
-                scene: asset_server.load("models/tree.glb#Scene0"),
-                transform: Transform::from_xyz(x, 0.0, z),
-                ..default()
-            },
-            Gatherable {
-                resource_type: ResourceType::Wood,
-                health: 100,
-                respawn_timer: None,
-            },
-            RigidBody::Fixed,
-            Collider::cylinder(1.0, 0.5),
-        ));
-    }
+    spawn_resource_nodes(&mut commands, &registry, *seed, |definition, transform| SceneBundle {
+        scene: game_assets.resource_models[&definition.id].clone(),
+        transform,
+        ..default()
+    });
+    *spawned = true;
+}
 
-    for _ in 0..10 {
-        let x = rng.gen_range(-20.0..20.0);
-        let z = rng.gen_range(-20.0..20.0);
-        commands.spawn((
-            SceneBundle {
-                scene: asset_server.load("models/rock.glb#Scene0"),
-                transform: Transform::from_xyz(x, 0.0, z),
-                ..default()
-            },
-            Gatherable {
-                resource_type: ResourceType::Stone,
-                health: 100,
-                respawn_timer: None,
-            },
-            RigidBody::Fixed,
-            Collider::cylinder(0.5, 0.5),
-        ));
+/// Spawns the resource nodes without models once their definitions are loaded.
+fn spawn_resources_headless(
+    mut commands: Commands,
+    registry: Res<ResourceRegistry>,
+    seed: Res<WorldSeed>,
+    mut spawned: Local<bool>,
+) {
+    if *spawned || !registry.is_loaded() {
+        return;
     }
-    */
-} 
\ No newline at end of file
+
+    spawn_resource_nodes(&mut commands, &registry, *seed, |_, transform| {
+        SpatialBundle::from_transform(transform)
+    });
+    *spawned = true;
+}
@@ -1,33 +1,222 @@
-//! Handles keyboard input for player movement.
+//! Handles keyboard, mouse and gamepad input through rebindable actions.
 // Only processes input and sets a movement direction resource.
 use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
+use bevy::input::gamepad::{GamepadConnection, GamepadConnectionEvent};
+use bevy::input::InputSystem;
+use crate::components::bindings::{Action, ActionMap, Binding};
+use crate::systems::config::add_config;
+use crate::systems::GameSet;
 
-/// Resource to store the current movement direction from input
+/// Resource to store the current movement input. Its length is the requested
+/// share of full speed, so a half-tilted stick walks at half speed.
 #[derive(Resource, Default)]
 pub struct MovementInput(pub Vec2);
 
-pub fn register(app: &mut App) {
-    app.init_resource::<MovementInput>();
-    // Run input system in FixedUpdate for consistent input handling
-    app.add_systems(FixedUpdate, input_system);
+/// Resource: The gamepad the player is using, if any. The first gamepad connected is
+/// used until it disconnects, then play moves to another connected gamepad.
+#[derive(Resource, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ActiveGamepad(pub Option<Gamepad>);
+
+/// Resource: Presses of the actions handled in the fixed schedule (see [`Action::is_per_tick`]).
+/// Presses are latched every frame and handed to the next fixed tick, so each one is
+/// handled on exactly one tick: a frame running two ticks does not handle it twice, and
+/// a frame running none does not drop it. Code can press actions too, e.g. input replay.
+#[derive(Resource, Default, Clone, Debug)]
+pub struct TickActions {
+    /// Presses waiting for the next tick.
+    latched: Vec<Action>,
+    /// Presses handled by the tick running now.
+    current: Vec<Action>,
 }
 
-/// System to read WASD input and update the movement direction resource
+impl TickActions {
+    /// Presses `action` for the next tick.
+    pub fn press(&mut self, action: Action) {
+        if !self.latched.contains(&action) {
+            self.latched.push(action);
+        }
+    }
+
+    /// Whether `action` was pressed for the tick running now.
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.current.contains(&action)
+    }
+
+    /// Every action pressed for the tick running now.
+    pub fn pressed(&self) -> &[Action] {
+        &self.current
+    }
+
+    /// Hands the latched presses to the tick starting now.
+    fn start_tick(&mut self) {
+        self.current = std::mem::take(&mut self.latched);
+    }
+}
+
+/// Resource: Movement set by code rather than a device, e.g. by input replay. While set,
+/// it replaces the move actions until whoever injected it clears it.
+#[derive(Resource, Default, Clone, Copy, Debug)]
+pub struct InjectedMovement(pub Option<Vec2>);
+
+/// Plugin that loads the input bindings and reads the move actions into [`MovementInput`].
+pub struct GameInputPlugin;
+
+impl Plugin for GameInputPlugin {
+    fn build(&self, app: &mut App) {
+        init_input_resources(app);
+        add_config::<ActionMap>(app);
+        app.add_systems(PreUpdate, (track_gamepads, latch_tick_actions).after(InputSystem));
+        // Run input system in FixedUpdate for consistent input handling
+        app.add_systems(FixedUpdate, input_system.in_set(GameSet::Input));
+    }
+}
+
+/// Inits the resources the input systems and [`Actions`] read, and hands latched
+/// presses to each fixed tick as it starts.
+pub(crate) fn init_input_resources(app: &mut App) {
+    app.init_resource::<MovementInput>()
+        .init_resource::<ActionMap>()
+        .init_resource::<ActiveGamepad>()
+        .init_resource::<TickActions>()
+        .init_resource::<InjectedMovement>()
+        .add_systems(FixedFirst, start_tick_actions);
+}
+
+/// Reads actions through the current [`ActionMap`] instead of raw keys and buttons.
+#[derive(SystemParam)]
+pub struct Actions<'w> {
+    map: Res<'w, ActionMap>,
+    keyboard: Res<'w, ButtonInput<KeyCode>>,
+    mouse: Res<'w, ButtonInput<MouseButton>>,
+    gamepad_buttons: Res<'w, ButtonInput<GamepadButton>>,
+    gamepad_axes: Res<'w, Axis<GamepadAxis>>,
+    gamepad: Res<'w, ActiveGamepad>,
+}
+
+impl Actions<'_> {
+    /// Whether any binding of `action` is held.
+    pub fn pressed(&self, action: Action) -> bool {
+        self.map.bindings(action).iter().any(|binding| match *binding {
+            Binding::Key(key) => self.keyboard.pressed(key),
+            Binding::Mouse(button) => self.mouse.pressed(button),
+            Binding::Gamepad(button) => self
+                .gamepad
+                .0
+                .is_some_and(|gamepad| self.gamepad_buttons.pressed(GamepadButton::new(gamepad, button))),
+        })
+    }
+
+    /// Whether any binding of `action` was pressed since the last input update. Systems
+    /// in the fixed schedule read [`TickActions`] instead.
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.map.bindings(action).iter().any(|binding| match *binding {
+            Binding::Key(key) => self.keyboard.just_pressed(key),
+            Binding::Mouse(button) => self.mouse.just_pressed(button),
+            Binding::Gamepad(button) => self
+                .gamepad
+                .0
+                .is_some_and(|gamepad| self.gamepad_buttons.just_pressed(GamepadButton::new(gamepad, button))),
+        })
+    }
+
+    /// Position of the active gamepad's left stick past the deadzone (x = right, y = up).
+    pub fn left_stick(&self) -> Vec2 {
+        self.stick(GamepadAxisType::LeftStickX, GamepadAxisType::LeftStickY)
+    }
+
+    /// Position of the active gamepad's right stick past the deadzone (x = right, y = up).
+    pub fn right_stick(&self) -> Vec2 {
+        self.stick(GamepadAxisType::RightStickX, GamepadAxisType::RightStickY)
+    }
+
+    fn stick(&self, x: GamepadAxisType, y: GamepadAxisType) -> Vec2 {
+        let Some(gamepad) = self.gamepad.0 else {
+            return Vec2::ZERO;
+        };
+        let axis = |axis_type| self.gamepad_axes.get(GamepadAxis::new(gamepad, axis_type)).unwrap_or(0.0);
+        apply_deadzone(Vec2::new(axis(x), axis(y)), self.map.stick_deadzone)
+    }
+
+    /// The bindings and sensitivities in use.
+    pub fn map(&self) -> &ActionMap {
+        &self.map
+    }
+}
+
+/// Drops stick positions within `deadzone` of the center and rescales the rest,
+/// so the result goes smoothly from zero at the deadzone edge to one at full tilt.
+pub fn apply_deadzone(stick: Vec2, deadzone: f32) -> Vec2 {
+    let length = stick.length();
+    if length <= deadzone {
+        return Vec2::ZERO;
+    }
+    let scaled = ((length - deadzone) / (1.0 - deadzone)).min(1.0);
+    stick / length * scaled
+}
+
+/// Run condition that is true when `action` was just pressed.
+pub fn action_just_pressed(action: Action) -> impl FnMut(Actions) -> bool + Clone {
+    move |actions: Actions| actions.just_pressed(action)
+}
+
+/// Latches this frame's presses of the actions handled in the fixed schedule for the next tick.
+pub(crate) fn latch_tick_actions(actions: Actions, mut tick_actions: ResMut<TickActions>) {
+    for action in actions.map().actions().filter(Action::is_per_tick) {
+        if actions.just_pressed(action) {
+            tick_actions.press(action);
+        }
+    }
+}
+
+/// Hands the latched presses to the fixed tick starting now.
+fn start_tick_actions(mut tick_actions: ResMut<TickActions>) {
+    tick_actions.start_tick();
+}
+
+/// Picks the gamepad to play with as gamepads connect and disconnect.
+fn track_gamepads(
+    mut events: EventReader<GamepadConnectionEvent>,
+    gamepads: Res<Gamepads>,
+    mut active: ResMut<ActiveGamepad>,
+) {
+    for event in events.read() {
+        match &event.connection {
+            GamepadConnection::Connected(info) => {
+                if active.0.is_none() {
+                    active.0 = Some(event.gamepad);
+                    println!("Using gamepad {}", info.name);
+                }
+            }
+            GamepadConnection::Disconnected => {
+                if active.0 == Some(event.gamepad) {
+                    active.0 = gamepads.iter().find(|gamepad| *gamepad != event.gamepad);
+                    println!("Gamepad disconnected");
+                }
+            }
+        }
+    }
+}
+
+/// System to read the move actions and update the movement direction resource.
+/// Keys give full speed; the left stick gives its tilt when no move key is held.
 fn input_system(
-    keyboard: Res<ButtonInput<KeyCode>>,
+    actions: Actions,
+    injected: Res<InjectedMovement>,
     mut movement_input: ResMut<MovementInput>,
 ) {
-    // Use a single Vec2 for input to avoid multiple allocations
+    if let Some(direction) = injected.0 {
+        movement_input.0 = direction;
+        return;
+    }
     let mut direction = Vec2::ZERO;
-    
-    // Combine all input checks into a single block
-    if keyboard.any_pressed([KeyCode::KeyW, KeyCode::KeyS, KeyCode::KeyA, KeyCode::KeyD]) {
-        if keyboard.pressed(KeyCode::KeyW) { direction.y += 1.0; }
-        if keyboard.pressed(KeyCode::KeyS) { direction.y -= 1.0; }
-        if keyboard.pressed(KeyCode::KeyA) { direction.x -= 1.0; }
-        if keyboard.pressed(KeyCode::KeyD) { direction.x += 1.0; }
-        movement_input.0 = direction.normalize_or_zero();
+    if actions.pressed(Action::MoveForward) { direction.y += 1.0; }
+    if actions.pressed(Action::MoveBackward) { direction.y -= 1.0; }
+    if actions.pressed(Action::MoveLeft) { direction.x -= 1.0; }
+    if actions.pressed(Action::MoveRight) { direction.x += 1.0; }
+    movement_input.0 = if direction != Vec2::ZERO {
+        direction.normalize()
     } else {
-        movement_input.0 = Vec2::ZERO;
-    }
-} 
\ No newline at end of file
+        actions.left_stick()
+    };
+}
@@ -1,6 +1,49 @@
+pub mod config;
 pub mod input;
 pub mod camera;
 pub mod resources;
 pub mod entities;
 pub mod ui;
-pub mod movement; 
\ No newline at end of file
+pub mod movement;
+pub mod save;
+pub mod placement;
+pub mod crafting;
+pub mod replay;
+pub mod spatial;
+pub mod skills;
+pub mod npcs;
+pub mod quests;
+pub mod daynight;
+pub mod weather;
+pub mod workers;
+pub mod navigation;
+
+use bevy::prelude::*;
+
+/// Ordering of the gameplay systems that run in `FixedUpdate`, so every tick
+/// reads input, moves, gathers, crafts, tracks quests and then places the camera in the same order.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameSet {
+    Input,
+    Movement,
+    Gathering,
+    Crafting,
+    Quests,
+    Camera,
+}
+
+/// Chains the [`GameSet`]s in `FixedUpdate`.
+pub(crate) fn configure_game_sets(app: &mut App) {
+    app.configure_sets(
+        FixedUpdate,
+        (
+            GameSet::Input,
+            GameSet::Movement,
+            GameSet::Gathering,
+            GameSet::Crafting,
+            GameSet::Quests,
+            GameSet::Camera,
+        )
+            .chain(),
+    );
+}
@@ -1,31 +1,63 @@
-//! Handles player movement and rotation, making movement camera-relative.
+//! Handles player movement and rotation, making movement camera-relative, and walks
+//! the player along click-to-move paths when there is no movement input.
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::Velocity;
 use crate::components::types::{Player, Position};
+use crate::components::weather::{Weather, WeatherRegistry};
 use crate::systems::input::MovementInput;
 use crate::systems::camera::MainCamera;
+use crate::systems::navigation::{sync_nav_grid, NavGrid, PathFollower, WAYPOINT_REACHED};
+use crate::systems::GameSet;
 
-pub fn register(app: &mut App) {
-    // Run movement in FixedUpdate for consistent physics
-    app.add_systems(FixedUpdate, player_movement);
+/// Plugin for camera-relative player movement.
+pub struct GameMovementPlugin;
+
+impl Plugin for GameMovementPlugin {
+    fn build(&self, app: &mut App) {
+        // Run movement in FixedUpdate for consistent physics
+        app.add_systems(FixedUpdate, player_movement.after(sync_nav_grid).in_set(GameSet::Movement));
+    }
 }
 
-/// Moves the player based on input, relative to the camera's facing direction, and rotates the player to face movement.
+/// Moves the player based on input, relative to the camera's facing direction, at their
+/// speed as the weather allows, and rotates the player to face movement. Without input the
+/// player walks their click-to-move path, if any; any input cancels it.
 fn player_movement(
+    time: Res<Time>,
     movement_input: Res<MovementInput>,
+    weather: Res<Weather>,
+    weather_registry: Res<WeatherRegistry>,
+    grid: Res<NavGrid>,
     camera_query: Query<&Transform, (With<MainCamera>, Without<Player>)>,
-    mut player_query: Query<(&Player, &mut Position, &mut Transform, &mut Velocity)>,
+    mut player_query: Query<(&Player, &mut Position, &mut Transform, &mut Velocity, &mut PathFollower)>,
 ) {
-    let (player, mut position, mut transform, mut velocity) = match player_query.get_single_mut() {
+    let (player, mut position, mut transform, mut velocity, mut path) = match player_query.get_single_mut() {
         Ok(v) => v,
         Err(_) => return,
     };
+    let speed = player.speed * weather_registry.effects(weather.kind()).move_speed;
 
     let input = movement_input.0;
     if input == Vec2::ZERO {
         velocity.linvel = Vec3::ZERO;
+        if let Some(destination) = path.destination {
+            let here = transform.translation;
+            if (destination - here).xz().length() <= WAYPOINT_REACHED {
+                path.clear();
+            } else {
+                let offset = (path.steer(&grid, here, destination) - here).xz();
+                // Slow down on the last stretch instead of overshooting the waypoint
+                let step = offset.clamp_length_max(speed * time.delta_seconds()) / time.delta_seconds();
+                velocity.linvel = Vec3::new(step.x, 0.0, step.y);
+                if offset != Vec2::ZERO {
+                    transform.rotation = Quat::from_rotation_y((-offset.x).atan2(-offset.y));
+                }
+            }
+            position.value = transform.translation;
+        }
         return;
     }
+    path.clear();
 
     let camera_transform = match camera_query.get_single() {
         Ok(t) => t,
@@ -36,16 +68,17 @@ fn player_movement(
     let forward = camera_transform.forward().xz().normalize_or_zero();
     let right = camera_transform.right().xz().normalize_or_zero();
 
-    // Calculate movement direction in world space (single operation)
-    let move_dir = (right * input.x + forward * input.y).normalize_or_zero();
-    let move_vec = Vec3::new(move_dir.x, 0.0, move_dir.y) * player.speed;
+    // Calculate movement direction in world space, keeping the input's share of full speed
+    let move_dir = (right * input.x + forward * input.y).normalize_or_zero() * input.length().min(1.0);
+    let move_vec = Vec3::new(move_dir.x, 0.0, move_dir.y) * speed;
     
     // Update velocity
     velocity.linvel = move_vec;
 
     // Only update rotation if we're moving
     if move_vec.length_squared() > 0.01 {
-        let target_rot = Quat::from_rotation_y(move_vec.x.atan2(move_vec.z));
+        // Bevy's forward is -Z, so point -Z along the movement direction
+        let target_rot = Quat::from_rotation_y((-move_vec.x).atan2(-move_vec.z));
         // Use a fixed rotation speed for consistency
         transform.rotation = transform.rotation.slerp(target_rot, 0.2);
     }
@@ -0,0 +1,532 @@
+//! Navigation: a walkability grid over the ground built from the static colliders that
+//! stand on it (trees, rocks, NPCs, the stockpile), kept up to date as nodes deplete,
+//! respawn and despawn, and A* paths across it for workers and click-to-move.
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use bevy_rapier3d::rapier::math::Isometry;
+use crate::components::bindings::Action;
+use crate::components::types::Player;
+use crate::systems::input::TickActions;
+use crate::systems::GameSet;
+
+/// Side of a grid cell in world units.
+pub const NAV_CELL_SIZE: f32 = 0.5;
+/// Half the side of the square of ground the grid covers, around the origin.
+pub const NAV_HALF_EXTENT: f32 = 50.0;
+/// How far obstacles are grown, so a walker's body clears them and not just its center.
+pub const AGENT_RADIUS: f32 = 0.4;
+/// Colliders whose top is no higher than this are ground, not obstacles.
+const GROUND_HEIGHT: f32 = 0.05;
+/// Most cells one path query looks at before giving up.
+const MAX_EXPANSIONS: usize = 40_000;
+/// Rings of blocked cells around a goal inside an obstacle a path may cross to reach it.
+const GOAL_RINGS: i32 = 4;
+/// Rings of blocked cells around a start inside an obstacle's margin a path may cross to leave it.
+const ESCAPE_RINGS: i32 = 2;
+/// How many times more a step through a blocked cell costs than one over free ground.
+const BLOCKED_STEP_COST: f32 = 4.0;
+/// How close a walker gets to a waypoint before heading for the next one.
+pub const WAYPOINT_REACHED: f32 = 0.1;
+
+/// Plugin for the navigation grid.
+pub struct GameNavigationPlugin;
+
+impl Plugin for GameNavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NavGrid>()
+            .init_resource::<HoveredGround>()
+            .add_event::<WalkRequest>()
+            .add_systems(Update, update_hovered_ground)
+            .add_systems(FixedUpdate, (walk_actions, plan_walks).chain().in_set(GameSet::Input))
+            // Walkers path through this tick's obstacles
+            .add_systems(FixedUpdate, sync_nav_grid.in_set(GameSet::Movement))
+            // Also sync every frame, so despawns between fixed ticks are never missed
+            .add_systems(PostUpdate, sync_nav_grid);
+    }
+}
+
+/// Event: Asks the player to walk to a spot on the ground along a path around
+/// obstacles. Sent by the walk-to action, but any system can send one.
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct WalkRequest {
+    pub destination: Vec3,
+}
+
+/// Resource: The spot on the ground under the mouse cursor, if any.
+#[derive(Resource, Default, Clone, Copy, Debug, PartialEq)]
+pub struct HoveredGround(pub Option<Vec3>);
+
+/// Component: A path being walked, planned on the [`NavGrid`] and planned again
+/// whenever the destination changes or the grid changes along the way.
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct PathFollower {
+    /// Where the path leads, `None` when not walking anywhere.
+    pub destination: Option<Vec3>,
+    /// Waypoints still ahead, the next one first.
+    pub waypoints: Vec<Vec3>,
+    /// [`NavGrid::version`] the path was last checked against, `None` for a path
+    /// restored from a save, which holds on whatever grid it is next walked on.
+    grid_version: Option<u64>,
+    /// Whether the last plan found a way to the destination.
+    found_path: bool,
+}
+
+impl PathFollower {
+    /// Plans a path from `from` to `to`, or clears the path and returns `false`
+    /// if `to` cannot be reached.
+    pub fn plan(&mut self, grid: &NavGrid, from: Vec3, to: Vec3) -> bool {
+        match grid.find_path(from, to) {
+            Some(waypoints) => {
+                self.destination = Some(to);
+                self.waypoints = waypoints;
+                self.grid_version = Some(grid.version());
+                self.found_path = true;
+                true
+            }
+            None => {
+                self.clear();
+                false
+            }
+        }
+    }
+
+    /// Stops walking.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Walks on along `waypoints` to `destination`, as planned before a save, without
+    /// planning again on the next grid.
+    pub fn resume(&mut self, destination: Vec3, waypoints: Vec<Vec3>) {
+        *self = Self { destination: Some(destination), waypoints, grid_version: None, found_path: true };
+    }
+
+    /// The point a walker at `from` heading for `to` should walk toward next: the next
+    /// waypoint of a path to `to`, planned again if `to` changed or the grid changed
+    /// along what is left of the path (or anywhere, while there is no way there), or
+    /// `to` itself once the waypoints run out or when there is no way around.
+    pub fn steer(&mut self, grid: &NavGrid, from: Vec3, to: Vec3) -> Vec3 {
+        let grid_changed = self.grid_version.is_some_and(|version| {
+            version != grid.version()
+                && (!self.found_path
+                    || grid.changed_along(
+                        version,
+                        std::iter::once(from).chain(self.waypoints.iter().copied()).chain(std::iter::once(to)),
+                    ))
+        });
+        if self.destination != Some(to) || grid_changed {
+            self.plan(grid, from, to);
+            // Remember the destination even without a path, so it is not planned every tick
+            self.destination = Some(to);
+        }
+        // Changes elsewhere on the grid leave the path as it is
+        self.grid_version = Some(grid.version());
+        let reached = self
+            .waypoints
+            .iter()
+            .take_while(|waypoint| (**waypoint - from).xz().length() <= WAYPOINT_REACHED)
+            .count();
+        self.waypoints.drain(..reached);
+        self.waypoints.first().copied().unwrap_or(to)
+    }
+}
+
+/// Resource: Which cells of the ground can be walked on. Every obstacle marks the
+/// cells under its footprint, grown by [`AGENT_RADIUS`]; a cell is walkable while no
+/// obstacle marks it. Obstacles are tracked by entity, so one can move, leave or
+/// come back without rebuilding the rest of the grid.
+#[derive(Resource, Clone, Debug)]
+pub struct NavGrid {
+    cell_size: f32,
+    half_extent: f32,
+    /// Number of obstacles covering each blocked cell.
+    blocked: HashMap<IVec2, u32>,
+    /// Cells each obstacle covers.
+    obstacles: HashMap<Entity, Vec<IVec2>>,
+    /// Bumped on every change, so walkers know when to look for a new path.
+    version: u64,
+    /// The version each cell last changed in, so walkers only look for a new path
+    /// when their own way changed.
+    changed_at: HashMap<IVec2, u64>,
+}
+
+impl Default for NavGrid {
+    fn default() -> Self {
+        Self::new(NAV_CELL_SIZE, NAV_HALF_EXTENT)
+    }
+}
+
+impl NavGrid {
+    /// An empty grid of `cell_size` cells over the square of ground reaching
+    /// `half_extent` from the origin.
+    pub fn new(cell_size: f32, half_extent: f32) -> Self {
+        Self {
+            cell_size,
+            half_extent,
+            blocked: HashMap::new(),
+            obstacles: HashMap::new(),
+            version: 0,
+            changed_at: HashMap::new(),
+        }
+    }
+
+    /// Changes every time an obstacle is added, moved or removed.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Number of obstacles on the grid.
+    pub fn obstacle_count(&self) -> usize {
+        self.obstacles.len()
+    }
+
+    /// Marks `owner` as an obstacle covering the ground between `min` and `max`
+    /// (only x and z count), replacing wherever it was before.
+    pub fn set_obstacle(&mut self, owner: Entity, min: Vec3, max: Vec3) {
+        let min = self.cell(min - Vec3::splat(AGENT_RADIUS));
+        let max = self.cell(max + Vec3::splat(AGENT_RADIUS));
+        let cells: Vec<IVec2> = (min.x..=max.x).flat_map(|x| (min.y..=max.y).map(move |z| IVec2::new(x, z))).collect();
+        if self.obstacles.get(&owner) == Some(&cells) {
+            return;
+        }
+        self.remove_obstacle(owner);
+        self.version += 1;
+        for cell in &cells {
+            *self.blocked.entry(*cell).or_default() += 1;
+            self.changed_at.insert(*cell, self.version);
+        }
+        self.obstacles.insert(owner, cells);
+    }
+
+    /// Clears `owner`'s cells. Returns whether it was an obstacle.
+    pub fn remove_obstacle(&mut self, owner: Entity) -> bool {
+        let Some(cells) = self.obstacles.remove(&owner) else {
+            return false;
+        };
+        self.version += 1;
+        for cell in cells {
+            if let Some(count) = self.blocked.get_mut(&cell) {
+                *count -= 1;
+                if *count == 0 {
+                    self.blocked.remove(&cell);
+                }
+            }
+            self.changed_at.insert(cell, self.version);
+        }
+        true
+    }
+
+    /// Whether any cell a straight walk through `points` in order crosses changed
+    /// after version `since`.
+    pub fn changed_along(&self, since: u64, points: impl IntoIterator<Item = Vec3>) -> bool {
+        let changed = |cell: IVec2| self.changed_at.get(&cell).is_some_and(|version| *version > since);
+        let mut points = points.into_iter().map(|point| self.cell(point));
+        let Some(mut previous) = points.next() else {
+            return false;
+        };
+        if changed(previous) {
+            return true;
+        }
+        points.any(|cell| {
+            let crossed = self.line_cells(previous, cell).any(changed);
+            previous = cell;
+            crossed
+        })
+    }
+
+    /// Whether the cell under `position` is on the grid and free of obstacles.
+    pub fn is_walkable(&self, position: Vec3) -> bool {
+        self.cell_walkable(self.cell(position))
+    }
+
+    /// A path from `from` to `to` as waypoints to walk through in order, ending at `to`,
+    /// or, if `to` is inside an obstacle (e.g. the node a worker heads for), where the
+    /// path first reaches that obstacle's margin. Waypoints are at `from`'s height, with
+    /// every corner that can be cut in a straight line cut. `None` if `to` cannot be
+    /// reached; a walker standing inside an obstacle's margin can still walk out of it.
+    pub fn find_path(&self, from: Vec3, to: Vec3) -> Option<Vec<Vec3>> {
+        let start = self.cell(from);
+        let goal = self.cell(to);
+        if !self.in_bounds(goal) {
+            return None;
+        }
+        let mut cells = self.search(start, goal)?;
+        // Stop at the edge of the goal's obstacle rather than walking into it
+        let reaches_goal = self.cell_walkable(goal);
+        if !reaches_goal {
+            let edge = cells.iter().rposition(|cell| self.cell_walkable(*cell)).unwrap_or(0);
+            cells.truncate(edge + 1);
+        }
+        let mut waypoints: Vec<Vec3> = self
+            .smooth(&cells)
+            .into_iter()
+            .skip(1)
+            .map(|cell| self.center(cell, from.y))
+            .collect();
+        // End on the exact spot asked for when it is walkable
+        if reaches_goal && !waypoints.is_empty() {
+            waypoints.pop();
+            waypoints.push(Vec3::new(to.x, from.y, to.z));
+        }
+        Some(waypoints)
+    }
+
+    fn cell(&self, position: Vec3) -> IVec2 {
+        (position.xz() / self.cell_size).floor().as_ivec2()
+    }
+
+    fn center(&self, cell: IVec2, height: f32) -> Vec3 {
+        let center = (cell.as_vec2() + Vec2::splat(0.5)) * self.cell_size;
+        Vec3::new(center.x, height, center.y)
+    }
+
+    fn in_bounds(&self, cell: IVec2) -> bool {
+        let limit = (self.half_extent / self.cell_size).ceil() as i32;
+        (-limit..limit).contains(&cell.x) && (-limit..limit).contains(&cell.y)
+    }
+
+    fn cell_walkable(&self, cell: IVec2) -> bool {
+        self.in_bounds(cell) && !self.blocked.contains_key(&cell)
+    }
+
+    /// A* over the 8-connected grid, never cutting the corner of a blocked cell.
+    /// Returns the cells from `start` to `goal`.
+    ///
+    /// Blocked cells are only crossed, at a higher cost, to walk out of the obstacle
+    /// `start` is in (within [`ESCAPE_RINGS`] of it) or into the one `goal` is in
+    /// (within [`GOAL_RINGS`] of it). Once into the goal's obstacle there is no coming
+    /// back out, so a path never cuts through an obstacle on its way.
+    fn search(&self, start: IVec2, goal: IVec2) -> Option<Vec<IVec2>> {
+        let escaping = |cell: IVec2| {
+            !self.cell_walkable(start) && self.in_bounds(cell) && chebyshev(cell, start) <= ESCAPE_RINGS
+        };
+        let approaching = |cell: IVec2| {
+            !self.cell_walkable(goal) && self.in_bounds(cell) && chebyshev(cell, goal) <= GOAL_RINGS
+        };
+        let can_step = |from: IVec2, to: IVec2| {
+            if self.cell_walkable(to) {
+                self.cell_walkable(from) || escaping(from)
+            } else {
+                (escaping(to) && escaping(from)) || approaching(to)
+            }
+        };
+
+        let mut open = BinaryHeap::new();
+        let mut came_from: HashMap<IVec2, IVec2> = HashMap::new();
+        let mut cost: HashMap<IVec2, f32> = HashMap::from([(start, 0.0)]);
+        open.push(OpenCell { cell: start, cost: 0.0, estimate: octile(start, goal) });
+
+        let mut expansions = 0;
+        while let Some(OpenCell { cell, cost: pushed_cost, .. }) = open.pop() {
+            // A cheaper way here was found after this entry was pushed, and already expanded
+            if pushed_cost > cost[&cell] {
+                continue;
+            }
+            if cell == goal {
+                let mut cells = vec![goal];
+                while let Some(previous) = came_from.get(cells.last().unwrap()) {
+                    cells.push(*previous);
+                }
+                cells.reverse();
+                return Some(cells);
+            }
+            expansions += 1;
+            if expansions > MAX_EXPANSIONS {
+                return None;
+            }
+            let here = cost[&cell];
+            for (step, step_cost) in NEIGHBORS {
+                let next = cell + step;
+                if !can_step(cell, next) {
+                    continue;
+                }
+                let diagonal = step.x != 0 && step.y != 0;
+                let corners = [cell + IVec2::new(step.x, 0), cell + IVec2::new(0, step.y)];
+                if diagonal && !corners.into_iter().all(|corner| can_step(cell, corner)) {
+                    continue;
+                }
+                let next_cost = here + if self.cell_walkable(next) { step_cost } else { step_cost * BLOCKED_STEP_COST };
+                if cost.get(&next).is_some_and(|known| *known <= next_cost) {
+                    continue;
+                }
+                cost.insert(next, next_cost);
+                came_from.insert(next, cell);
+                open.push(OpenCell { cell: next, cost: next_cost, estimate: next_cost + octile(next, goal) });
+            }
+        }
+        None
+    }
+
+    /// Drops every cell of `cells` that the path can skip by walking straight from the
+    /// last kept one, keeping the first and last.
+    fn smooth(&self, cells: &[IVec2]) -> Vec<IVec2> {
+        let Some(&first) = cells.first() else {
+            return Vec::new();
+        };
+        let mut kept = vec![first];
+        let mut index = 0;
+        while index + 1 < cells.len() {
+            // Farthest cell still in a straight, clear line of the last kept one
+            let mut next = index + 1;
+            while next + 1 < cells.len() && self.line_is_clear(cells[index], cells[next + 1]) {
+                next += 1;
+            }
+            kept.push(cells[next]);
+            index = next;
+        }
+        kept
+    }
+
+    /// Whether every cell a straight walk from `from` to `to` crosses is walkable, or
+    /// blocked but part of the walk out of an obstacle `from` is in.
+    fn line_is_clear(&self, from: IVec2, to: IVec2) -> bool {
+        let mut leaving = !self.cell_walkable(from);
+        self.line_cells(from, to).all(|cell| {
+            leaving &= !self.cell_walkable(cell);
+            leaving || self.cell_walkable(cell)
+        })
+    }
+
+    /// The cells a straight walk between the centers of `from` and `to` crosses, in order
+    /// (some more than once).
+    fn line_cells(&self, from: IVec2, to: IVec2) -> impl Iterator<Item = IVec2> {
+        let a = from.as_vec2() + Vec2::splat(0.5);
+        let b = to.as_vec2() + Vec2::splat(0.5);
+        let steps = ((b - a).abs().max_element() * 8.0).ceil().max(1.0) as i32;
+        (0..=steps).map(move |step| a.lerp(b, step as f32 / steps as f32).floor().as_ivec2())
+    }
+}
+
+/// The 8 neighboring cells and the cost of stepping to each, in cells.
+const NEIGHBORS: [(IVec2, f32); 8] = [
+    (IVec2::new(1, 0), 1.0),
+    (IVec2::new(-1, 0), 1.0),
+    (IVec2::new(0, 1), 1.0),
+    (IVec2::new(0, -1), 1.0),
+    (IVec2::new(1, 1), std::f32::consts::SQRT_2),
+    (IVec2::new(1, -1), std::f32::consts::SQRT_2),
+    (IVec2::new(-1, 1), std::f32::consts::SQRT_2),
+    (IVec2::new(-1, -1), std::f32::consts::SQRT_2),
+];
+
+/// Number of rings of cells between two cells.
+fn chebyshev(a: IVec2, b: IVec2) -> i32 {
+    (a - b).abs().max_element()
+}
+
+/// Shortest distance in cells between two cells when walking in 8 directions.
+fn octile(a: IVec2, b: IVec2) -> f32 {
+    let d = (a - b).abs();
+    let (long, short) = (d.x.max(d.y) as f32, d.x.min(d.y) as f32);
+    long + (std::f32::consts::SQRT_2 - 1.0) * short
+}
+
+/// A cell waiting to be looked at, ordered so the heap pops the lowest estimate first
+/// and breaks ties the same way every time.
+#[derive(PartialEq)]
+struct OpenCell {
+    cell: IVec2,
+    /// Cost of the way here when pushed.
+    cost: f32,
+    estimate: f32,
+}
+
+impl Eq for OpenCell {}
+
+impl Ord for OpenCell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .total_cmp(&self.estimate)
+            .then_with(|| (other.cell.x, other.cell.y).cmp(&(self.cell.x, self.cell.y)))
+    }
+}
+
+impl PartialOrd for OpenCell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Static colliders whose shape, position or enabled state changed since the grid was last synced.
+type ChangedObstacles = Or<(Added<Collider>, Changed<Transform>, Added<ColliderDisabled>)>;
+
+/// Brings the grid up to date with the static colliders that spawned, moved, were
+/// disabled (depleted nodes), enabled again or despawned since the last sync.
+/// Colliders that do not rise above the ground, like the ground itself, are not obstacles.
+pub fn sync_nav_grid(
+    mut grid: ResMut<NavGrid>,
+    mut removed: RemovedComponents<Collider>,
+    mut enabled: RemovedComponents<ColliderDisabled>,
+    changed: Query<Entity, (With<Collider>, ChangedObstacles)>,
+    obstacles: Query<(&Collider, &Transform, &RigidBody, Has<ColliderDisabled>)>,
+) {
+    for entity in removed.read() {
+        grid.remove_obstacle(entity);
+    }
+    for entity in enabled.read().chain(changed.iter()) {
+        let Ok((collider, transform, body, disabled)) = obstacles.get(entity) else {
+            continue;
+        };
+        if *body != RigidBody::Fixed || disabled {
+            grid.remove_obstacle(entity);
+            continue;
+        }
+        let aabb = collider.raw.compute_aabb(&Isometry::from_parts(transform.translation.into(), transform.rotation.into()));
+        let (min, max) = (Vec3::from(aabb.mins), Vec3::from(aabb.maxs));
+        if max.y <= GROUND_HEIGHT {
+            continue;
+        }
+        grid.set_obstacle(entity, min, max);
+    }
+}
+
+/// Tracks which spot on the ground is under the mouse cursor.
+fn update_hovered_ground(
+    windows: Query<&Window>,
+    camera: Query<(&Camera, &GlobalTransform)>,
+    mut hovered: ResMut<HoveredGround>,
+) {
+    // Nothing to point at without a window and camera (e.g. headless)
+    let (Ok((camera, camera_transform)), Ok(window)) = (camera.get_single(), windows.get_single()) else {
+        return;
+    };
+    let spot = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor))
+        .and_then(|ray| ray.intersect_plane(Vec3::ZERO, Plane3d::new(Vec3::Y)).map(|distance| ray.get_point(distance)));
+    if hovered.0 != spot {
+        hovered.0 = spot;
+    }
+}
+
+/// Turns the walk-to action into a [`WalkRequest`] for the spot under the cursor.
+fn walk_actions(actions: Res<TickActions>, hovered: Res<HoveredGround>, mut requests: EventWriter<WalkRequest>) {
+    if actions.just_pressed(Action::WalkTo) {
+        if let Some(destination) = hovered.0 {
+            requests.send(WalkRequest { destination });
+        }
+    }
+}
+
+/// Plans the player's path for every [`WalkRequest`]; the player then walks it while
+/// there is no movement input.
+fn plan_walks(
+    mut requests: EventReader<WalkRequest>,
+    grid: Res<NavGrid>,
+    mut player_query: Query<(&Transform, &mut PathFollower), With<Player>>,
+) {
+    let Ok((transform, mut follower)) = player_query.get_single_mut() else {
+        return;
+    };
+    for request in requests.read() {
+        if !follower.plan(&grid, transform.translation, request.destination) {
+            println!("Can't find a way there.");
+            continue;
+        }
+        // Stop at the closest spot the path reaches when the destination is inside an obstacle
+        follower.destination = follower.waypoints.last().copied();
+    }
+}
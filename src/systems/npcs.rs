@@ -0,0 +1,55 @@
+//! Characters other than the player: talking to them.
+use bevy::prelude::*;
+use crate::components::bindings::Action;
+use crate::components::types::{Npc, Player, Position};
+use crate::components::workers::Worker;
+use crate::systems::input::TickActions;
+use crate::systems::GameSet;
+
+/// Farthest the player can be from an NPC to talk to it.
+pub const TALK_RANGE: f32 = 3.0;
+
+/// Plugin for talking to NPCs.
+pub struct GameNpcPlugin;
+
+impl Plugin for GameNpcPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<NpcTalkedTo>()
+            .add_systems(FixedUpdate, talk_to_npcs.in_set(GameSet::Input));
+    }
+}
+
+/// Event: The player talked to an NPC.
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct NpcTalkedTo {
+    /// Id of the NPC.
+    pub npc: String,
+}
+
+/// Talks to the closest NPC within [`TALK_RANGE`] when the talk action is pressed.
+/// Workers take jobs rather than talk, so they never stand in the way of another NPC.
+fn talk_to_npcs(
+    actions: Res<TickActions>,
+    player_query: Query<&Position, With<Player>>,
+    npc_query: Query<(&Npc, &Position), Without<Worker>>,
+    mut talked: EventWriter<NpcTalkedTo>,
+) {
+    if !actions.just_pressed(Action::Talk) {
+        return;
+    }
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    let closest = npc_query
+        .iter()
+        .map(|(npc, position)| (npc, position.value.distance(player.value)))
+        .filter(|(_, distance)| *distance <= TALK_RANGE)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b));
+    match closest {
+        Some((npc, _)) => {
+            println!("Talking to {}.", npc.name);
+            talked.send(NpcTalkedTo { npc: npc.id.clone() });
+        }
+        None => println!("Nobody to talk to."),
+    }
+}
@@ -0,0 +1,67 @@
+//! Seeded placement of resource nodes, so the same `WorldSeed` always produces the same map.
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use crate::components::types::WorldSeed;
+
+/// Number of random candidates tried for each point before giving up on it.
+const PLACEMENT_ATTEMPTS: u32 = 30;
+
+/// Where resource nodes may be placed on the ground plane (x, z).
+#[derive(Clone, Copy, Debug)]
+pub struct PlacementRules {
+    /// Nodes are placed within `-half_extent..half_extent` on both axes.
+    pub half_extent: f32,
+    /// Minimum distance between any two nodes.
+    pub min_spacing: f32,
+    /// Center of the zone kept free of nodes (the player spawn).
+    pub clear_center: Vec2,
+    /// Radius of the zone kept free of nodes.
+    pub clear_radius: f32,
+}
+
+impl Default for PlacementRules {
+    fn default() -> Self {
+        Self {
+            half_extent: 20.0,
+            min_spacing: 3.0,
+            clear_center: Vec2::ZERO,
+            clear_radius: 5.0,
+        }
+    }
+}
+
+impl PlacementRules {
+    /// Whether a node may go at `candidate`: inside the area, outside the clear zone and
+    /// at least `min_spacing` away from every node already `placed`.
+    pub fn allows(&self, candidate: Vec2, placed: impl IntoIterator<Item = Vec2>) -> bool {
+        let in_area = candidate.abs().max_element() <= self.half_extent;
+        let in_clear_zone = candidate.distance(self.clear_center) < self.clear_radius;
+        in_area && !in_clear_zone && placed.into_iter().all(|point| point.distance(candidate) >= self.min_spacing)
+    }
+}
+
+/// Deterministic RNG for world generation.
+pub fn world_rng(seed: WorldSeed) -> ChaCha8Rng {
+    ChaCha8Rng::seed_from_u64(seed.0)
+}
+
+/// Scatters up to `count` points following `rules`, Poisson-disk style: each point is
+/// drawn at random and rejected if it lands too close to an earlier one or inside the
+/// clear zone. Returns fewer points only if the area is too crowded to fit them all.
+pub fn scatter_points(rng: &mut impl Rng, count: usize, rules: &PlacementRules) -> Vec<Vec2> {
+    let mut points: Vec<Vec2> = Vec::with_capacity(count);
+    for _ in 0..count {
+        for _ in 0..PLACEMENT_ATTEMPTS {
+            let candidate = Vec2::new(
+                rng.gen_range(-rules.half_extent..rules.half_extent),
+                rng.gen_range(-rules.half_extent..rules.half_extent),
+            );
+            if rules.allows(candidate, points.iter().copied()) {
+                points.push(candidate);
+                break;
+            }
+        }
+    }
+    points
+}
@@ -0,0 +1,108 @@
+//! Quests: starts quests once their prerequisites are done, counts gathering, crafting,
+//! talking and where the player walks toward their objectives, and hands out rewards.
+use bevy::prelude::*;
+use crate::components::equipment::{Equipment, Tool};
+use crate::components::quests::{QuestActivity, QuestBook, QuestLog, Reward};
+use crate::components::skills::{SkillCurve, Skills};
+use crate::components::types::{Player, PlayerInventory, Position};
+use crate::systems::config::add_config;
+use crate::systems::crafting::CraftCompleted;
+use crate::systems::npcs::NpcTalkedTo;
+use crate::systems::resources::ResourceGathered;
+use crate::systems::skills::SkillLevelUp;
+use crate::systems::GameSet;
+
+/// Plugin for the quests file, the quest log and quest rewards.
+pub struct GameQuestsPlugin;
+
+impl Plugin for GameQuestsPlugin {
+    fn build(&self, app: &mut App) {
+        add_config::<QuestBook>(app);
+        app.init_resource::<QuestBook>()
+            .init_resource::<QuestLog>()
+            .add_event::<QuestStarted>()
+            .add_event::<QuestCompleted>()
+            .add_systems(FixedUpdate, (start_quests, track_quests).chain().in_set(GameSet::Quests));
+    }
+}
+
+/// Event: A quest's prerequisites were all completed and it started.
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct QuestStarted {
+    pub quest: String,
+}
+
+/// Event: Every objective of a quest is done and its rewards were handed out.
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct QuestCompleted {
+    pub quest: String,
+}
+
+/// Starts every quest whose prerequisites are completed.
+fn start_quests(book: Res<QuestBook>, mut log: ResMut<QuestLog>, mut started: EventWriter<QuestStarted>) {
+    for quest in log.start_available(&book) {
+        if let Some(definition) = book.get(&quest) {
+            println!("New quest: {}", definition.title);
+        }
+        started.send(QuestStarted { quest });
+    }
+}
+
+/// Counts this tick's gathering, crafting, talking and the player's position toward
+/// the active quests, and rewards the quests that complete.
+fn track_quests(
+    book: Res<QuestBook>,
+    mut log: ResMut<QuestLog>,
+    mut gathered: EventReader<ResourceGathered>,
+    mut crafted: EventReader<CraftCompleted>,
+    mut talked: EventReader<NpcTalkedTo>,
+    mut completed: EventWriter<QuestCompleted>,
+    mut level_ups: EventWriter<SkillLevelUp>,
+    mut inventory: ResMut<PlayerInventory>,
+    curve: Res<SkillCurve>,
+    mut player_query: Query<(&Position, &mut Equipment, &mut Skills), With<Player>>,
+) {
+    let Ok((position, mut equipment, mut skills)) = player_query.get_single_mut() else {
+        return;
+    };
+    let mut activities: Vec<QuestActivity> = gathered
+        .read()
+        .map(|hit| QuestActivity::Gathered { resource: hit.resource, amount: hit.gained })
+        .collect();
+    activities.extend(crafted.read().map(|craft| QuestActivity::Crafted { recipe: &craft.recipe }));
+    activities.extend(talked.read().map(|talk| QuestActivity::TalkedTo { npc: &talk.npc }));
+    activities.push(QuestActivity::At { position: position.value });
+
+    for activity in &activities {
+        for quest in log.record(&book, activity) {
+            let Some(definition) = book.get(&quest) else {
+                continue;
+            };
+            println!("Quest complete: {}", definition.title);
+            for reward in &definition.rewards {
+                match *reward {
+                    Reward::Items(item, amount) => {
+                        let added = inventory.add_up_to(item, amount);
+                        if added < amount {
+                            println!("No room for {} of the {} {} reward", amount - added, amount, item.id());
+                        }
+                    }
+                    Reward::Xp(skill, xp) => {
+                        if let Some(level) = skills.add_xp(skill, xp, &curve) {
+                            println!("{} is now level {}!", skill.name(), level);
+                            level_ups.send(SkillLevelUp { skill, level });
+                        }
+                    }
+                    Reward::Tool(kind, tier) => {
+                        if equipment.receive(Tool::new(kind, tier)) {
+                            println!("Received a {} {}", tier.name(), kind.name());
+                        } else {
+                            println!("Received a {} {}, kept as a spare", tier.name(), kind.name());
+                        }
+                    }
+                }
+            }
+            completed.send(QuestCompleted { quest });
+        }
+    }
+}
@@ -0,0 +1,464 @@
+//! Records the player's input every fixed tick and replays it headlessly, so a
+//! session can be reproduced exactly and attached to a bug report.
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use bevy::prelude::*;
+use bevy::app::FixedMain;
+use bevy::ecs::system::RunSystemOnce;
+use bevy::time::Fixed;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use crate::components::bindings::Action;
+use crate::components::types::{Player, PlayerInventory, Position, ResourceNode, ResourceType, WorldSeed};
+use crate::systems::camera::{CameraMode, MainCamera};
+use crate::systems::config::{config_load_state, ConfigLoadState};
+use crate::systems::input::{action_just_pressed, latch_tick_actions, InjectedMovement, MovementInput, TickActions};
+use crate::systems::navigation::HoveredGround;
+use crate::systems::resources::HoveredNode;
+use crate::systems::save::{apply_save, capture_save, SaveError, SaveGame};
+use crate::systems::GameSet;
+use crate::HeadlessGamePlugin;
+
+/// Version written to new recordings. Bump it whenever [`InputRecording`] changes shape.
+pub const RECORDING_VERSION: u32 = 11;
+/// Default recording file, relative to the working directory.
+pub const RECORDING_PATH: &str = "recordings/recording.ron";
+/// Largest distance between the recorded and replayed final player positions
+/// that still counts as a match.
+pub const REPLAY_POSITION_TOLERANCE: f32 = 0.01;
+/// Longest a replay waits for the game's data files to load.
+const REPLAY_LOAD_TIMEOUT: Duration = Duration::from_secs(30);
+/// Number of craft slots whose actions are recorded.
+const RECORDED_CRAFT_SLOTS: usize = 9;
+
+/// Plugin for recording input to a file (F6 by default to start and stop).
+pub struct GameReplayPlugin;
+
+impl Plugin for GameReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputRecorder>()
+            .init_resource::<RecordingSettings>()
+            // Record what the input systems produced, before anything acts on it
+            .add_systems(FixedUpdate, record_input.after(GameSet::Input).before(GameSet::Movement))
+            .add_systems(Update, toggle_recording.run_if(action_just_pressed(Action::ToggleRecording)));
+    }
+}
+
+/// Resource: Where finished recordings are written.
+#[derive(Resource, Clone, Debug)]
+pub struct RecordingSettings {
+    pub path: PathBuf,
+}
+
+impl Default for RecordingSettings {
+    fn default() -> Self {
+        Self { path: PathBuf::from(RECORDING_PATH) }
+    }
+}
+
+/// Orbit of the main camera around the player. Recorded per tick as the change since the last tick.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct CameraOrbit {
+    pub angle: f32,
+    pub distance: f32,
+    pub pitch: f32,
+}
+
+impl CameraOrbit {
+    fn of(camera: &MainCamera) -> Self {
+        Self { angle: camera.angle, distance: camera.distance, pitch: camera.pitch }
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn difference(&self, from: &CameraOrbit) -> Self {
+        Self {
+            angle: self.angle - from.angle,
+            distance: self.distance - from.distance,
+            pitch: self.pitch - from.pitch,
+        }
+    }
+
+    fn add(&mut self, delta: &CameraOrbit) {
+        self.angle += delta.angle;
+        self.distance += delta.distance;
+        self.pitch += delta.pitch;
+    }
+}
+
+/// Input of one fixed tick.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct InputFrame {
+    pub movement: Vec2,
+    /// Gameplay actions just pressed this tick (gathering, talking, assigning jobs and crafting).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub actions: Vec<Action>,
+    /// Position of the node clicked to gather this tick. Nodes are found by position,
+    /// as they are new entities in the replayed world.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pick: Option<Vec3>,
+    /// Spot on the ground clicked to walk to this tick.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub walk_to: Option<Vec3>,
+    /// Change of the camera orbit since the previous tick.
+    #[serde(default, skip_serializing_if = "CameraOrbit::is_zero")]
+    pub camera: CameraOrbit,
+    /// Camera mode switched to this tick.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub camera_mode: Option<CameraMode>,
+}
+
+/// The results a replay is checked against.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ReplayOutcome {
+    pub inventory: HashMap<ResourceType, u32>,
+    pub player_translation: Vec3,
+}
+
+/// A recorded session: the game state it started from, the input of every fixed
+/// tick and the outcome it led to.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct InputRecording {
+    pub version: u32,
+    /// Game state, including the world seed, when recording started.
+    pub start: SaveGame,
+    pub camera_orbit: CameraOrbit,
+    #[serde(default)]
+    pub camera_mode: CameraMode,
+    pub camera_translation: Vec3,
+    pub camera_rotation: Quat,
+    /// Length of the player's gathering cooldown when recording started; it depends
+    /// on the tool of the last hit.
+    pub gather_cooldown_duration: Duration,
+    /// How far the player's gathering cooldown had run when recording started.
+    pub gather_cooldown_elapsed: Duration,
+    pub frames: Vec<InputFrame>,
+    pub outcome: ReplayOutcome,
+}
+
+/// Errors from reading or writing a recording.
+#[derive(Debug, Error)]
+pub enum RecordingError {
+    #[error("could not access recording: {0}")]
+    Io(#[from] io::Error),
+    #[error("could not write recording: {0}")]
+    Serialize(#[from] ron::Error),
+    #[error("could not parse recording: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("recording version {found} is not supported (expected {expected})")]
+    UnsupportedVersion { found: u32, expected: u32 },
+    #[error("could not capture or restore the game state: {0}")]
+    Save(#[from] SaveError),
+    #[error("could not load game data file {path}")]
+    GameData { path: &'static str },
+}
+
+impl InputRecording {
+    /// Serializes the recording as RON.
+    pub fn to_ron(&self) -> Result<String, RecordingError> {
+        Ok(ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?)
+    }
+
+    /// Parses a recording from RON, rejecting files written by another recording version.
+    pub fn from_ron(text: &str) -> Result<Self, RecordingError> {
+        let recording: InputRecording = ron::from_str(text)?;
+        if recording.version != RECORDING_VERSION {
+            return Err(RecordingError::UnsupportedVersion {
+                found: recording.version,
+                expected: RECORDING_VERSION,
+            });
+        }
+        Ok(recording)
+    }
+
+    /// Writes the recording to `path`, creating its directory if needed.
+    pub fn write(&self, path: &Path) -> Result<(), RecordingError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_ron()?)?;
+        Ok(())
+    }
+
+    /// Reads a recording from `path`.
+    pub fn read(path: &Path) -> Result<Self, RecordingError> {
+        Self::from_ron(&fs::read_to_string(path)?)
+    }
+}
+
+/// Resource: The recording in progress, if any.
+#[derive(Resource, Default)]
+pub struct InputRecorder {
+    recording: Option<InputRecording>,
+    /// Camera orbit as replay will see it after the last recorded tick.
+    last_camera: CameraOrbit,
+    last_camera_mode: CameraMode,
+}
+
+impl InputRecorder {
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+}
+
+/// Starts recording from the current game state, replacing any recording in progress.
+pub fn start_recording(world: &mut World) -> Result<(), RecordingError> {
+    let start = capture_save(world)?;
+    let (camera_orbit, camera_mode, camera_transform) = {
+        let mut cameras = world.query::<(&MainCamera, &Transform)>();
+        let (camera, transform) = cameras.single(world);
+        (CameraOrbit::of(camera), camera.mode, *transform)
+    };
+    let (gather_cooldown_duration, gather_cooldown_elapsed) = {
+        let mut players = world.query::<&Player>();
+        let cooldown = &players.single(world).gathering_cooldown;
+        (cooldown.duration(), cooldown.elapsed())
+    };
+
+    let mut recorder = world.resource_mut::<InputRecorder>();
+    recorder.last_camera = camera_orbit;
+    recorder.last_camera_mode = camera_mode;
+    recorder.recording = Some(InputRecording {
+        version: RECORDING_VERSION,
+        start,
+        camera_orbit,
+        camera_mode,
+        camera_translation: camera_transform.translation,
+        camera_rotation: camera_transform.rotation,
+        gather_cooldown_duration,
+        gather_cooldown_elapsed,
+        frames: Vec::new(),
+        outcome: ReplayOutcome::default(),
+    });
+    Ok(())
+}
+
+/// Stops recording and returns the finished recording, if one was in progress.
+pub fn stop_recording(world: &mut World) -> Option<InputRecording> {
+    let mut recording = world.resource_mut::<InputRecorder>().recording.take()?;
+    recording.outcome = capture_outcome(world);
+    Some(recording)
+}
+
+/// Result of replaying a recording.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplayReport {
+    pub expected: ReplayOutcome,
+    pub actual: ReplayOutcome,
+}
+
+impl ReplayReport {
+    /// Whether the replay ended with the recorded inventory and, within
+    /// [`REPLAY_POSITION_TOLERANCE`], the recorded player position.
+    pub fn matches(&self) -> bool {
+        let stacks = |outcome: &ReplayOutcome| {
+            let mut stacks: Vec<(&str, u32)> = outcome
+                .inventory
+                .iter()
+                .filter(|(_, amount)| **amount > 0)
+                .map(|(resource, amount)| (resource.id(), *amount))
+                .collect();
+            stacks.sort();
+            stacks
+        };
+        stacks(&self.expected) == stacks(&self.actual)
+            && self.expected.player_translation.distance(self.actual.player_translation)
+                <= REPLAY_POSITION_TOLERANCE
+    }
+}
+
+/// Replays `recording` in a headless game and reports whether it reproduces the recorded outcome.
+///
+/// The game loads its data files from the assets folder at runtime, as a played game does.
+pub fn replay(recording: &InputRecording) -> Result<ReplayReport, RecordingError> {
+    let mut app = headless_game(recording.start.world_seed)?;
+    let world = &mut app.world;
+    apply_save(world, &recording.start)?;
+    {
+        let mut cameras = world.query::<(&mut MainCamera, &mut Transform)>();
+        let (mut camera, mut transform) = cameras.single_mut(world);
+        camera.angle = recording.camera_orbit.angle;
+        camera.distance = recording.camera_orbit.distance;
+        camera.pitch = recording.camera_orbit.pitch;
+        camera.mode = recording.camera_mode;
+        transform.translation = recording.camera_translation;
+        transform.rotation = recording.camera_rotation;
+    }
+    {
+        let mut players = world.query::<&mut Player>();
+        let mut player = players.single_mut(world);
+        // Tick rather than set the elapsed time, so a cooldown that had run out counts as finished
+        player.gathering_cooldown.set_duration(recording.gather_cooldown_duration);
+        player.gathering_cooldown.reset();
+        player.gathering_cooldown.tick(recording.gather_cooldown_elapsed);
+    }
+
+    for frame in &recording.frames {
+        world.resource_mut::<InjectedMovement>().0 = Some(frame.movement);
+        let mut tick_actions = world.resource_mut::<TickActions>();
+        for action in &frame.actions {
+            tick_actions.press(*action);
+        }
+        // Point at the clicked node or ground, as the cursor did
+        let picked = frame.pick.and_then(|position| node_at(world, position));
+        if picked.is_some() {
+            world.resource_mut::<TickActions>().press(Action::Pick);
+        }
+        world.resource_mut::<HoveredNode>().0 = picked;
+        if frame.walk_to.is_some() {
+            world.resource_mut::<TickActions>().press(Action::WalkTo);
+        }
+        world.resource_mut::<HoveredGround>().0 = frame.walk_to;
+        let mut cameras = world.query::<&mut MainCamera>();
+        let mut camera = cameras.single_mut(world);
+        let mut orbit = CameraOrbit::of(&camera);
+        orbit.add(&frame.camera);
+        camera.angle = orbit.angle;
+        camera.distance = orbit.distance;
+        camera.pitch = orbit.pitch;
+        if let Some(mode) = frame.camera_mode {
+            camera.mode = mode;
+        }
+        run_fixed_tick(world);
+    }
+    world.resource_mut::<InjectedMovement>().0 = None;
+    world.resource_mut::<HoveredNode>().0 = None;
+    world.resource_mut::<HoveredGround>().0 = None;
+
+    Ok(ReplayReport {
+        expected: recording.outcome.clone(),
+        actual: capture_outcome(world),
+    })
+}
+
+/// Starts a headless game with `world_seed` and runs it until its data files have loaded
+/// and the world has been generated.
+fn headless_game(world_seed: u64) -> Result<App, RecordingError> {
+    let mut app = App::new();
+    app.insert_resource(WorldSeed(world_seed)).add_plugins((MinimalPlugins, HeadlessGamePlugin));
+    let started = Instant::now();
+    loop {
+        app.update();
+        match config_load_state(&app.world) {
+            ConfigLoadState::Loaded => break,
+            ConfigLoadState::Failed(path) => return Err(RecordingError::GameData { path }),
+            ConfigLoadState::Loading(path) if started.elapsed() > REPLAY_LOAD_TIMEOUT => {
+                return Err(RecordingError::GameData { path });
+            }
+            ConfigLoadState::Loading(_) => std::thread::sleep(Duration::from_millis(1)),
+        }
+    }
+    // Let the loaded files reach their resources, then the world generate from them
+    app.update();
+    app.update();
+    Ok(app)
+}
+
+/// Advances `world` by one fixed timestep as a frame running a single tick would.
+pub(crate) fn run_fixed_tick(world: &mut World) {
+    run_fixed_frame(world, 1);
+}
+
+/// Advances `world` by `ticks` fixed timesteps as one frame would: latches the frame's
+/// presses, runs only the fixed schedule so every tick matches a recorded one, then
+/// clears the presses as the per-frame input update would.
+pub(crate) fn run_fixed_frame(world: &mut World, ticks: u32) {
+    world.run_system_once(latch_tick_actions);
+
+    for _ in 0..ticks {
+        let mut fixed_time = world.resource_mut::<Time<Fixed>>();
+        let timestep = fixed_time.timestep();
+        fixed_time.advance_by(timestep);
+        *world.resource_mut::<Time>() = world.resource::<Time<Fixed>>().as_generic();
+        world.run_schedule(FixedMain);
+    }
+
+    world.resource_mut::<ButtonInput<KeyCode>>().clear();
+    world.resource_mut::<ButtonInput<MouseButton>>().clear();
+    world.resource_mut::<ButtonInput<GamepadButton>>().clear();
+}
+
+/// Reads the recording at `path` and replays it.
+pub fn replay_file(path: &Path) -> Result<ReplayReport, RecordingError> {
+    replay(&InputRecording::read(path)?)
+}
+
+/// The resource node at exactly `position`, if any.
+fn node_at(world: &mut World, position: Vec3) -> Option<Entity> {
+    let mut nodes = world.query_filtered::<(Entity, &Position), With<ResourceNode>>();
+    nodes.iter(world).find(|(_, at)| at.value == position).map(|(node, _)| node)
+}
+
+fn capture_outcome(world: &mut World) -> ReplayOutcome {
+    let mut players = world.query_filtered::<&Transform, With<Player>>();
+    let player_translation = players.single(world).translation;
+    ReplayOutcome {
+        inventory: world.resource::<PlayerInventory>().totals(),
+        player_translation,
+    }
+}
+
+/// Gameplay actions recorded per tick as pressed. Clicks are recorded by what they point
+/// at instead, as replays have no cursor.
+fn recorded_actions() -> impl Iterator<Item = Action> {
+    [Action::Gather, Action::Talk, Action::AssignJob].into_iter().chain((0..RECORDED_CRAFT_SLOTS).map(Action::Craft))
+}
+
+/// Appends this tick's input to the recording in progress.
+fn record_input(
+    mut recorder: ResMut<InputRecorder>,
+    movement: Res<MovementInput>,
+    actions: Res<TickActions>,
+    hovered: Res<HoveredNode>,
+    hovered_ground: Res<HoveredGround>,
+    node_query: Query<&Position, With<ResourceNode>>,
+    camera_query: Query<&MainCamera>,
+) {
+    let InputRecorder { recording: Some(recording), last_camera, last_camera_mode } = &mut *recorder else {
+        return;
+    };
+    let camera = camera_query.get_single().map_or(*last_camera, CameraOrbit::of);
+    let delta = camera.difference(last_camera);
+    // Follow the same arithmetic as replay, so rounding errors do not add up
+    last_camera.add(&delta);
+    let mode = camera_query.get_single().map_or(*last_camera_mode, |camera| camera.mode);
+    let camera_mode = (mode != *last_camera_mode).then_some(mode);
+    *last_camera_mode = mode;
+
+    recording.frames.push(InputFrame {
+        movement: movement.0,
+        actions: recorded_actions().filter(|action| actions.just_pressed(*action)).collect(),
+        pick: hovered
+            .0
+            .filter(|_| actions.just_pressed(Action::Pick))
+            .and_then(|node| node_query.get(node).ok())
+            .map(|node| node.value),
+        walk_to: hovered_ground.0.filter(|_| actions.just_pressed(Action::WalkTo)),
+        camera: delta,
+        camera_mode,
+    });
+}
+
+/// Starts recording, or stops and writes the recording in progress.
+fn toggle_recording(world: &mut World) {
+    if !world.resource::<InputRecorder>().is_recording() {
+        match start_recording(world) {
+            Ok(()) => println!("Recording input"),
+            Err(err) => eprintln!("Could not start recording: {}", err),
+        }
+        return;
+    }
+    let path = world.resource::<RecordingSettings>().path.clone();
+    let Some(recording) = stop_recording(world) else {
+        return;
+    };
+    match recording.write(&path) {
+        Ok(()) => println!("Recorded {} ticks to {}", recording.frames.len(), path.display()),
+        Err(err) => eprintln!("Could not write recording: {}", err),
+    }
+}
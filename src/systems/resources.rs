@@ -1,148 +1,503 @@
 //! Handles resource gathering logic and inventory tracking.
+use std::f32::consts::TAU;
+use std::time::Duration;
 use bevy::prelude::*;
-use bevy::prelude::Input;
-use bevy::input::mouse::MouseButton;
-use bevy::math::Vec3;
 use bevy_rapier3d::prelude::*;
-use crate::components::types::{Player, Position, Gatherable, ResourceNode, PlayerInventory, ResourceType};
+use thiserror::Error;
+use crate::components::types::{Player, Position, Gatherable, ResourceNode, ResourceType, PlayerInventory, YieldRule};
+use crate::components::definitions::{
+    ResourceRegistry,
+};
+use crate::components::bindings::Action;
+use crate::components::inventory::Inventory;
+use crate::components::time_of_day::TimeOfDay;
+use crate::components::weather::{Weather, WeatherRegistry};
+use crate::components::skills::{Skill, SkillCurve, Skills};
+use crate::components::equipment::{Equipment, Tool, ToolKind, ToolRequirement, ToolTier};
+use crate::systems::config::{add_config, sync_config};
+use crate::systems::input::TickActions;
+use crate::systems::spatial::{sync_node_index, NodeIndex};
+use crate::systems::GameSet;
 
-pub fn register(app: &mut App) {
-    // Run gathering in FixedUpdate for consistent timing
-    app.add_systems(FixedUpdate, gather_resources)
-        .add_systems(Update, handle_resource_click);
+/// Distance between the rings of candidate spots tried when a node's own spot is blocked.
+const RESPAWN_SEARCH_STEP: f32 = 1.0;
+/// Number of rings of candidate spots tried around a blocked node.
+const RESPAWN_SEARCH_RINGS: u32 = 3;
+/// Number of candidate spots per ring.
+const RESPAWN_SEARCH_DIRECTIONS: u32 = 8;
+/// Farthest a mouse click can reach into the world.
+const PICK_DISTANCE: f32 = 200.0;
+/// Cosine of the widest angle between the player's forward and a node they can
+/// gather (about 45 degrees).
+pub const GATHER_FACING_COS: f32 = 0.7;
+
+/// Plugin for resource gathering. Any input source can send a [`GatherRequest`];
+/// one system validates it and answers with [`ResourceGathered`] or [`GatherRejected`].
+pub struct GameResourcesPlugin;
+
+impl Plugin for GameResourcesPlugin {
+    fn build(&self, app: &mut App) {
+        add_config::<ResourceRegistry>(app);
+        // Run gathering in FixedUpdate for consistent timing
+        app.init_resource::<ResourceRegistry>()
+            .add_systems(Update, apply_stack_sizes.after(sync_config::<ResourceRegistry>))
+            .init_resource::<HoveredNode>()
+            .add_event::<GatherRequest>()
+            .add_event::<ResourceGathered>()
+            .add_event::<GatherRejected>()
+            .add_event::<ToolBroken>()
+            .add_systems(FixedUpdate, gather_actions.in_set(GameSet::Input))
+            .init_resource::<NodeIndex>()
+            .add_systems(
+                FixedUpdate,
+                (sync_node_index, process_gather_requests, handle_resource_respawn)
+                    .chain()
+                    .in_set(GameSet::Gathering),
+            )
+            // Also sync every frame, so despawns between fixed ticks are never missed
+            .add_systems(PostUpdate, sync_node_index)
+            .add_systems(Update, update_hovered_node);
+    }
 }
 
-/// System for gathering resources when the player is close and facing the resource.
-fn gather_resources(
-    time: Res<Time>,
-    keyboard: Res<ButtonInput<KeyCode>>,
-    mut commands: Commands,
-    mut inventory: ResMut<PlayerInventory>,
-    mut player_query: Query<(&Position, &mut Player, &Transform), With<Player>>,
-    resource_query: Query<(Entity, &Position, &Gatherable, &Transform), With<ResourceNode>>,
+/// Event: Asks to hit a node for resources. Sent by the gather and pick actions,
+/// but any system can send one.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GatherRequest {
+    pub target: GatherTarget,
+}
+
+/// Which node a [`GatherRequest`] is for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GatherTarget {
+    /// The closest node in range in front of the player.
+    Facing,
+    /// A specific node, e.g. the one clicked.
+    Node(Entity),
+}
+
+/// Event: A gather hit landed on a node.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResourceGathered {
+    pub node: Entity,
+    /// Resource the hit gathers, which is the node's own unless its definition drops another.
+    pub resource: ResourceType,
+    /// Units added to the inventory by this hit; zero for nodes that only yield once broken.
+    pub gained: u32,
+    /// Inventory amount of the resource after this hit.
+    pub total: u32,
+    /// Whether this hit broke the node.
+    pub depleted: bool,
+}
+
+/// Event: A tool wore out on its last hit and is gone from the player's equipment.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ToolBroken {
+    pub tool: Tool,
+}
+
+/// Event: A gather request was turned down.
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct GatherRejected(pub GatherError);
+
+/// Why a gather request was turned down.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum GatherError {
+    #[error("still recovering from the last hit")]
+    OnCooldown,
+    #[error("nothing to gather in reach")]
+    NothingInReach,
+    #[error("that is not a resource node")]
+    NotGatherable,
+    #[error("it is waiting to grow back")]
+    Depleted,
+    #[error("too far away")]
+    OutOfRange,
+    #[error("not facing it")]
+    NotFacing,
+    #[error("needs a {} of {} tier or better", kind.name(), tier.name())]
+    ToolRequired { kind: ToolKind, tier: ToolTier },
+    #[error("needs {} level {level}", skill.name())]
+    SkillTooLow { skill: Skill, level: u32 },
+    #[error("no room for more {}", resource.id())]
+    StackFull { resource: ResourceType },
+    #[error("{} has no definition", resource.id())]
+    UnknownResource { resource: ResourceType },
+}
+
+/// Gives the inventory the stack sizes of the resource definitions whenever they change.
+fn apply_stack_sizes(registry: Res<ResourceRegistry>, mut inventory: ResMut<PlayerInventory>) {
+    if registry.is_changed() {
+        registry.apply_stack_sizes(&mut inventory);
+    }
+}
+
+/// Resource: The gatherable node under the mouse cursor, if any.
+#[derive(Resource, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HoveredNode(pub Option<Entity>);
+
+/// Returns the first collider `ray` hits among the entities `is_gatherable` accepts,
+/// looking through everything else. Depleted nodes have no collider and are never picked.
+pub fn pick_gatherable(
+    context: &RapierContext,
+    ray: Ray3d,
+    is_gatherable: impl Fn(Entity) -> bool,
+) -> Option<Entity> {
+    let filter = QueryFilter::new().predicate(&is_gatherable);
+    context
+        .cast_ray(ray.origin, *ray.direction, PICK_DISTANCE, true, filter)
+        .map(|(entity, _)| entity)
+}
+
+/// Tracks which node is under the mouse cursor.
+fn update_hovered_node(
+    windows: Query<&Window>,
+    camera: Query<(&Camera, &GlobalTransform)>,
+    context: Res<RapierContext>,
+    gatherables: Query<(), With<Gatherable>>,
+    mut hovered: ResMut<HoveredNode>,
 ) {
-    let (player_position, mut player, player_transform) = match player_query.get_single_mut() {
-        Ok(v) => v,
-        Err(_) => return,
+    // Nothing to point at without a window and camera (e.g. headless)
+    let (Ok((camera, camera_transform)), Ok(window)) = (camera.get_single(), windows.get_single()) else {
+        return;
     };
+    let node = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor))
+        .and_then(|ray| pick_gatherable(&context, ray, |entity| gatherables.contains(entity)));
+    if hovered.0 != node {
+        hovered.0 = node;
+    }
+}
 
-    // Update gathering cooldown
-    if !player.gathering_cooldown.finished() {
-        player.gathering_cooldown.tick(time.delta());
-        return;
+/// Turns the gather and pick actions into [`GatherRequest`]s.
+fn gather_actions(
+    actions: Res<TickActions>,
+    hovered: Res<HoveredNode>,
+    mut requests: EventWriter<GatherRequest>,
+) {
+    if actions.just_pressed(Action::Gather) {
+        requests.send(GatherRequest { target: GatherTarget::Facing });
     }
+    if actions.just_pressed(Action::Pick) {
+        if let Some(node) = hovered.0 {
+            requests.send(GatherRequest { target: GatherTarget::Node(node) });
+        }
+    }
+}
 
-    // Only process gathering if E is pressed
-    if !keyboard.just_pressed(KeyCode::KeyE) {
-        return;
+/// Where a gatherer can reach from this tick.
+pub(crate) struct Reach {
+    pub position: Vec3,
+    pub forward: Vec3,
+    pub range: f32,
+}
+
+impl Reach {
+    /// Whether a node at `position` can be hit, ignoring its state.
+    pub(crate) fn check(&self, position: Vec3) -> Result<(), GatherError> {
+        if self.position.distance(position) > self.range {
+            return Err(GatherError::OutOfRange);
+        }
+        if self.forward.dot((position - self.position).normalize_or_zero()) <= GATHER_FACING_COS {
+            return Err(GatherError::NotFacing);
+        }
+        Ok(())
     }
+}
 
-    // Cache player position and forward vector
-    let player_pos = player_position.value;
-    let player_forward = player_transform.forward().normalize_or_zero();
-    let gathering_range_sq = player.gathering_range * player.gathering_range;
+/// Validates every [`GatherRequest`] against the gathering cooldown, range, facing cone,
+/// tool and skill requirements and stack limit, hits the node if all pass and reports the outcome
+/// as a [`ResourceGathered`] or [`GatherRejected`] event.
+pub fn process_gather_requests(
+    time: Res<Time>,
+    mut requests: EventReader<GatherRequest>,
+    mut gathered: EventWriter<ResourceGathered>,
+    mut rejected: EventWriter<GatherRejected>,
+    mut broken: EventWriter<ToolBroken>,
+    mut commands: Commands,
+    mut inventory: ResMut<PlayerInventory>,
+    registry: Res<ResourceRegistry>,
+    curve: Res<SkillCurve>,
+    mut player_query: Query<(&Position, &mut Player, &mut Equipment, &Skills, &Transform)>,
+    index: Res<NodeIndex>,
+    mut resource_query: Query<(&Position, &mut Gatherable), With<ResourceNode>>,
+) {
+    let Ok((player_position, mut player, mut equipment, skills, player_transform)) = player_query.get_single_mut() else {
+        requests.clear();
+        return;
+    };
+    player.gathering_cooldown.tick(time.delta());
+    let reach = Reach {
+        position: player_position.value,
+        forward: player_transform.forward().normalize_or_zero(),
+        range: player.gathering_range,
+    };
 
-    // Find the closest valid resource
-    let mut closest_resource = None;
-    let mut closest_distance = f32::MAX;
+    for request in requests.read() {
+        let result = if player.gathering_cooldown.finished() {
+            find_target(request.target, &reach, &index, &resource_query).and_then(|entity| {
+                let (_, mut gatherable) = resource_query.get_mut(entity).expect("target was just found");
+                let gatherer = Gatherer {
+                    bare_damage: player.gather_damage,
+                    bare_seconds: player.gather_seconds,
+                    equipment: &mut equipment,
+                    skills,
+                    curve: &curve,
+                };
+                apply_gather_hit(&mut commands, entity, &mut gatherable, gatherer, &mut inventory, &registry)
+            })
+        } else {
+            Err(GatherError::OnCooldown)
+        };
 
-    for (entity, resource_position, gatherable, resource_transform) in resource_query.iter() {
-        let distance_sq = player_pos.distance_squared(resource_position.value);
-        
-        // Skip if too far
-        if distance_sq > gathering_range_sq {
-            continue;
+        match result {
+            Ok(hit) => {
+                player.gathering_cooldown.set_duration(Duration::from_secs_f32(hit.cooldown_seconds));
+                player.gathering_cooldown.reset();
+                if let Some(tool) = hit.broken_tool {
+                    println!("Your {} {} broke!", tool.tier.name(), tool.kind.name());
+                    broken.send(ToolBroken { tool });
+                }
+                let name = registry.display_name(hit.resource);
+                if hit.gained > 0 {
+                    println!("Gathered {}! Total: {}", name, hit.total);
+                } else {
+                    println!("Hit {}! Health left: {}", name, hit.health);
+                }
+                gathered.send(ResourceGathered {
+                    node: hit.node,
+                    resource: hit.resource,
+                    gained: hit.gained,
+                    total: hit.total,
+                    depleted: hit.health == 0,
+                });
+            }
+            Err(reason) => {
+                println!("Cannot gather: {}", reason);
+                rejected.send(GatherRejected(reason));
+            }
         }
+    }
+}
 
-        // Check if player is facing the resource
-        let to_resource = (resource_transform.translation - player_pos).normalize_or_zero();
-        if player_forward.dot(to_resource) <= 0.7 {
-            continue;
+/// Returns the node a request is for if the player can reach it. A [`GatherTarget::Facing`]
+/// request goes to the closest node in range in front of the player.
+fn find_target(
+    target: GatherTarget,
+    reach: &Reach,
+    index: &NodeIndex,
+    resource_query: &Query<(&Position, &mut Gatherable), With<ResourceNode>>,
+) -> Result<Entity, GatherError> {
+    match target {
+        GatherTarget::Facing => index
+            .nearest_in_cone(reach.position, reach.forward, GATHER_FACING_COS, reach.range, |entity| {
+                // Nodes depleted this tick are only dropped from the index next tick
+                resource_query.get(entity).is_ok_and(|(_, gatherable)| !gatherable.is_depleted())
+            })
+            .map(|(entity, _)| entity)
+            .ok_or(GatherError::NothingInReach),
+        GatherTarget::Node(entity) => {
+            let (position, gatherable) = resource_query.get(entity).map_err(|_| GatherError::NotGatherable)?;
+            if gatherable.is_depleted() {
+                return Err(GatherError::Depleted);
+            }
+            reach.check(position.value)?;
+            Ok(entity)
         }
+    }
+}
+
+/// Outcome of one gather hit against a node.
+pub(crate) struct GatherHit {
+    pub node: Entity,
+    pub resource: ResourceType,
+    /// Units added to the inventory by this hit.
+    pub gained: u32,
+    /// Inventory amount of the resource after this hit.
+    pub total: u32,
+    /// Node health left after this hit.
+    pub health: u32,
+    /// Seconds until the gatherer can hit again.
+    pub cooldown_seconds: f32,
+    /// The tool this hit wore out, if it did.
+    pub broken_tool: Option<Tool>,
+}
+
+/// The player or a worker, as far as one gather hit is concerned.
+pub(crate) struct Gatherer<'a> {
+    /// Damage of a bare-handed hit.
+    pub bare_damage: u32,
+    /// Seconds between bare-handed hits.
+    pub bare_seconds: f32,
+    pub equipment: &'a mut Equipment,
+    pub skills: &'a Skills,
+    pub curve: &'a SkillCurve,
+}
 
-        // Update closest resource if this one is closer
-        if distance_sq < closest_distance {
-            closest_distance = distance_sq;
-            closest_resource = Some((entity, gatherable));
+/// Hits a node with the equipped tool of the kind it needs, or bare-handed without one,
+/// adds whatever its yield rule awards for the hit to the inventory and depletes the
+/// node once its health reaches zero. The tool wears down with every hit and is
+/// swapped for the best spare of its kind, if any, when it breaks; the level of the skill the node trains shortens the
+/// cooldown and raises the yield.
+/// Fails without touching the node or tool if its type has no definition, the gatherer
+/// lacks the tool or skill level it requires or the inventory has no room at all for
+/// its resource.
+pub(crate) fn apply_gather_hit(
+    commands: &mut Commands,
+    entity: Entity,
+    gatherable: &mut Gatherable,
+    gatherer: Gatherer,
+    inventory: &mut Inventory,
+    registry: &ResourceRegistry,
+) -> Result<GatherHit, GatherError> {
+    let Gatherer { bare_damage, bare_seconds, equipment, skills, curve } = gatherer;
+    let definition = registry
+        .get(gatherable.resource_type)
+        .ok_or(GatherError::UnknownResource { resource: gatherable.resource_type })?;
+    let resource = definition.drops.unwrap_or(definition.id);
+    let tool_kind = definition.tool.map(|requirement| requirement.kind);
+    let tool = tool_kind.and_then(|kind| equipment.tool(kind));
+    if let Some(requirement @ ToolRequirement { min_tier: Some(tier), .. }) = definition.tool {
+        if !requirement.is_met_by(tool) {
+            return Err(GatherError::ToolRequired { kind: requirement.kind, tier });
         }
     }
+    // Resources that train no skill are gathered at level 1
+    let level = definition.skill.map_or(1, |rule| skills.level(rule.skill, curve));
+    if let Some(rule) = definition.skill.filter(|rule| level < rule.min_level) {
+        return Err(GatherError::SkillTooLow { skill: rule.skill, level: rule.min_level });
+    }
+    if inventory.room_for(resource) == 0 {
+        return Err(GatherError::StackFull { resource });
+    }
 
-    // Process the closest valid resource
-    if let Some((entity, gatherable)) = closest_resource {
-        let current_amount = *inventory
-            .resources
-            .get(&gatherable.resource_type)
-            .unwrap_or(&0);
-        if current_amount < inventory.max_stack_size {
-            // Add resource to inventory
-            let new_amount = current_amount + 1;
-            inventory
-                .resources
-                .insert(gatherable.resource_type, new_amount);
-            
-            // Despawn the resource node
-            commands.entity(entity).despawn_recursive();
-            
-            // Reset gathering cooldown
-            player.gathering_cooldown.reset();
-            
-            println!("Gathered {}! Total: {}", gatherable.resource_type.get_name(), new_amount);
+    let (damage, cooldown_seconds) = tool.map_or((bare_damage, bare_seconds), |tool| {
+        (tool.tier.damage(), tool.tier.cooldown_seconds())
+    });
+    let broken_tool = tool_kind.and_then(|kind| {
+        let tool = equipment.tool_mut(kind)?;
+        if tool.wear() {
+            let broken = equipment.unequip(kind);
+            equipment.equip_spare(kind);
+            broken
+        } else {
+            None
         }
+    });
+
+    gatherable.health = gatherable.health.saturating_sub(damage);
+    let depleted = gatherable.health == 0;
+    let earned = match definition.yield_rule {
+        YieldRule::PerHit(amount) => amount,
+        YieldRule::OnDepletion(amount) if depleted => amount,
+        YieldRule::OnDepletion(_) => 0,
+    };
+    let earned = if earned > 0 { earned + curve.yield_bonus(level) } else { 0 };
+
+    // Anything beyond the inventory's room is lost
+    let gained = inventory.add_up_to(resource, earned);
+
+    if depleted && !definition.respawns {
+        commands.entity(entity).despawn_recursive();
+    } else if depleted {
+        // Hide the resource node until it respawns
+        deplete_node(commands, entity, gatherable, definition.respawn_seconds);
     }
+
+    Ok(GatherHit {
+        node: entity,
+        resource,
+        gained,
+        total: inventory.count(resource),
+        health: gatherable.health,
+        cooldown_seconds: cooldown_seconds * curve.cooldown_factor(level),
+        broken_tool,
+    })
+}
+
+/// Hides a gathered node, takes it out of physics and starts its respawn countdown.
+pub(crate) fn deplete_node(commands: &mut Commands, entity: Entity, gatherable: &mut Gatherable, respawn_seconds: f32) {
+    gatherable.respawn_timer = Some(Timer::from_seconds(respawn_seconds, TimerMode::Once));
+    commands.entity(entity).insert((Visibility::Hidden, ColliderDisabled));
 }
 
-fn handle_resource_click(
+/// Counts down depleted nodes, faster or slower as the weather's regrowth has it, and
+/// brings them back when their timer finishes and it is the part of the day they
+/// respawn in, at their own spot or the nearest free spot around it.
+pub fn handle_resource_respawn(
+    time: Res<Time>,
+    clock: Res<TimeOfDay>,
+    weather: Res<Weather>,
+    weather_registry: Res<WeatherRegistry>,
     mut commands: Commands,
-    windows: Query<&Window>,
-    camera: Query<(&Camera, &GlobalTransform)>,
-    mouse_button: Res<Input<MouseButton>>,
-    player_query: Query<(&Transform, &Player)>,
-    resource_query: Query<(Entity, &Transform, &Gatherable)>,
-    mut inventory: ResMut<PlayerInventory>,
+    registry: Res<ResourceRegistry>,
+    rapier_context: Res<RapierContext>,
+    mut resource_query: Query<(Entity, &mut Gatherable, &mut Position, &mut Transform, &Collider), With<ResourceNode>>,
 ) {
-    if !mouse_button.just_pressed(MouseButton::Left) {
-        return;
+    let effects = weather_registry.effects(weather.kind());
+    for (entity, mut gatherable, mut position, mut transform, collider) in resource_query.iter_mut() {
+        // Look before borrowing mutably, so standing nodes are not marked changed every tick
+        if gatherable.respawn_timer.is_none() {
+            continue;
+        }
+        let regrowth = effects.regrowth(gatherable.resource_type).max(0.0);
+        let Some(timer) = gatherable.respawn_timer.as_mut() else {
+            continue;
+        };
+        if !timer.tick(time.delta().mul_f32(regrowth)).finished() {
+            continue;
+        }
+        let definition = registry.get(gatherable.resource_type);
+        if definition.and_then(|definition| definition.respawn_during).is_some_and(|period| period != clock.period()) {
+            continue;
+        }
+
+        // If every spot is blocked, keep the node hidden and try again next tick
+        let Some(spot) = find_respawn_spot(&rapier_context, position.value, transform.rotation, collider) else {
+            continue;
+        };
+
+        gatherable.respawn_timer = None;
+        if let Some(definition) = definition {
+            gatherable.health = definition.health;
+        }
+        position.value = spot;
+        transform.translation = spot;
+        commands
+            .entity(entity)
+            .insert(Visibility::Inherited)
+            .remove::<ColliderDisabled>();
     }
+}
 
-    let (camera, camera_transform) = camera.single();
-    let window = windows.single();
-
-    if let Some(cursor_position) = window.cursor_position() {
-        if let Some(_ray) = camera.viewport_to_world(camera_transform, cursor_position) {
-            let (player_transform, player) = player_query.single();
-            let player_pos = player_transform.translation;
-            let gathering_range_squared = player.gathering_range * player.gathering_range;
-
-            // Find the closest resource within range
-            let mut closest_resource = None;
-            let mut closest_distance = f32::MAX;
-
-            for (entity, transform, gatherable) in resource_query.iter() {
-                let distance_squared = transform.translation.distance_squared(player_pos);
-                if distance_squared <= gathering_range_squared {
-                    if distance_squared < closest_distance {
-                        closest_distance = distance_squared;
-                        closest_resource = Some((entity, gatherable));
-                    }
-                }
-            }
+/// Returns `origin` if no moving body (player, NPCs) overlaps the node's collider there,
+/// otherwise the first free spot on rings around it.
+fn find_respawn_spot(
+    rapier_context: &RapierContext,
+    origin: Vec3,
+    rotation: Quat,
+    collider: &Collider,
+) -> Option<Vec3> {
+    let is_free = |spot: Vec3| {
+        rapier_context
+            .intersection_with_shape(spot, rotation, collider, QueryFilter::only_dynamic())
+            .is_none()
+    };
 
-            // If we found a resource within range, gather it
-            if let Some((entity, gatherable)) = closest_resource {
-                let current_amount = *inventory.resources.get(&gatherable.resource_type).unwrap_or(&0);
-                if current_amount < inventory.max_stack_size {
-                    inventory.resources.insert(gatherable.resource_type.clone(), current_amount + 1);
-                    commands.entity(entity).despawn_recursive();
-                }
+    if is_free(origin) {
+        return Some(origin);
+    }
+
+    for ring in 1..=RESPAWN_SEARCH_RINGS {
+        let radius = ring as f32 * RESPAWN_SEARCH_STEP;
+        for direction in 0..RESPAWN_SEARCH_DIRECTIONS {
+            let angle = direction as f32 * TAU / RESPAWN_SEARCH_DIRECTIONS as f32;
+            let spot = origin + Vec3::new(angle.cos(), 0.0, angle.sin()) * radius;
+            if is_free(spot) {
+                return Some(spot);
             }
         }
     }
+    None
 }
-
-/// (Optional) Handles resource respawn if you want to add it back after a timer.
-fn handle_resource_respawn(
-    _time: Res<Time>,
-    _resource_query: Query<(&mut Gatherable, &mut Visibility)>,
-) {
-    // No-op: resources are despawned when gathered. Implement respawn logic here if desired.
-} 
\ No newline at end of file
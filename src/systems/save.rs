@@ -0,0 +1,539 @@
+//! Saves the game state to disk and rebuilds the world from a save file.
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use bevy::prelude::*;
+use bevy::hierarchy::DespawnRecursiveExt;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use crate::components::types::{
+    GameAssets, Gatherable, Npc, Player, PlayerInventory, Position, ResourceNode, ResourceType, WorldSeed,
+};
+use crate::components::definitions::ResourceRegistry;
+use crate::components::equipment::Equipment;
+use crate::components::inventory::{ItemStack, DEFAULT_SLOT_COUNT};
+use crate::components::skills::Skills;
+use crate::components::quests::QuestLog;
+use crate::components::time_of_day::TimeOfDay;
+use crate::components::weather::Weather;
+use crate::components::workers::{Stockpile, Worker, WorkerState};
+use crate::components::bindings::Action;
+use crate::systems::crafting::{CraftJob, CraftingQueue};
+use crate::systems::entities::{resource_node_components, stockpile_components, worker_components};
+use crate::systems::input::action_just_pressed;
+use crate::systems::navigation::PathFollower;
+use crate::systems::weather::WEATHER_SEED_SALT;
+
+/// Version written to new save files. New fields get `#[serde(default)]` so older saves
+/// still load; bump it only for changes a default cannot cover, with a migration from the
+/// old version in [`SaveGame::from_ron`].
+pub const SAVE_VERSION: u32 = 8;
+/// First save version with weather; older saves start the weather afresh from the world seed.
+const WEATHER_SAVE_VERSION: u32 = 7;
+/// Default quick-save file, relative to the working directory.
+pub const QUICK_SAVE_PATH: &str = "saves/quicksave.ron";
+
+/// Plugin for the quick-save and quick-load actions (F5 and F9 by default).
+pub struct GameSavePlugin;
+
+impl Plugin for GameSavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SaveSettings>().add_systems(
+            Update,
+            (
+                quick_save.run_if(action_just_pressed(Action::QuickSave)),
+                quick_load.run_if(action_just_pressed(Action::QuickLoad)),
+            ),
+        );
+    }
+}
+
+/// Resource: Where quick-save and quick-load read and write.
+#[derive(Resource, Clone, Debug)]
+pub struct SaveSettings {
+    pub path: PathBuf,
+}
+
+impl Default for SaveSettings {
+    fn default() -> Self {
+        Self { path: PathBuf::from(QUICK_SAVE_PATH) }
+    }
+}
+
+/// Everything needed to rebuild a game: the world seed, the time of day, the weather,
+/// the player, their quests and crafting queue, the workers, the stockpiles and every
+/// resource node.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SaveGame {
+    pub version: u32,
+    pub world_seed: u64,
+    #[serde(default)]
+    pub time_of_day: TimeOfDay,
+    #[serde(default = "unseeded_weather")]
+    pub weather: Weather,
+    /// Inventory slots in order, `None` for empty ones.
+    pub inventory: Vec<Option<ItemStack>>,
+    pub player: PlayerSave,
+    #[serde(default)]
+    pub quests: QuestLog,
+    /// Queued crafts, front first. Their inputs are already out of the inventory.
+    #[serde(default)]
+    pub crafting: Vec<CraftJobSave>,
+    /// Workers in id order.
+    #[serde(default)]
+    pub workers: Vec<WorkerSave>,
+    #[serde(default)]
+    pub stockpiles: Vec<StockpileSave>,
+    pub nodes: Vec<NodeSave>,
+}
+
+/// Saved player state.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PlayerSave {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    /// Equipped tools and their wear.
+    #[serde(default)]
+    pub equipment: Equipment,
+    /// XP earned in each skill.
+    #[serde(default)]
+    pub skills: Skills,
+    /// The click-to-move path the player is walking, if any.
+    #[serde(default)]
+    pub path: Option<PathSave>,
+}
+
+/// Stand-in weather for saves from before weather, reseeded once the world seed is known.
+fn unseeded_weather() -> Weather {
+    Weather::new(0)
+}
+
+/// The weather a new game with `world_seed` starts with.
+fn starting_weather(world_seed: u64) -> Weather {
+    Weather::new(world_seed ^ WEATHER_SEED_SALT)
+}
+
+/// The version of a save file, read before the rest to pick how to parse it.
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+/// Save file version 1, from before inventories had slots: the inventory was a count per item.
+#[derive(Deserialize)]
+struct SaveGameV1 {
+    world_seed: u64,
+    inventory: HashMap<ResourceType, u32>,
+    player: PlayerSave,
+    nodes: Vec<NodeSave>,
+}
+
+impl SaveGameV1 {
+    /// Puts each item in a stack of its own, as the version 1 inventory held any amount.
+    fn migrate(self) -> SaveGame {
+        let mut stacks: Vec<ItemStack> = self
+            .inventory
+            .into_iter()
+            .filter(|(_, amount)| *amount > 0)
+            .map(|(item, amount)| ItemStack { item, amount })
+            .collect();
+        stacks.sort_by(|a, b| a.item.id().cmp(b.item.id()));
+        let mut inventory: Vec<Option<ItemStack>> = stacks.into_iter().map(Some).collect();
+        if inventory.len() < DEFAULT_SLOT_COUNT {
+            inventory.resize(DEFAULT_SLOT_COUNT, None);
+        }
+        SaveGame {
+            version: SAVE_VERSION,
+            world_seed: self.world_seed,
+            time_of_day: TimeOfDay::default(),
+            weather: starting_weather(self.world_seed),
+            inventory,
+            player: self.player,
+            quests: QuestLog::default(),
+            crafting: Vec::new(),
+            workers: Vec::new(),
+            stockpiles: Vec::new(),
+            nodes: self.nodes,
+        }
+    }
+}
+
+/// Saved state of one queued craft.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CraftJobSave {
+    pub recipe: String,
+    /// Length of the craft and how far it has run, in seconds.
+    pub duration: f32,
+    pub elapsed: f32,
+}
+
+/// Saved state of one worker. Workers from saves that did not keep what they were doing
+/// go back to looking for work when loaded, if they have a job.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WorkerSave {
+    pub id: String,
+    pub name: String,
+    pub position: Vec3,
+    pub job: Option<ResourceType>,
+    /// Load slots in order, `None` for empty ones.
+    pub load: Vec<Option<ItemStack>>,
+    pub equipment: Equipment,
+    pub skills: Skills,
+    #[serde(default)]
+    pub state: Option<WorkerStateSave>,
+    #[serde(default)]
+    pub gathering_cooldown: Option<TimerSave>,
+    #[serde(default)]
+    pub retry_cooldown: Option<TimerSave>,
+    /// The path the worker is walking, if any.
+    #[serde(default)]
+    pub path: Option<PathSave>,
+}
+
+/// Saved state of a worker's state machine. Nodes are saved by position, as they are
+/// new entities once loaded.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum WorkerStateSave {
+    Idle,
+    Seek,
+    MoveTo(Vec3),
+    Gather(Vec3),
+    Return,
+}
+
+/// Saved path being walked.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PathSave {
+    pub destination: Vec3,
+    /// Waypoints still ahead, the next one first.
+    pub waypoints: Vec<Vec3>,
+}
+
+impl PathSave {
+    fn of(path: &PathFollower) -> Option<Self> {
+        path.destination.map(|destination| Self { destination, waypoints: path.waypoints.clone() })
+    }
+}
+
+/// Saved state of one stockpile.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StockpileSave {
+    pub position: Vec3,
+    /// Slots in order, `None` for empty ones.
+    pub inventory: Vec<Option<ItemStack>>,
+}
+
+/// Saved state of one resource node.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct NodeSave {
+    pub resource_type: ResourceType,
+    pub position: Vec3,
+    pub health: u32,
+    /// Countdown of a depleted node, `None` while it can be gathered.
+    pub respawn: Option<RespawnSave>,
+}
+
+/// Saved progress of a timer, in seconds.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct TimerSave {
+    pub duration: f32,
+    pub elapsed: f32,
+}
+
+impl TimerSave {
+    fn of(timer: &Timer) -> Self {
+        Self { duration: timer.duration().as_secs_f32(), elapsed: timer.elapsed_secs() }
+    }
+
+    fn timer(&self) -> Timer {
+        let mut timer = Timer::from_seconds(self.duration, TimerMode::Once);
+        timer.set_elapsed(std::time::Duration::from_secs_f32(self.elapsed));
+        timer
+    }
+}
+
+/// Saved progress of a depleted node's respawn timer.
+pub type RespawnSave = TimerSave;
+
+/// Errors from reading or writing a save file.
+#[derive(Debug, Error)]
+pub enum SaveError {
+    #[error("could not access save file: {0}")]
+    Io(#[from] io::Error),
+    #[error("could not write save file: {0}")]
+    Serialize(#[from] ron::Error),
+    #[error("could not parse save file: {0}")]
+    Parse(#[from] ron::error::SpannedError),
+    #[error("save file version {found} is not supported (expected {expected} or older)")]
+    UnsupportedVersion { found: u32, expected: u32 },
+    #[error("the world has no player to save or load")]
+    NoPlayer,
+}
+
+impl SaveGame {
+    /// Serializes the save as RON.
+    pub fn to_ron(&self) -> Result<String, SaveError> {
+        Ok(ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?)
+    }
+
+    /// Parses a save from RON, migrating files written by older save versions and
+    /// rejecting ones from newer versions.
+    pub fn from_ron(text: &str) -> Result<Self, SaveError> {
+        let header: SaveHeader = ron::from_str(text)?;
+        match header.version {
+            1 => Ok(ron::from_str::<SaveGameV1>(text)?.migrate()),
+            2..=SAVE_VERSION => {
+                let mut save: SaveGame = ron::from_str(text)?;
+                if save.version < WEATHER_SAVE_VERSION {
+                    save.weather = starting_weather(save.world_seed);
+                }
+                save.version = SAVE_VERSION;
+                Ok(save)
+            }
+            found => Err(SaveError::UnsupportedVersion { found, expected: SAVE_VERSION }),
+        }
+    }
+
+    /// Writes the save to `path`, creating its directory if needed.
+    pub fn write(&self, path: &Path) -> Result<(), SaveError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_ron()?)?;
+        Ok(())
+    }
+
+    /// Reads a save from `path`.
+    pub fn read(path: &Path) -> Result<Self, SaveError> {
+        Self::from_ron(&fs::read_to_string(path)?)
+    }
+}
+
+/// Captures the current game state.
+pub fn capture_save(world: &mut World) -> Result<SaveGame, SaveError> {
+    let player = {
+        let mut players = world.query_filtered::<(&Transform, &Equipment, &Skills, &PathFollower), With<Player>>();
+        let (transform, equipment, skills, path) = players.get_single(world).map_err(|_| SaveError::NoPlayer)?;
+        PlayerSave {
+            translation: transform.translation,
+            rotation: transform.rotation,
+            equipment: equipment.clone(),
+            skills: skills.clone(),
+            path: PathSave::of(path),
+        }
+    };
+    let mut workers_query = world.query::<(&Npc, &Worker, &Position, &Equipment, &Skills, &PathFollower)>();
+    let mut workers: Vec<WorkerSave> = workers_query
+        .iter(world)
+        .map(|(npc, worker, position, equipment, skills, path)| {
+            let node_position = |node: Entity| world.get::<Position>(node).map(|node| node.value);
+            let state = match worker.state {
+                WorkerState::Idle => Some(WorkerStateSave::Idle),
+                WorkerState::Seek => Some(WorkerStateSave::Seek),
+                WorkerState::MoveTo(node) => node_position(node).map(WorkerStateSave::MoveTo),
+                WorkerState::Gather(node) => node_position(node).map(WorkerStateSave::Gather),
+                WorkerState::Return => Some(WorkerStateSave::Return),
+            };
+            WorkerSave {
+                id: npc.id.clone(),
+                name: npc.name.clone(),
+                position: position.value,
+                job: worker.job,
+                load: worker.load.slots().to_vec(),
+                equipment: equipment.clone(),
+                skills: skills.clone(),
+                // A node that is gone leaves the worker looking for another
+                state: state.or(Some(WorkerStateSave::Seek)),
+                gathering_cooldown: Some(TimerSave::of(&worker.gathering_cooldown)),
+                retry_cooldown: Some(TimerSave::of(&worker.retry_cooldown)),
+                path: PathSave::of(path),
+            }
+        })
+        .collect();
+    workers.sort_by(|a, b| a.id.cmp(&b.id));
+    let mut stockpiles_query = world.query::<(&Stockpile, &Position)>();
+    let mut stockpiles: Vec<StockpileSave> = stockpiles_query
+        .iter(world)
+        .map(|(stockpile, position)| StockpileSave {
+            position: position.value,
+            inventory: stockpile.inventory.slots().to_vec(),
+        })
+        .collect();
+    stockpiles.sort_by(|a, b| a.position.x.total_cmp(&b.position.x).then(a.position.z.total_cmp(&b.position.z)));
+    let mut nodes_query = world.query_filtered::<(&Position, &Gatherable), With<ResourceNode>>();
+    let nodes = nodes_query
+        .iter(world)
+        .map(|(position, gatherable)| NodeSave {
+            resource_type: gatherable.resource_type,
+            position: position.value,
+            health: gatherable.health,
+            respawn: gatherable.respawn_timer.as_ref().map(TimerSave::of),
+        })
+        .collect();
+
+    Ok(SaveGame {
+        version: SAVE_VERSION,
+        world_seed: world.resource::<WorldSeed>().0,
+        time_of_day: *world.resource::<TimeOfDay>(),
+        weather: *world.resource::<Weather>(),
+        inventory: world.resource::<PlayerInventory>().slots().to_vec(),
+        player,
+        quests: world.resource::<QuestLog>().clone(),
+        crafting: world
+            .resource::<CraftingQueue>()
+            .jobs
+            .iter()
+            .map(|job| CraftJobSave {
+                recipe: job.recipe.clone(),
+                duration: job.timer.duration().as_secs_f32(),
+                elapsed: job.timer.elapsed_secs(),
+            })
+            .collect(),
+        workers,
+        stockpiles,
+        nodes,
+    })
+}
+
+/// Replaces the current game state with `save`: restores the seed, clock, weather, inventory,
+/// player with their equipment and skills, quest log and crafting queue, and despawns every
+/// worker, stockpile and resource node before spawning the saved ones, with each worker
+/// back at what it was doing.
+///
+/// Nodes whose type is no longer in the resource definitions are skipped. Fails, changing
+/// nothing, if the world has no player.
+pub fn apply_save(world: &mut World, save: &SaveGame) -> Result<(), SaveError> {
+    let mut players = world
+        .query_filtered::<
+            (&mut Transform, &mut Position, &mut Velocity, &mut PathFollower, &mut Equipment, &mut Skills),
+            With<Player>,
+        >();
+    let (mut transform, mut position, mut velocity, mut path, mut equipment, mut skills) =
+        players.get_single_mut(world).map_err(|_| SaveError::NoPlayer)?;
+    transform.translation = save.player.translation;
+    transform.rotation = save.player.rotation;
+    position.value = save.player.translation;
+    *velocity = Velocity::zero();
+    // Walk on along the saved path; one planned before loading would lead off from the wrong spot
+    match &save.player.path {
+        Some(saved) => path.resume(saved.destination, saved.waypoints.clone()),
+        None => path.clear(),
+    }
+    equipment.clone_from(&save.player.equipment);
+    skills.clone_from(&save.player.skills);
+
+    world.insert_resource(WorldSeed(save.world_seed));
+    world.insert_resource(save.time_of_day);
+    world.insert_resource(save.weather);
+    world.resource_mut::<PlayerInventory>().set_slots(save.inventory.clone());
+    world.insert_resource(save.quests.clone());
+    // Crafts queued since the save have taken inputs the saved inventory still holds
+    world.resource_mut::<CraftingQueue>().jobs = save
+        .crafting
+        .iter()
+        .map(|saved| {
+            let mut timer = Timer::from_seconds(saved.duration, TimerMode::Once);
+            timer.set_elapsed(std::time::Duration::from_secs_f32(saved.elapsed));
+            CraftJob { recipe: saved.recipe.clone(), timer }
+        })
+        .collect();
+
+    let old_entities: Vec<Entity> = world
+        .query_filtered::<Entity, Or<(With<ResourceNode>, With<Worker>, With<Stockpile>)>>()
+        .iter(world)
+        .collect();
+    for entity in old_entities {
+        world.entity_mut(entity).despawn_recursive();
+    }
+
+    for saved in &save.stockpiles {
+        let transform = Transform::from_translation(saved.position);
+        let mut entity = world.spawn((SpatialBundle::from_transform(transform), stockpile_components(saved.position)));
+        entity.get_mut::<Stockpile>().unwrap().inventory.set_slots(saved.inventory.clone());
+    }
+
+    for node in &save.nodes {
+        let Some(definition) = world.resource::<ResourceRegistry>().get(node.resource_type).cloned() else {
+            eprintln!("Skipping saved node of unknown resource type {}", node.resource_type.id());
+            continue;
+        };
+        let transform = Transform::from_translation(node.position);
+        // Reuse the loaded model when there is one; headless games have none
+        let model = world
+            .get_resource::<GameAssets>()
+            .and_then(|assets| assets.resource_models.get(&node.resource_type).cloned());
+        let mut entity = match model {
+            Some(scene) => world.spawn(SceneBundle { scene, transform, ..default() }),
+            None => world.spawn(SpatialBundle::from_transform(transform)),
+        };
+        entity.insert(resource_node_components(&definition, node.position));
+
+        let mut gatherable = entity.get_mut::<Gatherable>().unwrap();
+        gatherable.health = node.health;
+        if let Some(respawn) = node.respawn {
+            gatherable.respawn_timer = Some(respawn.timer());
+            entity.insert((Visibility::Hidden, ColliderDisabled));
+        }
+    }
+
+    // Workers after nodes, so the nodes they are busy with can be found again
+    let nodes: Vec<(Entity, Vec3)> = world
+        .query_filtered::<(Entity, &Position), With<ResourceNode>>()
+        .iter(world)
+        .map(|(entity, position)| (entity, position.value))
+        .collect();
+    let node_at = |position: Vec3| nodes.iter().find(|(_, at)| *at == position).map(|(node, _)| *node);
+    for saved in &save.workers {
+        let transform = Transform::from_translation(saved.position);
+        let mut entity = world.spawn((
+            SpatialBundle::from_transform(transform),
+            worker_components(&saved.id, &saved.name, saved.position),
+        ));
+        let mut worker = entity.get_mut::<Worker>().unwrap();
+        worker.assign(saved.job);
+        let state = match saved.state {
+            Some(WorkerStateSave::Idle) => Some(WorkerState::Idle),
+            Some(WorkerStateSave::Seek) => Some(WorkerState::Seek),
+            Some(WorkerStateSave::MoveTo(position)) => node_at(position).map(WorkerState::MoveTo),
+            Some(WorkerStateSave::Gather(position)) => node_at(position).map(WorkerState::Gather),
+            Some(WorkerStateSave::Return) => Some(WorkerState::Return),
+            None => None,
+        };
+        if let Some(state) = state {
+            worker.state = state;
+        }
+        if let Some(cooldown) = saved.gathering_cooldown {
+            worker.gathering_cooldown = cooldown.timer();
+        }
+        if let Some(cooldown) = saved.retry_cooldown {
+            worker.retry_cooldown = cooldown.timer();
+        }
+        worker.load.set_slots(saved.load.clone());
+        entity.get_mut::<Equipment>().unwrap().clone_from(&saved.equipment);
+        entity.get_mut::<Skills>().unwrap().clone_from(&saved.skills);
+        if let Some(path) = &saved.path {
+            entity.get_mut::<PathFollower>().unwrap().resume(path.destination, path.waypoints.clone());
+        }
+    }
+    Ok(())
+}
+
+/// Writes the game state to the quick-save file.
+fn quick_save(world: &mut World) {
+    let path = world.resource::<SaveSettings>().path.clone();
+    match capture_save(world).and_then(|save| save.write(&path)) {
+        Ok(()) => println!("Game saved to {}", path.display()),
+        Err(err) => eprintln!("Quick-save failed: {}", err),
+    }
+}
+
+/// Rebuilds the world from the quick-save file.
+fn quick_load(world: &mut World) {
+    let path = world.resource::<SaveSettings>().path.clone();
+    match SaveGame::read(&path).and_then(|save| apply_save(world, &save)) {
+        Ok(()) => println!("Game loaded from {}", path.display()),
+        Err(err) => eprintln!("Quick-load failed: {}", err),
+    }
+}
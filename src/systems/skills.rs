@@ -0,0 +1,55 @@
+//! Gathering skill progression: every successful gather hit earns XP in the skill its
+//! resource trains, and levelling up is announced with a [`SkillLevelUp`] event.
+use bevy::prelude::*;
+use crate::components::definitions::ResourceRegistry;
+use crate::components::skills::{
+    Skill, SkillCurve, Skills,
+};
+use crate::components::types::Player;
+use crate::systems::config::add_config;
+use crate::systems::resources::{process_gather_requests, ResourceGathered};
+use crate::systems::GameSet;
+
+/// Plugin for the skill curve and awarding gathering XP.
+pub struct GameSkillsPlugin;
+
+impl Plugin for GameSkillsPlugin {
+    fn build(&self, app: &mut App) {
+        add_config::<SkillCurve>(app);
+        app.init_resource::<SkillCurve>()
+            .add_event::<SkillLevelUp>()
+            // Award XP the same tick, so the next hit already gets the new level's bonuses
+            .add_systems(FixedUpdate, award_gather_xp.after(process_gather_requests).in_set(GameSet::Gathering));
+    }
+}
+
+/// Event: The player reached a new level in a skill.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SkillLevelUp {
+    pub skill: Skill,
+    pub level: u32,
+}
+
+/// Gives the player the XP of every node they hit this tick, in the skill the node's
+/// resource trains.
+fn award_gather_xp(
+    mut gathered: EventReader<ResourceGathered>,
+    mut level_ups: EventWriter<SkillLevelUp>,
+    registry: Res<ResourceRegistry>,
+    curve: Res<SkillCurve>,
+    mut player_query: Query<&mut Skills, With<Player>>,
+) {
+    let Ok(mut skills) = player_query.get_single_mut() else {
+        gathered.clear();
+        return;
+    };
+    for hit in gathered.read() {
+        let Some(rule) = registry.get(hit.resource).and_then(|definition| definition.skill) else {
+            continue;
+        };
+        if let Some(level) = skills.add_xp(rule.skill, rule.xp, &curve) {
+            println!("{} is now level {}!", rule.skill.name(), level);
+            level_ups.send(SkillLevelUp { skill: rule.skill, level });
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! Spatial index of resource nodes, so finding the nodes around a point costs the same
+//! whether the map holds a hundred nodes or a hundred thousand.
+use std::collections::HashMap;
+use bevy::prelude::*;
+use crate::components::types::{Gatherable, Position, ResourceNode};
+
+/// Side of a grid cell in world units. Queries much wider than this visit many cells;
+/// much narrower wastes memory on near-empty cells.
+pub const DEFAULT_CELL_SIZE: f32 = 4.0;
+
+/// Uniform grid over the ground plane (x and z) that buckets entities by position.
+/// Heights are kept for distance checks but do not affect which cell an entity is in.
+#[derive(Clone, Debug)]
+pub struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<IVec2, Vec<(Entity, Vec3)>>,
+    positions: HashMap<Entity, Vec3>,
+}
+
+impl Default for SpatialGrid {
+    fn default() -> Self {
+        Self::new(DEFAULT_CELL_SIZE)
+    }
+}
+
+impl SpatialGrid {
+    /// Creates an empty grid. Panics if `cell_size` is not positive.
+    pub fn new(cell_size: f32) -> Self {
+        assert!(cell_size > 0.0, "cell size must be positive");
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+            positions: HashMap::new(),
+        }
+    }
+
+    /// Number of entities in the grid.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Whether the grid holds no entities.
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Where `entity` was last inserted, if it is in the grid.
+    pub fn position(&self, entity: Entity) -> Option<Vec3> {
+        self.positions.get(&entity).copied()
+    }
+
+    /// Adds `entity` at `position`, moving it if it is already in the grid.
+    pub fn insert(&mut self, entity: Entity, position: Vec3) {
+        if let Some(old) = self.positions.insert(entity, position) {
+            if old == position {
+                return;
+            }
+            self.remove_from_cell(entity, old);
+        }
+        self.cells.entry(self.cell(position)).or_default().push((entity, position));
+    }
+
+    /// Takes `entity` out of the grid. Returns whether it was in it.
+    pub fn remove(&mut self, entity: Entity) -> bool {
+        match self.positions.remove(&entity) {
+            Some(position) => {
+                self.remove_from_cell(entity, position);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Every entity within `radius` of `center`, in no particular order.
+    pub fn within_radius(&self, center: Vec3, radius: f32) -> impl Iterator<Item = (Entity, Vec3)> + '_ {
+        let min = self.cell(center - Vec3::splat(radius));
+        let max = self.cell(center + Vec3::splat(radius));
+        let radius_sq = radius * radius;
+        (min.x..=max.x)
+            .flat_map(move |x| (min.y..=max.y).map(move |z| IVec2::new(x, z)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .filter(move |(_, position)| position.distance_squared(center) <= radius_sq)
+    }
+
+    /// The closest entity within `radius` of `center` that `accept` agrees to.
+    pub fn nearest_in_radius(
+        &self,
+        center: Vec3,
+        radius: f32,
+        accept: impl FnMut(Entity) -> bool,
+    ) -> Option<(Entity, Vec3)> {
+        nearest(center, self.within_radius(center, radius), accept)
+    }
+
+    /// The closest entity within `radius` of `origin` that lies inside the cone around
+    /// `forward` whose half-angle has cosine `min_cos`, and that `accept` agrees to.
+    /// `forward` must be normalized.
+    pub fn nearest_in_cone(
+        &self,
+        origin: Vec3,
+        forward: Vec3,
+        min_cos: f32,
+        radius: f32,
+        accept: impl FnMut(Entity) -> bool,
+    ) -> Option<(Entity, Vec3)> {
+        let in_cone = self
+            .within_radius(origin, radius)
+            .filter(|(_, position)| forward.dot((*position - origin).normalize_or_zero()) > min_cos);
+        nearest(origin, in_cone, accept)
+    }
+
+    fn cell(&self, position: Vec3) -> IVec2 {
+        (position.xz() / self.cell_size).floor().as_ivec2()
+    }
+
+    fn remove_from_cell(&mut self, entity: Entity, position: Vec3) {
+        let cell = self.cell(position);
+        if let Some(entries) = self.cells.get_mut(&cell) {
+            entries.retain(|(other, _)| *other != entity);
+            if entries.is_empty() {
+                self.cells.remove(&cell);
+            }
+        }
+    }
+}
+
+/// The candidate closest to `center` that `accept` agrees to.
+fn nearest(
+    center: Vec3,
+    candidates: impl Iterator<Item = (Entity, Vec3)>,
+    mut accept: impl FnMut(Entity) -> bool,
+) -> Option<(Entity, Vec3)> {
+    candidates
+        .filter(|(entity, _)| accept(*entity))
+        .min_by(|(_, a), (_, b)| a.distance_squared(center).total_cmp(&b.distance_squared(center)))
+}
+
+/// Resource: Spatial index of the resource nodes that can be gathered right now.
+/// Depleted nodes leave the index and come back, at their new spot, when they respawn.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct NodeIndex(pub SpatialGrid);
+
+/// Nodes that spawned, moved, or were hit or respawned since the index was last synced.
+type ChangedNodes = (With<ResourceNode>, Or<(Changed<Position>, Changed<Gatherable>)>);
+
+/// Keeps [`NodeIndex`] in step with nodes spawning, moving, depleting, respawning and
+/// despawning.
+pub fn sync_node_index(
+    mut index: ResMut<NodeIndex>,
+    mut removed: RemovedComponents<ResourceNode>,
+    nodes: Query<(Entity, &Position, &Gatherable), ChangedNodes>,
+) {
+    for entity in removed.read() {
+        index.remove(entity);
+    }
+    for (entity, position, gatherable) in &nodes {
+        if gatherable.is_depleted() {
+            index.remove(entity);
+        } else {
+            index.insert(entity, position.value);
+        }
+    }
+}
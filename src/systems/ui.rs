@@ -1,26 +1,54 @@
-//! UI system for displaying the player's inventory (HUD).
+//! UI system for displaying the player's inventory, tools, skills and quests, and the workers (HUD).
 use bevy::prelude::*;
 use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
 use std::fs::OpenOptions;
 use std::io::Write;
-use crate::components::types::{InventoryText, PlayerInventory, ResourceType};
+use crate::components::types::{Gatherable, InventoryText, Player, PlayerInventory, Position};
+use crate::components::definitions::ResourceRegistry;
+use crate::components::equipment::Equipment;
+use crate::components::skills::{SkillCurve, Skills};
+use crate::components::quests::{QuestBook, QuestLog, QuestState};
+use crate::components::recipes::RecipeBook;
+use crate::components::types::{Npc, ResourceType};
+use crate::components::workers::{Stockpile, Worker};
+use crate::systems::resources::HoveredNode;
+
+/// Gap between a hovered node's collider and its highlight ring.
+const HIGHLIGHT_MARGIN: f32 = 0.2;
 
 #[derive(Component)]
 struct FpsText;
 
-pub fn register(app: &mut App) {
-    app.add_systems(Startup, setup_ui)
-        // Update UI less frequently for better performance
-        .add_systems(Update, update_inventory_text)
-        .add_systems(Update, update_fps_text);
+/// Marker for the quest log text entity.
+#[derive(Component)]
+struct QuestLogText;
+
+/// Marker for the workers panel text entity.
+#[derive(Component)]
+struct WorkersText;
+
+/// Plugin for the inventory and FPS HUD and the hovered node highlight.
+pub struct GameUiPlugin;
+
+impl Plugin for GameUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_ui)
+            // Update UI less frequently for better performance
+            .add_systems(Update, update_inventory_text)
+            .add_systems(Update, update_quest_log_text)
+            .add_systems(Update, update_workers_text)
+            .add_systems(Update, update_fps_text)
+            .add_systems(Update, highlight_hovered_node);
+    }
 }
 
-/// Sets up the inventory HUD in the top-left corner.
-fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
+/// Sets up the inventory HUD in the top-left corner, the quest log in the bottom-left
+/// corner, the FPS counter in the top-right and the workers panel below it.
+fn setup_ui(mut commands: Commands) {
     // Inventory text
     commands.spawn((
         TextBundle::from_section(
-            "Inventory: Wood: 0, Stone: 0, Ore: 0",
+            "Inventory:",
             TextStyle {
                 font_size: 20.0,
                 color: Color::WHITE,
@@ -36,6 +64,25 @@ fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
         InventoryText,
     ));
 
+    // Quest log text
+    commands.spawn((
+        TextBundle::from_section(
+            "Quests:",
+            TextStyle {
+                font_size: 18.0,
+                color: Color::rgb(1.0, 0.9, 0.6),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.0),
+            left: Val::Px(10.0),
+            ..default()
+        }),
+        QuestLogText,
+    ));
+
     // FPS text
     commands.spawn((
         TextBundle::from_section(
@@ -48,33 +95,166 @@ fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
         )
         .with_style(Style {
             position_type: PositionType::Absolute,
-            top: Val::Px(40.0),
-            left: Val::Px(10.0),
+            top: Val::Px(10.0),
+            right: Val::Px(10.0),
             ..default()
         }),
         FpsText,
     ));
+
+    // Workers text
+    commands.spawn((
+        TextBundle::from_section(
+            "Workers:",
+            TextStyle {
+                font_size: 18.0,
+                color: Color::rgb(0.9, 0.8, 0.5),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(40.0),
+            right: Val::Px(10.0),
+            ..default()
+        }),
+        WorkersText,
+    ));
 }
 
-/// Updates the inventory HUD when the player's inventory changes.
+/// Updates the inventory HUD when the player's inventory, equipment or skills change.
 fn update_inventory_text(
     inventory: Res<PlayerInventory>,
+    registry: Res<ResourceRegistry>,
+    recipes: Res<RecipeBook>,
+    curve: Res<SkillCurve>,
+    player_query: Query<(&Equipment, &Skills), With<Player>>,
     mut query: Query<&mut Text, With<InventoryText>>,
 ) {
     if let Ok(mut text) = query.get_single_mut() {
-        let new_text = format!(
-            "Inventory: Wood: {}, Stone: {}, Ore: {}",
-            inventory.resources.get(&ResourceType::Wood).unwrap_or(&0),
-            inventory.resources.get(&ResourceType::Stone).unwrap_or(&0),
-            inventory.resources.get(&ResourceType::Ore).unwrap_or(&0)
+        // One entry per defined resource type, in definition file order
+        let mut counts: Vec<String> = registry
+            .iter()
+            .map(|definition| format!("{}: {}", definition.display_name, inventory.count(definition.id)))
+            .collect();
+        // Then any crafted items the player holds, by id
+        let mut crafted: Vec<_> = inventory
+            .totals()
+            .into_iter()
+            .filter(|(resource, _)| registry.get(*resource).is_none())
+            .collect();
+        crafted.sort_by_key(|(resource, _)| resource.id());
+        counts.extend(crafted.into_iter().map(|(resource, amount)| {
+            format!("{}: {}", recipes.output_name(resource).unwrap_or(resource.id()), amount)
+        }));
+        let mut new_text = format!(
+            "Inventory ({}/{} slots): {}",
+            inventory.used_slots(),
+            inventory.slots().len(),
+            counts.join(", ")
         );
+        if let Ok((equipment, skills)) = player_query.get_single() {
+            let tools: Vec<String> = equipment
+                .tools()
+                .iter()
+                .map(|tool| {
+                    format!("{} {} ({}/{})", tool.tier.name(), tool.kind.name(), tool.durability, tool.tier.max_durability())
+                })
+                .collect();
+            if !tools.is_empty() {
+                new_text.push_str(&format!("\nTools: {}", tools.join(", ")));
+            }
+            let spares: Vec<String> =
+                equipment.spares().iter().map(|tool| format!("{} {}", tool.tier.name(), tool.kind.name())).collect();
+            if !spares.is_empty() {
+                new_text.push_str(&format!("\nSpare tools: {}", spares.join(", ")));
+            }
+            // Level and XP into the current level, out of what the next level takes
+            let skills: Vec<String> = skills
+                .iter()
+                .map(|(skill, xp)| {
+                    let level = curve.level_for(xp);
+                    match curve.xp_to_next(level) {
+                        Some(next) => {
+                            format!("{} {} ({}/{} XP)", skill.name(), level, xp - curve.xp_for_level(level), next)
+                        }
+                        None => format!("{} {} (max)", skill.name(), level),
+                    }
+                })
+                .collect();
+            if !skills.is_empty() {
+                new_text.push_str(&format!("\nSkills: {}", skills.join(", ")));
+            }
+        }
         if text.sections[0].value != new_text {
             text.sections[0].value = new_text;
         }
     }
 }
 
-pub fn update_fps_text(
+/// Lists the active quests with their objectives' progress, in quests file order.
+fn update_quest_log_text(
+    book: Res<QuestBook>,
+    log: Res<QuestLog>,
+    mut query: Query<&mut Text, With<QuestLogText>>,
+) {
+    let Ok(mut text) = query.get_single_mut() else {
+        return;
+    };
+    let mut lines = vec!["Quests:".to_string()];
+    for quest in book.iter() {
+        let Some(QuestState::Active { progress }) = log.state(&quest.id) else {
+            continue;
+        };
+        lines.push(quest.title.clone());
+        for (objective, progress) in quest.objectives.iter().zip(progress) {
+            lines.push(format!("  {} ({}/{})", objective.describe(), progress, objective.required()));
+        }
+    }
+    let new_text = lines.join("\n");
+    if text.sections[0].value != new_text {
+        text.sections[0].value = new_text;
+    }
+}
+
+/// Lists every worker with its job, what it is doing and its load, in id order, and
+/// what the stockpiles hold.
+fn update_workers_text(
+    registry: Res<ResourceRegistry>,
+    worker_query: Query<(&Npc, &Worker)>,
+    stockpile_query: Query<&Stockpile>,
+    mut query: Query<&mut Text, With<WorkersText>>,
+) {
+    let Ok(mut text) = query.get_single_mut() else {
+        return;
+    };
+    let mut workers: Vec<(&Npc, &Worker)> = worker_query.iter().collect();
+    workers.sort_by(|(a, _), (b, _)| a.id.cmp(&b.id));
+    let mut lines = vec!["Workers (J to assign a job):".to_string()];
+    for (npc, worker) in workers {
+        let job = worker.job.map_or("no job", |job| registry.display_name(job));
+        lines.push(format!("  {}: {}, {} ({} carried)", npc.name, job, worker.state.name(), worker.carried()));
+    }
+    let mut stored: Vec<(ResourceType, u32)> = Vec::new();
+    for stockpile in &stockpile_query {
+        for (resource, amount) in stockpile.inventory.totals() {
+            match stored.iter_mut().find(|(other, _)| *other == resource) {
+                Some((_, total)) => *total += amount,
+                None => stored.push((resource, amount)),
+            }
+        }
+    }
+    stored.sort_by_key(|(resource, _)| resource.id());
+    let stored: Vec<String> =
+        stored.iter().map(|(resource, amount)| format!("{} {}", amount, registry.display_name(*resource))).collect();
+    lines.push(format!("Stockpile: {}", if stored.is_empty() { "empty".to_string() } else { stored.join(", ") }));
+    let new_text = lines.join("\n");
+    if text.sections[0].value != new_text {
+        text.sections[0].value = new_text;
+    }
+}
+
+fn update_fps_text(
     diagnostics: Res<DiagnosticsStore>,
     mut query: Query<&mut Text, With<FpsText>>,
 ) {
@@ -93,4 +273,26 @@ pub fn update_fps_text(
             }
         }
     }
-} 
\ No newline at end of file
+}
+
+/// Draws a ring around the node under the cursor: green when it is in gathering range, white otherwise.
+fn highlight_hovered_node(
+    hovered: Res<HoveredNode>,
+    registry: Res<ResourceRegistry>,
+    player_query: Query<(&Position, &Player)>,
+    node_query: Query<(&Position, &Gatherable)>,
+    mut gizmos: Gizmos,
+) {
+    let Some(Ok((position, gatherable))) = hovered.0.map(|entity| node_query.get(entity)) else {
+        return;
+    };
+    let Some(definition) = registry.get(gatherable.resource_type) else {
+        return;
+    };
+    let in_range = player_query
+        .get_single()
+        .is_ok_and(|(player_position, player)| player_position.value.distance(position.value) <= player.gathering_range);
+    let color = if in_range { Color::GREEN } else { Color::WHITE };
+    let radius = definition.collider.footprint_radius() + HIGHLIGHT_MARGIN;
+    gizmos.circle(position.value + Vec3::Y * 0.05, Direction3d::Y, radius, color);
+}
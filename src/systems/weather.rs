@@ -0,0 +1,199 @@
+//! Weather: moves the seeded weather state on every fixed tick, lets storms knock
+//! nodes down and shows the weather with fog and dimmer light.
+use std::f32::consts::TAU;
+use bevy::pbr::{FogFalloff, FogSettings};
+use bevy::prelude::*;
+use rand::Rng;
+use crate::components::definitions::ResourceRegistry;
+use crate::components::types::{GameAssets, Gatherable, Position, ResourceNode, ResourceType, WorldSeed};
+use crate::components::weather::{Weather, WeatherKind, WeatherRegistry};
+use crate::systems::config::add_config;
+use crate::systems::camera::MainCamera;
+use crate::systems::daynight::{light_the_world, mix, Sun};
+use crate::systems::entities::resource_node_components;
+use crate::systems::placement::PlacementRules;
+use crate::systems::resources::{deplete_node, handle_resource_respawn};
+use crate::systems::GameSet;
+
+/// Mixed into the world seed for the weather, so the weather does not follow the map layout.
+pub(crate) const WEATHER_SEED_SALT: u64 = 0x5EA7_4E12;
+/// How far from the fallen node's spot a knocked-down node lands.
+const FALL_DISTANCE: f32 = 1.5;
+/// Directions tried around a knocked-down node, starting from a random one, for a spot to fall into.
+const FALL_DIRECTIONS: u32 = 8;
+/// Color of fog and of the sky under clouds.
+const OVERCAST: Color = Color::rgb(0.55, 0.58, 0.62);
+
+/// Plugin for the weather and what it does to the world.
+pub struct GameWeatherPlugin;
+
+impl Plugin for GameWeatherPlugin {
+    fn build(&self, app: &mut App) {
+        // Keep weather seeded by the embedding app
+        if !app.world.contains_resource::<Weather>() {
+            let seed = app.world.get_resource::<WorldSeed>().map_or_else(rand::random, |seed| seed.0);
+            app.insert_resource(Weather::new(seed ^ WEATHER_SEED_SALT));
+        }
+        add_config::<WeatherRegistry>(app);
+        app.init_resource::<WeatherRegistry>()
+            .add_event::<WeatherChanged>()
+            .add_event::<NodeKnockedDown>()
+            .add_systems(Update, show_weather.after(light_the_world))
+            // Everything in the tick sees the same weather
+            .add_systems(FixedUpdate, advance_weather.before(GameSet::Input))
+            .add_systems(FixedUpdate, knock_down_nodes.after(handle_resource_respawn).in_set(GameSet::Gathering));
+    }
+}
+
+/// Event: The weather turned into another kind.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WeatherChanged {
+    pub kind: WeatherKind,
+}
+
+/// Event: The weather knocked a node down, leaving a new node where it fell.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NodeKnockedDown {
+    /// The node knocked down, now waiting to respawn.
+    pub node: Entity,
+    /// The node it left behind.
+    pub fallen: Entity,
+}
+
+/// Moves the weather on by one fixed timestep and announces changes.
+fn advance_weather(
+    time: Res<Time>,
+    registry: Res<WeatherRegistry>,
+    mut weather: ResMut<Weather>,
+    mut changed: EventWriter<WeatherChanged>,
+) {
+    if let Some(kind) = weather.advance(time.delta_seconds(), &registry) {
+        println!("The weather turns: {}", kind.name());
+        changed.send(WeatherChanged { kind });
+    }
+}
+
+/// Now and then knocks down a standing node that can fall, as often as the weather's
+/// knockdown rate has it, depleting it and spawning the node it falls into next to it.
+fn knock_down_nodes(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut weather: ResMut<Weather>,
+    weather_registry: Res<WeatherRegistry>,
+    registry: Res<ResourceRegistry>,
+    game_assets: Option<Res<GameAssets>>,
+    mut knocked_down: EventWriter<NodeKnockedDown>,
+    mut resource_query: Query<(Entity, &Position, &mut Gatherable), With<ResourceNode>>,
+) {
+    let rate = weather_registry.effects(weather.kind()).knockdowns_per_minute;
+    if rate <= 0.0 {
+        return;
+    }
+    let mut rng = weather.rng();
+    if rng.gen::<f32>() >= rate * time.delta_seconds() / 60.0 {
+        return;
+    }
+
+    let falls_into = |resource: ResourceType| registry.get(resource).and_then(|definition| definition.falls_into);
+    let mut standing: Vec<(Entity, Vec3)> = resource_query
+        .iter()
+        .filter(|(_, _, gatherable)| !gatherable.is_depleted() && falls_into(gatherable.resource_type).is_some())
+        .map(|(entity, position, _)| (entity, position.value))
+        .collect();
+    if standing.is_empty() {
+        return;
+    }
+    // Pick from a fixed order, so replays knock down the same node whatever order the query gives
+    standing.sort_by(|(_, a), (_, b)| a.x.total_cmp(&b.x).then(a.z.total_cmp(&b.z)));
+    let (node, origin) = standing[rng.gen_range(0..standing.len())];
+    let Some(fallen_definition) = resource_query
+        .get(node)
+        .ok()
+        .and_then(|(_, _, gatherable)| falls_into(gatherable.resource_type))
+        .and_then(|fallen| registry.get(fallen))
+    else {
+        return;
+    };
+
+    // Fall in a random direction, lying along it, turning until the fallen node fits in
+    // the way world generation places nodes; with no room anywhere, the node stays standing
+    let rules = PlacementRules::default();
+    let others: Vec<Vec2> = resource_query
+        .iter()
+        .filter(|(entity, _, _)| *entity != node)
+        .map(|(_, position, _)| position.value.xz())
+        .collect();
+    let start = rng.gen_range(0.0..TAU);
+    let Some((angle, position)) = (0..FALL_DIRECTIONS)
+        .map(|step| start + TAU * step as f32 / FALL_DIRECTIONS as f32)
+        .map(|angle| (angle, origin + Vec3::new(angle.cos(), 0.0, angle.sin()) * FALL_DISTANCE))
+        .find(|(_, position)| rules.allows(position.xz(), others.iter().copied()))
+    else {
+        return;
+    };
+
+    let (_, _, mut gatherable) = resource_query.get_mut(node).expect("node was just found");
+    let Some(definition) = registry.get(gatherable.resource_type) else {
+        return;
+    };
+    println!("The storm knocked down a {}!", definition.display_name);
+    deplete_node(&mut commands, node, &mut gatherable, definition.respawn_seconds);
+
+    let transform = Transform::from_translation(position).with_rotation(Quat::from_rotation_y(-angle));
+    let model = game_assets.and_then(|assets| assets.resource_models.get(&fallen_definition.id).cloned());
+    let mut fallen = match model {
+        Some(scene) => commands.spawn(SceneBundle { scene, transform, ..default() }),
+        None => commands.spawn(SpatialBundle::from_transform(transform)),
+    };
+    fallen.insert(resource_node_components(fallen_definition, position));
+    knocked_down.send(NodeKnockedDown { node, fallen: fallen.id() });
+}
+
+/// Dims the sun, ambient light and sky under the weather and sets the camera's fog.
+///
+/// The light is reset every frame, so it is dimmed every frame; the fog is only touched
+/// when the weather kind or its settings change, or when a camera appears.
+fn show_weather(
+    mut commands: Commands,
+    mut shown_kind: Local<Option<WeatherKind>>,
+    weather: Res<Weather>,
+    registry: Res<WeatherRegistry>,
+    mut sun_query: Query<&mut DirectionalLight, With<Sun>>,
+    camera_query: Query<(Entity, Ref<MainCamera>)>,
+    ambient: Option<ResMut<AmbientLight>>,
+    clear_color: Option<ResMut<ClearColor>>,
+) {
+    let effects = registry.effects(weather.kind());
+    let light = effects.light.clamp(0.0, 1.0);
+    for mut sun in &mut sun_query {
+        sun.illuminance *= light;
+    }
+    if let Some(mut ambient) = ambient {
+        ambient.brightness *= light;
+    }
+    if let Some(mut clear_color) = clear_color {
+        // Keep the night sky dark under clouds
+        let overcast = OVERCAST * clear_color.0.l();
+        clear_color.0 = mix(overcast, clear_color.0, light);
+    }
+
+    let refresh = *shown_kind != Some(weather.kind()) || registry.is_changed();
+    *shown_kind = Some(weather.kind());
+    for (camera, main_camera) in &camera_query {
+        if !refresh && !main_camera.is_added() {
+            continue;
+        }
+        match effects.fog_distance {
+            Some(distance) => {
+                commands.entity(camera).insert(FogSettings {
+                    color: OVERCAST,
+                    falloff: FogFalloff::Linear { start: distance * 0.25, end: distance },
+                    ..default()
+                });
+            }
+            None => {
+                commands.entity(camera).remove::<FogSettings>();
+            }
+        }
+    }
+}
@@ -0,0 +1,322 @@
+//! Worker NPCs: a small state machine (idle, seek, move to, gather, return) that finds
+//! the nearest free node of each worker's job, walks there, gathers it by the player's
+//! rules and hauls the load to the nearest stockpile. Jobs are handed out with
+//! [`AssignJob`] events, from the assign-job action or any other system.
+use std::collections::HashSet;
+use std::time::Duration;
+use bevy::prelude::*;
+use crate::components::bindings::Action;
+use crate::components::definitions::ResourceRegistry;
+use crate::components::equipment::Equipment;
+use crate::components::skills::{SkillCurve, Skills};
+use crate::components::types::{Gatherable, Npc, Player, Position, ResourceNode, ResourceType};
+use crate::components::workers::{Stockpile, Worker, WorkerState};
+use crate::systems::input::TickActions;
+use crate::systems::navigation::{sync_nav_grid, NavGrid, PathFollower};
+use crate::systems::npcs::TALK_RANGE;
+use crate::systems::resources::{apply_gather_hit, process_gather_requests, GatherError, Gatherer, Reach};
+use crate::systems::spatial::NodeIndex;
+use crate::systems::GameSet;
+
+/// Farthest a worker looks for a node of its job's type.
+pub const WORK_RADIUS: f32 = 60.0;
+/// Farthest a worker can be from a stockpile to unload into it.
+pub const STOCKPILE_RANGE: f32 = 1.5;
+/// Share of its gathering range a worker walks into before it stops at a node.
+const ARRIVE_SHARE: f32 = 0.8;
+
+/// Plugin for worker NPCs and their jobs.
+pub struct GameWorkersPlugin;
+
+impl Plugin for GameWorkersPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AssignJob>()
+            .add_event::<WorkerGathered>()
+            .add_event::<WorkerDeposited>()
+            .add_systems(FixedUpdate, (assign_job_actions, assign_jobs).chain().in_set(GameSet::Input))
+            .add_systems(FixedUpdate, move_workers.after(sync_nav_grid).in_set(GameSet::Movement))
+            .add_systems(FixedUpdate, run_workers.after(process_gather_requests).in_set(GameSet::Gathering));
+    }
+}
+
+/// Event: Gives a worker a job, or takes it away with `None`. Sent by the assign-job
+/// action, but any system (e.g. a job board UI) can send one.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AssignJob {
+    pub worker: Entity,
+    pub job: Option<ResourceType>,
+}
+
+/// Event: A worker's gather hit landed on a node.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WorkerGathered {
+    pub worker: Entity,
+    pub node: Entity,
+    pub resource: ResourceType,
+    /// Units added to the worker's load by this hit.
+    pub gained: u32,
+}
+
+/// Event: A worker unloaded into a stockpile.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WorkerDeposited {
+    pub worker: Entity,
+    pub stockpile: Entity,
+    pub resource: ResourceType,
+    pub amount: u32,
+}
+
+/// Moves the closest worker within [`TALK_RANGE`] of the player on to the next job
+/// (every resource type in file order, then none) when the assign-job action is pressed.
+fn assign_job_actions(
+    actions: Res<TickActions>,
+    registry: Res<ResourceRegistry>,
+    player_query: Query<&Position, With<Player>>,
+    worker_query: Query<(Entity, &Worker, &Position)>,
+    mut assign: EventWriter<AssignJob>,
+) {
+    if !actions.just_pressed(Action::AssignJob) {
+        return;
+    }
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    let closest = worker_query
+        .iter()
+        .map(|(entity, worker, position)| (entity, worker, position.value.distance(player.value)))
+        .filter(|(_, _, distance)| *distance <= TALK_RANGE)
+        .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b));
+    let Some((entity, worker, _)) = closest else {
+        println!("No worker in reach.");
+        return;
+    };
+    let jobs: Vec<ResourceType> = registry.iter().map(|definition| definition.id).collect();
+    let job = match worker.job.and_then(|job| jobs.iter().position(|other| *other == job)) {
+        Some(index) => jobs.get(index + 1).copied(),
+        None => jobs.first().copied(),
+    };
+    assign.send(AssignJob { worker: entity, job });
+}
+
+/// Hands out the jobs asked for by [`AssignJob`] events.
+fn assign_jobs(
+    mut requests: EventReader<AssignJob>,
+    registry: Res<ResourceRegistry>,
+    mut worker_query: Query<(&Npc, &mut Worker)>,
+) {
+    for request in requests.read() {
+        let Ok((npc, mut worker)) = worker_query.get_mut(request.worker) else {
+            continue;
+        };
+        match request.job {
+            Some(job) => println!("{} now gathers {}.", npc.name, registry.display_name(job)),
+            None => println!("{} is taking a break.", npc.name),
+        }
+        worker.assign(request.job);
+    }
+}
+
+/// Walks every worker toward the node it is heading for or the nearest stockpile it is
+/// hauling to along a path around obstacles, turning it to face the way it walks.
+fn move_workers(
+    time: Res<Time>,
+    grid: Res<NavGrid>,
+    mut worker_query: Query<(&Worker, &mut PathFollower, &mut Position, &mut Transform)>,
+    node_query: Query<&Position, (With<ResourceNode>, Without<Worker>)>,
+    stockpile_query: Query<&Position, (With<Stockpile>, Without<Worker>)>,
+) {
+    for (worker, mut path, mut position, mut transform) in &mut worker_query {
+        let destination = match worker.state {
+            WorkerState::MoveTo(node) => node_query
+                .get(node)
+                .ok()
+                .map(|node| (node.value, worker.gathering_range * ARRIVE_SHARE)),
+            WorkerState::Return => nearest(position.value, stockpile_query.iter().map(|stockpile| stockpile.value))
+                .map(|stockpile| (stockpile, STOCKPILE_RANGE * ARRIVE_SHARE)),
+            _ => None,
+        };
+        let Some((target, stop_distance)) = destination else {
+            path.clear();
+            continue;
+        };
+        let distance_left = (target - position.value).xz().length() - stop_distance;
+        if distance_left <= 0.0 {
+            path.clear();
+            continue;
+        }
+        let offset = (path.steer(&grid, position.value, target) - position.value).xz();
+        let distance = offset.length();
+        if distance == 0.0 {
+            continue;
+        }
+        let direction = offset / distance;
+        let step = (worker.speed * time.delta_seconds()).min(distance).min(distance_left);
+        position.value += Vec3::new(direction.x, 0.0, direction.y) * step;
+        transform.translation = position.value;
+        // Bevy's forward is -Z, so point -Z along the walking direction
+        transform.rotation = Quat::from_rotation_y((-direction.x).atan2(-direction.y));
+    }
+}
+
+/// The point in `points` closest to `origin`.
+fn nearest(origin: Vec3, points: impl Iterator<Item = Vec3>) -> Option<Vec3> {
+    points.min_by(|a, b| a.distance_squared(origin).total_cmp(&b.distance_squared(origin)))
+}
+
+/// Runs every worker's state machine for one tick, in id order so the outcome does not
+/// depend on query order: claims the nearest free node of its job's type, hits it
+/// once in range by the same rules as the player, and unloads at the nearest stockpile
+/// once its load is full or there is nothing left to gather. A worker stopped by something
+/// in its way (a missing tool, a full stockpile or none at all) goes idle and tries
+/// again once its retry cooldown runs out.
+fn run_workers(
+    time: Res<Time>,
+    mut commands: Commands,
+    registry: Res<ResourceRegistry>,
+    curve: Res<SkillCurve>,
+    index: Res<NodeIndex>,
+    mut gathered: EventWriter<WorkerGathered>,
+    mut deposited: EventWriter<WorkerDeposited>,
+    mut worker_query: Query<(Entity, &Npc, &mut Worker, &Position, &mut Equipment, &mut Skills)>,
+    mut node_query: Query<(&Position, &mut Gatherable), With<ResourceNode>>,
+    mut stockpile_query: Query<(Entity, &Position, &mut Stockpile), Without<Worker>>,
+) {
+    let mut order: Vec<(String, Entity)> =
+        worker_query.iter().map(|(entity, npc, ..)| (npc.id.clone(), entity)).collect();
+    order.sort();
+    // Nodes some worker is already heading for or hitting
+    let mut claimed: HashSet<Entity> = worker_query.iter().filter_map(|(_, _, worker, ..)| worker.state.target()).collect();
+
+    for (_, entity) in order {
+        let (_, npc, mut worker, position, mut equipment, mut skills) =
+            worker_query.get_mut(entity).expect("worker was just listed");
+        worker.gathering_cooldown.tick(time.delta());
+        worker.retry_cooldown.tick(time.delta());
+        let standing = |node: Entity, nodes: &Query<(&Position, &mut Gatherable), With<ResourceNode>>| {
+            nodes.get(node).ok().filter(|(_, gatherable)| !gatherable.is_depleted()).map(|(position, _)| position.value)
+        };
+
+        let next = match worker.state {
+            WorkerState::Idle if worker.job.is_some() && worker.retry_cooldown.finished() => Some(WorkerState::Seek),
+            WorkerState::Idle => None,
+            WorkerState::Seek => {
+                let Some(job) = worker.job else {
+                    worker.state = WorkerState::Idle;
+                    continue;
+                };
+                let drop = registry.get(job).and_then(|definition| definition.drops).unwrap_or(job);
+                if worker.load.room_for(drop) == 0 {
+                    Some(WorkerState::Return)
+                } else {
+                    let node = index
+                        .nearest_in_radius(position.value, WORK_RADIUS, |node| {
+                            !claimed.contains(&node)
+                                && node_query.get(node).is_ok_and(|(_, gatherable)| {
+                                    gatherable.resource_type == job && !gatherable.is_depleted()
+                                })
+                        })
+                        .map(|(node, _)| node);
+                    match node {
+                        Some(node) => Some(WorkerState::MoveTo(node)),
+                        // Nothing left to gather, so bring in what there is
+                        None if worker.carried() > 0 => Some(WorkerState::Return),
+                        None => None,
+                    }
+                }
+            }
+            WorkerState::MoveTo(node) => match standing(node, &node_query) {
+                None => Some(WorkerState::Seek),
+                Some(target) if target.xz().distance(position.value.xz()) <= worker.gathering_range => {
+                    Some(WorkerState::Gather(node))
+                }
+                Some(_) => None,
+            },
+            WorkerState::Gather(node) => match standing(node, &node_query) {
+                None => Some(WorkerState::Seek),
+                Some(_) if !worker.gathering_cooldown.finished() => None,
+                Some(target) => {
+                    // Workers turn to face the node they hit
+                    let reach = Reach {
+                        position: position.value,
+                        forward: (target - position.value).normalize_or_zero(),
+                        range: worker.gathering_range,
+                    };
+                    let (_, mut gatherable) = node_query.get_mut(node).expect("node was just found");
+                    let trained = registry.get(gatherable.resource_type).and_then(|definition| definition.skill);
+                    let result = reach.check(target).and_then(|()| {
+                        let Worker { gather_damage, gather_seconds, ref mut load, .. } = *worker;
+                        let gatherer = Gatherer {
+                            bare_damage: gather_damage,
+                            bare_seconds: gather_seconds,
+                            equipment: &mut equipment,
+                            skills: &skills,
+                            curve: &curve,
+                        };
+                        apply_gather_hit(&mut commands, node, &mut gatherable, gatherer, load, &registry)
+                    });
+                    match result {
+                        Ok(hit) => {
+                            worker.gathering_cooldown.set_duration(Duration::from_secs_f32(hit.cooldown_seconds));
+                            worker.gathering_cooldown.reset();
+                            if let Some(tool) = hit.broken_tool {
+                                println!("{}'s {} {} broke!", npc.name, tool.tier.name(), tool.kind.name());
+                            }
+                            if let Some(rule) = trained {
+                                skills.add_xp(rule.skill, rule.xp, &curve);
+                            }
+                            gathered.send(WorkerGathered { worker: entity, node, resource: hit.resource, gained: hit.gained });
+                            (worker.load.room_for(hit.resource) == 0).then_some(WorkerState::Return)
+                        }
+                        Err(GatherError::OutOfRange) => Some(WorkerState::MoveTo(node)),
+                        Err(GatherError::StackFull { .. }) => Some(WorkerState::Return),
+                        Err(reason) => {
+                            println!("{} cannot gather: {}", npc.name, reason);
+                            Some(worker.stop())
+                        }
+                    }
+                }
+            },
+            WorkerState::Return => {
+                let stockpile = stockpile_query
+                    .iter()
+                    .map(|(stockpile, at, _)| (stockpile, at.value.xz().distance(position.value.xz())))
+                    .min_by(|(_, a), (_, b)| a.total_cmp(b));
+                match stockpile {
+                    Some((stockpile, distance)) if distance <= STOCKPILE_RANGE => {
+                        let (_, _, mut stockpile_inventory) = stockpile_query.get_mut(stockpile).expect("stockpile was just found");
+                        for (resource, amount) in worker.load.totals() {
+                            let added = stockpile_inventory.inventory.add_up_to(resource, amount);
+                            if added > 0 {
+                                worker.load.try_remove(resource, added).expect("worker carries what it unloads");
+                                deposited.send(WorkerDeposited { worker: entity, stockpile, resource, amount: added });
+                            }
+                        }
+                        if worker.carried() > 0 {
+                            println!("{} cannot unload: the stockpile is full", npc.name);
+                            Some(worker.stop())
+                        } else if worker.job.is_some() {
+                            Some(WorkerState::Seek)
+                        } else {
+                            Some(WorkerState::Idle)
+                        }
+                    }
+                    Some(_) => None,
+                    None => {
+                        println!("{} has nowhere to unload: there is no stockpile", npc.name);
+                        Some(worker.stop())
+                    }
+                }
+            }
+        };
+
+        if let Some(state) = next {
+            if let Some(node) = worker.state.target() {
+                claimed.remove(&node);
+            }
+            if let Some(node) = state.target() {
+                claimed.insert(node);
+            }
+            worker.state = state;
+        }
+    }
+}
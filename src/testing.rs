@@ -0,0 +1,293 @@
+//! Headless simulation harness for driving the gameplay systems from tests.
+//! Builds an app on `MinimalPlugins` and Rapier with a fixed, empty world
+//! (ground, player, camera rig) and steps the fixed schedule tick by tick.
+//! Only for tests: it reads the data files from the source tree.
+use std::fs;
+use std::path::Path;
+use bevy::prelude::*;
+
+use crate::components::time_of_day::TimeOfDay;
+use crate::components::config::{FromConfig, RonAsset};
+use crate::components::weather::{Weather, WeatherKind, WeatherRegistry};
+use crate::components::types::{Npc, PlayerInventory, Position, ResourceType};
+use crate::components::equipment::{Equipment, Tool};
+use crate::components::definitions::ResourceRegistry;
+use crate::components::recipes::RecipeBook;
+use crate::components::quests::QuestBook;
+use crate::components::skills::SkillCurve;
+use crate::systems::{
+    configure_game_sets,
+    camera::{GameCameraPlugin, MainCamera},
+    crafting::GameCraftingPlugin,
+    entities::{ground_components, player_components, resource_node_components, stockpile_components, worker_components},
+    input::{init_input_resources, MovementInput},
+    movement::GameMovementPlugin,
+    replay::{run_fixed_frame, run_fixed_tick, GameReplayPlugin},
+    resources::GameResourcesPlugin,
+    skills::GameSkillsPlugin,
+    npcs::GameNpcPlugin,
+    quests::GameQuestsPlugin,
+    daynight::GameDayNightPlugin,
+    weather::GameWeatherPlugin,
+    workers::GameWorkersPlugin,
+    navigation::{GameNavigationPlugin, NavGrid},
+};
+use crate::{add_headless_support, add_physics, insert_game_resources};
+
+/// Seed of the harness's weather.
+const HARNESS_WEATHER_SEED: u64 = 0;
+
+/// Deterministic simulation of the gameplay systems.
+///
+/// Movement comes from [`SimulationHarness::set_movement`] rather than the keyboard,
+/// so keyboard movement is not read; other keys (e.g. `E` to gather) are injected with
+/// [`SimulationHarness::press`]. Physics runs in the fixed schedule with the same
+/// timestep, so every [`SimulationHarness::tick`] advances the world by exactly one step.
+pub struct SimulationHarness {
+    pub app: App,
+    player: Entity,
+}
+
+impl SimulationHarness {
+    /// Creates a harness with the ground, the player at the origin and the camera rig.
+    pub fn new() -> Self {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        add_headless_support(&mut app);
+        // Physics runs in the fixed schedule, one step per tick
+        add_physics(&mut app);
+        insert_game_resources(&mut app);
+        configure_game_sets(&mut app);
+        init_input_resources(&mut app);
+        // Pin the weather, which would otherwise follow the random world seed
+        app.insert_resource(Weather::new(HARNESS_WEATHER_SEED));
+        app.add_plugins((
+            GameMovementPlugin,
+            GameResourcesPlugin,
+            GameCraftingPlugin,
+            GameCameraPlugin,
+            GameReplayPlugin,
+            GameSkillsPlugin,
+            GameNpcPlugin,
+            GameQuestsPlugin,
+            GameDayNightPlugin,
+            GameWeatherPlugin,
+            GameWorkersPlugin,
+            GameNavigationPlugin,
+        ))
+            // Read the data files directly instead of waiting on the asset server
+            .insert_resource(read_config::<RecipeBook>())
+            .insert_resource(read_config::<QuestBook>())
+            .insert_resource(read_config::<SkillCurve>())
+            .insert_resource(read_config::<WeatherRegistry>());
+        let registry = read_config::<ResourceRegistry>();
+        registry.apply_stack_sizes(&mut app.world.resource_mut::<PlayerInventory>());
+        app.insert_resource(registry);
+
+        app.world.spawn((
+            SpatialBundle::from_transform(Transform::from_xyz(0.0, -0.5, 0.0)),
+            ground_components(),
+        ));
+        app.world.spawn((
+            SpatialBundle::from_transform(
+                Transform::from_xyz(0.0, 5.0, 10.0).looking_at(Vec3::ZERO, Vec3::Y),
+            ),
+            MainCamera::default(),
+        ));
+        let player = app
+            .world
+            .spawn((
+                SpatialBundle::from_transform(Transform::from_xyz(0.0, 0.5, 0.0)),
+                player_components(),
+            ))
+            .id();
+
+        Self { app, player }
+    }
+
+    /// Spawns a resource node at full health at `position` and returns its entity.
+    ///
+    /// Panics if `resource_type` is not in the resource definitions file.
+    pub fn spawn_resource(&mut self, resource_type: ResourceType, position: Vec3) -> Entity {
+        let bundle = {
+            let registry = self.app.world.resource::<ResourceRegistry>();
+            let definition = registry.get(resource_type).expect("resource type is not defined");
+            resource_node_components(definition, position)
+        };
+        self.app
+            .world
+            .spawn((SpatialBundle::from_transform(Transform::from_translation(position)), bundle))
+            .id()
+    }
+
+    /// The in-game clock.
+    pub fn time_of_day(&self) -> TimeOfDay {
+        *self.app.world.resource::<TimeOfDay>()
+    }
+
+    /// Sets the in-game clock to `hour` of the current day.
+    pub fn set_hour(&mut self, hour: f32) {
+        self.app.world.resource_mut::<TimeOfDay>().set_hour(hour);
+    }
+
+    /// Moves the in-game clock on by `hours` without running any ticks.
+    pub fn advance_hours(&mut self, hours: f32) {
+        self.app.world.resource_mut::<TimeOfDay>().advance_hours(hours);
+    }
+
+    /// The current weather.
+    pub fn weather(&self) -> Weather {
+        *self.app.world.resource::<Weather>()
+    }
+
+    /// Switches the weather to `kind` for `seconds`.
+    pub fn set_weather(&mut self, kind: WeatherKind, seconds: f32) {
+        self.app.world.resource_mut::<Weather>().set(kind, seconds);
+    }
+
+    /// The navigation grid as of the last sync.
+    pub fn nav_grid(&self) -> &NavGrid {
+        self.app.world.resource::<NavGrid>()
+    }
+
+    /// Spawns an idle worker with the given id (also its name) at `position` and returns its entity.
+    pub fn spawn_worker(&mut self, id: &str, position: Vec3) -> Entity {
+        self.app
+            .world
+            .spawn((
+                SpatialBundle::from_transform(Transform::from_translation(position)),
+                worker_components(id, id, position),
+            ))
+            .id()
+    }
+
+    /// Spawns an empty stockpile at `position` and returns its entity.
+    pub fn spawn_stockpile(&mut self, position: Vec3) -> Entity {
+        self.app
+            .world
+            .spawn((
+                SpatialBundle::from_transform(Transform::from_translation(position)),
+                stockpile_components(position),
+            ))
+            .id()
+    }
+
+    /// Spawns an NPC with the given id at `position` and returns its entity.
+    pub fn spawn_npc(&mut self, id: &str, position: Vec3) -> Entity {
+        self.app
+            .world
+            .spawn((
+                SpatialBundle::from_transform(Transform::from_translation(position)),
+                Npc { id: id.to_owned(), name: id.to_owned() },
+                Position { value: position },
+            ))
+            .id()
+    }
+
+    /// Sets the movement input used until changed (x = right, y = forward; length 1 is full speed).
+    pub fn set_movement(&mut self, direction: Vec2) {
+        self.app.world.resource_mut::<MovementInput>().0 = direction;
+    }
+
+    /// Presses a key. It counts as just pressed for the next tick and stays held until released.
+    pub fn press(&mut self, key: KeyCode) {
+        self.app.world.resource_mut::<ButtonInput<KeyCode>>().press(key);
+    }
+
+    /// Releases a held key.
+    pub fn release(&mut self, key: KeyCode) {
+        self.app.world.resource_mut::<ButtonInput<KeyCode>>().release(key);
+    }
+
+    /// Advances the simulation by one fixed timestep.
+    pub fn tick(&mut self) {
+        // Presses only count as "just pressed" for one tick
+        run_fixed_tick(&mut self.app.world);
+    }
+
+    /// Runs one frame that advances the simulation by `ticks` fixed timesteps, as a frame
+    /// longer or shorter than the timestep does. Presses reach only its first tick, or
+    /// wait for the next frame when it runs none.
+    pub fn frame(&mut self, ticks: u32) {
+        run_fixed_frame(&mut self.app.world, ticks);
+    }
+
+    /// Advances the simulation by `ticks` fixed timesteps.
+    pub fn step(&mut self, ticks: u32) {
+        for _ in 0..ticks {
+            self.tick();
+        }
+    }
+
+    /// The player entity.
+    pub fn player(&self) -> Entity {
+        self.player
+    }
+
+    /// The player's current transform.
+    pub fn player_transform(&self) -> Transform {
+        *self.app.world.get::<Transform>(self.player).expect("player was despawned")
+    }
+
+    /// The player's inventory.
+    pub fn inventory(&self) -> &PlayerInventory {
+        self.app.world.resource::<PlayerInventory>()
+    }
+
+    /// Amount of `resource_type` in the player's inventory.
+    pub fn inventory_count(&self, resource_type: ResourceType) -> u32 {
+        self.inventory().count(resource_type)
+    }
+
+    /// Adds `amount` of `resource_type` to the player's inventory.
+    ///
+    /// Panics if it does not fit.
+    pub fn give(&mut self, resource_type: ResourceType, amount: u32) {
+        let mut inventory = self.app.world.resource_mut::<PlayerInventory>();
+        inventory.try_add(resource_type, amount).expect("no room in the inventory");
+    }
+
+    /// Equips `tool` on the player, returning the tool of the same kind it replaces.
+    pub fn equip(&mut self, tool: Tool) -> Option<Tool> {
+        let mut equipment = self.app.world.get_mut::<Equipment>(self.player).expect("player was despawned");
+        equipment.equip(tool)
+    }
+
+    /// The player's equipment.
+    pub fn equipment(&self) -> &Equipment {
+        self.app.world.get::<Equipment>(self.player).expect("player was despawned")
+    }
+
+    /// Every event of type `E` sent so far that is still buffered.
+    pub fn events<E: Event + Clone>(&self) -> Vec<E> {
+        let events = self.app.world.resource::<Events<E>>();
+        events.get_reader().read(events).cloned().collect()
+    }
+
+    /// Whether `entity` still exists.
+    pub fn exists(&self, entity: Entity) -> bool {
+        self.app.world.get_entity(entity).is_some()
+    }
+}
+
+/// Builds `R` from its config file in the game's assets folder.
+///
+/// Panics if the file cannot be read or parsed.
+pub fn read_config<R: FromConfig>() -> R {
+    R::from_config(&read_config_file::<R>())
+}
+
+/// Parses `R`'s config file in the game's assets folder as written, before any defaults fill it in.
+///
+/// Panics if the file cannot be read or parsed.
+pub fn read_config_file<R: FromConfig>() -> R::Config {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets").join(R::PATH);
+    let text = fs::read_to_string(&path).unwrap_or_else(|err| panic!("could not read {}: {}", path.display(), err));
+    R::Config::from_ron(&text).unwrap_or_else(|err| panic!("could not parse {}: {}", path.display(), err))
+}
+
+impl Default for SimulationHarness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
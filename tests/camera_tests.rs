@@ -0,0 +1,157 @@
+use bevy::prelude::*;
+use bevy_game_demo::components::config::{FromConfig, RonAsset};
+use bevy_game_demo::components::camera_settings::{CameraConfig, CameraSettings};
+use bevy_game_demo::systems::camera::{CameraMode, MainCamera, CAMERA_COLLISION_MARGIN, FIRST_PERSON_EYE_HEIGHT};
+use bevy_game_demo::testing::{read_config, SimulationHarness};
+use bevy_rapier3d::prelude::*;
+
+/// Where the default orbit puts the camera with nothing in the way.
+fn desired() -> Vec3 {
+    Vec3::new(0.0, 0.5, 0.0) + MainCamera::default().orbit_offset()
+}
+
+fn camera_translation(sim: &mut SimulationHarness) -> Vec3 {
+    let world = &mut sim.app.world;
+    world.query_filtered::<&Transform, With<MainCamera>>().single(world).translation
+}
+
+/// Spawns a wall across the camera's view, `z` units behind the player.
+fn spawn_wall(sim: &mut SimulationHarness, z: f32) -> Entity {
+    sim.app
+        .world
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_xyz(0.0, 1.5, z)),
+            RigidBody::Fixed,
+            Collider::cuboid(3.0, 3.0, 0.2),
+        ))
+        .id()
+}
+
+#[test]
+fn unobstructed_camera_settles_at_its_orbit_ignoring_the_player() {
+    let mut sim = SimulationHarness::new();
+    sim.step(200);
+
+    assert!(camera_translation(&mut sim).distance(desired()) < 0.05);
+}
+
+#[test]
+fn camera_pulls_in_front_of_a_wall_and_eases_back_out() {
+    let mut sim = SimulationHarness::new();
+    sim.step(200);
+    let wall = spawn_wall(&mut sim, 3.0);
+    sim.tick();
+    sim.tick();
+
+    // In front of the wall straight away, not easing through it. The margin is
+    // measured along the line from the player, which points mostly along z.
+    let blocked = camera_translation(&mut sim);
+    let wall_face = 3.0 - 0.2;
+    assert!(blocked.z < wall_face - CAMERA_COLLISION_MARGIN * 0.8, "camera is in the wall: {blocked:?}");
+    assert!(blocked.z > 2.0, "camera pulled in too far: {blocked:?}");
+
+    sim.app.world.entity_mut(wall).insert(ColliderDisabled);
+    sim.step(2);
+    let easing = camera_translation(&mut sim);
+    assert!(easing.z > blocked.z && easing.z < desired().z - 0.5, "camera should ease out: {easing:?}");
+    sim.step(200);
+    assert!(camera_translation(&mut sim).distance(desired()) < 0.05);
+}
+
+fn set_camera(sim: &mut SimulationHarness, mode: CameraMode, angle: f32) {
+    let mut camera = sim.app.world.query::<&mut MainCamera>().single_mut(&mut sim.app.world);
+    camera.mode = mode;
+    camera.angle = angle;
+}
+
+#[test]
+fn switching_modes_eases_into_the_new_view() {
+    let mut sim = SimulationHarness::new();
+    sim.step(200);
+    set_camera(&mut sim, CameraMode::FirstPerson, 0.0);
+    sim.step(3);
+
+    // On the way from the orbit to the player's head
+    let easing = camera_translation(&mut sim);
+    assert!(easing.z > 0.5 && easing.z < desired().z - 0.5, "camera should ease in: {easing:?}");
+    sim.step(200);
+    let head = sim.player_transform().translation + Vec3::Y * FIRST_PERSON_EYE_HEIGHT;
+    assert!(camera_translation(&mut sim).distance(head) < 0.05);
+
+    set_camera(&mut sim, CameraMode::TopDown, 0.0);
+    sim.step(200);
+    let top_down = camera_translation(&mut sim);
+    let height = MainCamera::default().top_down_height;
+    assert!((top_down.y - height).abs() < 0.05, "top-down camera should look down from its height: {top_down:?}");
+}
+
+#[test]
+fn walking_forward_moves_away_from_the_camera_in_every_mode() {
+    for mode in [CameraMode::Orbit, CameraMode::FirstPerson, CameraMode::TopDown] {
+        let mut sim = SimulationHarness::new();
+        let angle = 0.7;
+        set_camera(&mut sim, mode, angle);
+        sim.step(200);
+        let start = sim.player_transform().translation;
+        sim.set_movement(Vec2::Y);
+        sim.step(30);
+
+        let walked = (sim.player_transform().translation - start).xz().normalize();
+        let forward = Vec2::new(-angle.sin(), -angle.cos());
+        assert!(walked.dot(forward) > 0.99, "{mode:?} walked {walked:?}, expected {forward:?}");
+    }
+}
+
+#[test]
+fn camera_settings_file_matches_built_in_defaults() {
+    assert_eq!(read_config::<CameraSettings>(), CameraSettings::default());
+}
+
+#[test]
+fn partial_camera_config_only_changes_listed_settings() {
+    let config = CameraConfig::from_ron("(invert_y: Some(true), max_pitch: Some(0.9))").unwrap();
+    let settings = CameraSettings::from_config(&config);
+
+    assert!(settings.invert_y);
+    assert_eq!(settings.max_pitch, 0.9);
+    assert_eq!(
+        CameraSettings { invert_y: false, max_pitch: CameraSettings::default().max_pitch, ..settings },
+        CameraSettings::default()
+    );
+}
+
+#[test]
+fn camera_limits_set_the_wrong_way_round_are_ignored() {
+    let defaults = CameraSettings::default();
+    // Only the highest pitch is set, below the built-in lowest, so clamping to it would panic
+    let config =
+        CameraConfig::from_ron("(max_pitch: Some(-0.5), min_distance: Some(12.0), max_distance: Some(15.0))").unwrap();
+    let settings = CameraSettings::from_config(&config);
+
+    assert_eq!((settings.min_pitch, settings.max_pitch), (defaults.min_pitch, defaults.max_pitch));
+    // A pair set the right way round together still applies
+    assert_eq!((settings.min_distance, settings.max_distance), (12.0, 15.0));
+
+    let config = CameraConfig::from_ron("(min_top_down_height: Some(50.0))").unwrap();
+    let settings = CameraSettings::from_config(&config);
+    assert_eq!(settings.min_top_down_height, defaults.min_top_down_height);
+}
+
+#[test]
+fn pitch_raises_the_orbit_and_tilts_the_first_person_view() {
+    let mut sim = SimulationHarness::new();
+    sim.app.world.query::<&mut MainCamera>().single_mut(&mut sim.app.world).pitch = 1.0;
+    sim.step(200);
+
+    // Same distance from the player, but higher up
+    let orbit = camera_translation(&mut sim);
+    let player = sim.player_transform().translation;
+    assert!((orbit.distance(player) - MainCamera::default().distance).abs() < 0.05);
+    assert!(orbit.y > desired().y + 1.0, "camera should rise with pitch: {orbit:?}");
+
+    set_camera(&mut sim, CameraMode::FirstPerson, 0.0);
+    sim.step(200);
+    let world = &mut sim.app.world;
+    let forward = world.query_filtered::<&Transform, With<MainCamera>>().single(world).forward();
+    assert!((forward.y + 1.0f32.sin()).abs() < 0.01, "first-person view should look down: {forward:?}");
+}
@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+use bevy_game_demo::components::types::{CraftingStation, PlayerInventory, Position, ResourceType};
+use bevy_game_demo::systems::crafting::{
+    CraftCompleted, CraftError, CraftFailed, CraftRequested, CraftStarted, CraftingQueue,
+};
+use bevy_game_demo::testing::SimulationHarness;
+
+fn planks() -> ResourceType {
+    ResourceType::new("planks")
+}
+
+fn craft(sim: &mut SimulationHarness, recipe: &str) {
+    sim.app.world.send_event(CraftRequested { recipe: recipe.to_string() });
+    sim.tick();
+}
+
+fn failures(sim: &SimulationHarness) -> Vec<CraftError> {
+    sim.events::<CraftFailed>().into_iter().map(|failed| failed.reason).collect()
+}
+
+#[test]
+fn crafting_takes_inputs_then_gives_outputs_after_craft_time() {
+    let mut sim = SimulationHarness::new();
+    sim.give(ResourceType::WOOD, 3);
+    craft(&mut sim, "planks");
+
+    assert_eq!(sim.inventory_count(ResourceType::WOOD), 1);
+    assert_eq!(sim.inventory_count(planks()), 0);
+    assert_eq!(sim.events::<CraftStarted>().len(), 1);
+
+    // Planks take two seconds
+    sim.step(120);
+    assert_eq!(sim.inventory_count(planks()), 0);
+    sim.step(10);
+    assert_eq!(sim.inventory_count(planks()), 1);
+    assert_eq!(sim.events::<CraftCompleted>(), vec![CraftCompleted { recipe: "planks".to_string() }]);
+    assert!(sim.app.world.resource::<CraftingQueue>().jobs.is_empty());
+}
+
+#[test]
+fn queued_crafts_complete_one_at_a_time() {
+    let mut sim = SimulationHarness::new();
+    sim.give(ResourceType::WOOD, 4);
+    craft(&mut sim, "planks");
+    craft(&mut sim, "planks");
+    assert_eq!(sim.inventory_count(ResourceType::WOOD), 0);
+
+    sim.step(130);
+    assert_eq!(sim.inventory_count(planks()), 1);
+    sim.step(130);
+    assert_eq!(sim.inventory_count(planks()), 2);
+}
+
+#[test]
+fn craft_without_inputs_is_rejected() {
+    let mut sim = SimulationHarness::new();
+    sim.give(ResourceType::WOOD, 1);
+    craft(&mut sim, "planks");
+
+    assert_eq!(
+        failures(&sim),
+        vec![CraftError::MissingInput { resource: ResourceType::WOOD, needed: 2, available: 1 }]
+    );
+    assert_eq!(sim.inventory_count(ResourceType::WOOD), 1);
+    assert!(sim.events::<CraftStarted>().is_empty());
+}
+
+#[test]
+fn craft_that_would_overflow_the_inventory_is_rejected() {
+    let mut sim = SimulationHarness::new();
+    // One slot of planks one short of a full stack and one slot of wood
+    sim.app.world.resource_mut::<PlayerInventory>().set_slots(vec![None; 2]);
+    sim.give(planks(), 9);
+    sim.give(ResourceType::WOOD, 6);
+    craft(&mut sim, "planks");
+    // The first craft's planks are already spoken for and there is no free slot
+    craft(&mut sim, "planks");
+
+    assert_eq!(failures(&sim), vec![CraftError::StackFull { resource: planks() }]);
+    assert_eq!(sim.inventory_count(ResourceType::WOOD), 4);
+}
+
+#[test]
+fn unknown_recipe_is_rejected() {
+    let mut sim = SimulationHarness::new();
+    craft(&mut sim, "golden_crown");
+    assert_eq!(failures(&sim), vec![CraftError::UnknownRecipe]);
+}
+
+#[test]
+fn station_recipe_needs_a_station_nearby() {
+    let mut sim = SimulationHarness::new();
+    sim.give(ResourceType::ORE, 2);
+    craft(&mut sim, "ingot");
+    assert_eq!(failures(&sim), vec![CraftError::StationRequired { station: "furnace".to_string() }]);
+
+    sim.app.world.spawn((
+        Position { value: Vec3::new(2.0, 0.0, 0.0) },
+        CraftingStation { kind: "furnace".to_string() },
+    ));
+    craft(&mut sim, "ingot");
+    assert_eq!(sim.events::<CraftStarted>(), vec![CraftStarted { recipe: "ingot".to_string() }]);
+    assert_eq!(sim.inventory_count(ResourceType::ORE), 0);
+}
+
+#[test]
+fn number_keys_craft_recipes_in_file_order() {
+    let mut sim = SimulationHarness::new();
+    sim.give(ResourceType::STONE, 2);
+    sim.press(KeyCode::Digit2);
+    sim.tick();
+
+    assert_eq!(sim.events::<CraftStarted>(), vec![CraftStarted { recipe: "bricks".to_string() }]);
+}
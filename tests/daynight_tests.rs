@@ -0,0 +1,93 @@
+use bevy::prelude::*;
+use bevy_game_demo::components::time_of_day::{DayPeriod, TimeOfDay, START_HOUR};
+use bevy_game_demo::components::types::{Gatherable, ResourceType};
+use bevy_game_demo::systems::daynight::DayPeriodChanged;
+use bevy_game_demo::systems::save::{apply_save, capture_save};
+use bevy_game_demo::testing::SimulationHarness;
+
+/// Depletes `node` with a respawn timer that runs out on the next tick.
+fn deplete(sim: &mut SimulationHarness, node: Entity) {
+    let mut gatherable = sim.app.world.get_mut::<Gatherable>(node).unwrap();
+    gatherable.health = 0;
+    gatherable.respawn_timer = Some(Timer::from_seconds(0.001, TimerMode::Once));
+}
+
+fn is_depleted(sim: &SimulationHarness, node: Entity) -> bool {
+    sim.app.world.get::<Gatherable>(node).unwrap().is_depleted()
+}
+
+#[test]
+fn clock_wraps_into_the_next_day() {
+    let mut clock = TimeOfDay::new(22.0);
+    clock.advance_hours(5.0);
+    assert_eq!((clock.day(), clock.hour()), (1, 3.0));
+
+    // Half a day length of real time is twelve hours
+    clock.advance(clock.day_length_seconds / 2.0);
+    assert_eq!((clock.day(), clock.hour()), (1, 15.0));
+    assert_eq!(clock.period(), DayPeriod::Day);
+
+    clock.set_hour(-1.0);
+    assert_eq!((clock.day(), clock.hour(), clock.period()), (1, 23.0, DayPeriod::Night));
+}
+
+#[test]
+fn daylight_peaks_at_noon_and_is_gone_at_night() {
+    assert!((TimeOfDay::new(12.0).daylight() - 1.0).abs() < 1e-5);
+    assert!(TimeOfDay::new(7.0).daylight() < TimeOfDay::new(10.0).daylight());
+    assert_eq!(TimeOfDay::new(0.0).daylight(), 0.0);
+    assert_eq!(TimeOfDay::new(20.0).daylight(), 0.0);
+}
+
+#[test]
+fn clock_runs_with_the_simulation_and_announces_dusk() {
+    let mut sim = SimulationHarness::new();
+    assert_eq!(sim.time_of_day().hour(), START_HOUR);
+    sim.set_hour(17.99);
+    // One second is 0.04 hours at the default day length
+    sim.step(64);
+
+    assert_eq!(sim.time_of_day().period(), DayPeriod::Night);
+    assert_eq!(sim.events::<DayPeriodChanged>(), vec![DayPeriodChanged { period: DayPeriod::Night, day: 0 }]);
+}
+
+#[test]
+fn paused_clock_stands_still() {
+    let mut sim = SimulationHarness::new();
+    sim.app.world.resource_mut::<TimeOfDay>().paused = true;
+    sim.step(64);
+
+    assert_eq!(sim.time_of_day().hour(), START_HOUR);
+    sim.advance_hours(2.0);
+    assert_eq!(sim.time_of_day().hour(), START_HOUR + 2.0);
+}
+
+#[test]
+fn ore_only_respawns_at_night() {
+    let mut sim = SimulationHarness::new();
+    let ore = sim.spawn_resource(ResourceType::ORE, Vec3::new(5.0, 0.0, 5.0));
+    let rock = sim.spawn_resource(ResourceType::STONE, Vec3::new(-5.0, 0.0, 5.0));
+    sim.tick();
+    deplete(&mut sim, ore);
+    deplete(&mut sim, rock);
+    sim.step(4);
+
+    assert!(is_depleted(&sim, ore));
+    assert!(!is_depleted(&sim, rock));
+
+    sim.advance_hours(12.0);
+    sim.tick();
+    assert!(!is_depleted(&sim, ore));
+}
+
+#[test]
+fn save_keeps_the_clock() {
+    let mut sim = SimulationHarness::new();
+    sim.advance_hours(40.0);
+    let save = capture_save(&mut sim.app.world).unwrap();
+
+    let mut loaded_sim = SimulationHarness::new();
+    apply_save(&mut loaded_sim.app.world, &save).unwrap();
+    assert_eq!(loaded_sim.time_of_day(), sim.time_of_day());
+    assert_eq!((loaded_sim.time_of_day().day(), loaded_sim.time_of_day().hour()), (2, 0.0));
+}
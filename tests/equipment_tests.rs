@@ -0,0 +1,122 @@
+use bevy::prelude::*;
+use bevy_game_demo::components::equipment::{Equipment, Tool, ToolKind, ToolTier};
+use bevy_game_demo::components::skills::{Skill, SkillCurve, Skills};
+use bevy_game_demo::components::types::{Gatherable, ResourceType};
+use bevy_game_demo::systems::resources::{GatherError, GatherRejected, GatherRequest, GatherTarget, ToolBroken};
+use bevy_game_demo::systems::save::{apply_save, capture_save};
+use bevy_game_demo::testing::SimulationHarness;
+
+/// A harness past the initial gathering cooldown with a node of `resource` in front of the player.
+fn harness_facing(resource: ResourceType) -> (SimulationHarness, Entity) {
+    let mut sim = SimulationHarness::new();
+    sim.step(64);
+    let node = sim.spawn_resource(resource, Vec3::new(0.0, 0.0, -1.5));
+    sim.tick();
+    (sim, node)
+}
+
+/// Sends a gather request for `node` for one tick.
+fn hit(sim: &mut SimulationHarness, node: Entity) {
+    sim.app.world.send_event(GatherRequest { target: GatherTarget::Node(node) });
+    sim.tick();
+}
+
+fn health(sim: &SimulationHarness, node: Entity) -> u32 {
+    sim.app.world.get::<Gatherable>(node).unwrap().health
+}
+
+#[test]
+fn ore_needs_an_iron_pickaxe() {
+    let (mut sim, ore) = harness_facing(ResourceType::ORE);
+    // Skilled enough that only the tool holds the player back
+    let curve = sim.app.world.resource::<SkillCurve>().clone();
+    let player = sim.player();
+    sim.app.world.get_mut::<Skills>(player).unwrap().add_xp(Skill::Mining, curve.xp_for_level(3), &curve);
+    hit(&mut sim, ore);
+    sim.equip(Tool::new(ToolKind::Pickaxe, ToolTier::Stone));
+    hit(&mut sim, ore);
+    assert_eq!(health(&sim, ore), 200);
+
+    sim.equip(Tool::new(ToolKind::Pickaxe, ToolTier::Iron));
+    hit(&mut sim, ore);
+
+    let needs_iron = GatherRejected(GatherError::ToolRequired { kind: ToolKind::Pickaxe, tier: ToolTier::Iron });
+    assert_eq!(sim.events::<GatherRejected>(), vec![needs_iron.clone(), needs_iron]);
+    assert_eq!(health(&sim, ore), 100);
+}
+
+#[test]
+fn axe_hits_harder_and_recovers_sooner_than_bare_hands() {
+    let (mut sim, tree) = harness_facing(ResourceType::WOOD);
+    sim.equip(Tool::new(ToolKind::Axe, ToolTier::Stone));
+    hit(&mut sim, tree);
+    assert_eq!(health(&sim, tree), 100 - ToolTier::Stone.damage());
+
+    // Past the stone axe's cooldown but well short of the bare-handed one
+    sim.step(52);
+    hit(&mut sim, tree);
+
+    assert!(sim.events::<GatherRejected>().is_empty());
+    assert_eq!(health(&sim, tree), 0);
+}
+
+#[test]
+fn tools_wear_with_every_hit_and_break() {
+    let (mut sim, rock) = harness_facing(ResourceType::STONE);
+    sim.equip(Tool { kind: ToolKind::Pickaxe, tier: ToolTier::Stone, durability: 2 });
+    hit(&mut sim, rock);
+    assert_eq!(sim.equipment().tool(ToolKind::Pickaxe).unwrap().durability, 1);
+
+    sim.step(64);
+    hit(&mut sim, rock);
+
+    assert_eq!(
+        sim.events::<ToolBroken>(),
+        vec![ToolBroken { tool: Tool { kind: ToolKind::Pickaxe, tier: ToolTier::Stone, durability: 0 } }]
+    );
+    assert!(sim.equipment().tools().is_empty());
+}
+
+#[test]
+fn tool_of_the_wrong_kind_neither_helps_nor_wears() {
+    let (mut sim, rock) = harness_facing(ResourceType::STONE);
+    let axe = Tool::new(ToolKind::Axe, ToolTier::Iron);
+    sim.equip(axe);
+    hit(&mut sim, rock);
+
+    // Bare-handed damage
+    assert_eq!(health(&sim, rock), 100);
+    assert_eq!(sim.equipment().tool(ToolKind::Axe), Some(&axe));
+}
+
+#[test]
+fn save_keeps_equipped_tools_and_their_wear() {
+    let (mut sim, tree) = harness_facing(ResourceType::WOOD);
+    sim.equip(Tool::new(ToolKind::Axe, ToolTier::Iron));
+    hit(&mut sim, tree);
+    let save = capture_save(&mut sim.app.world).unwrap();
+
+    let player = sim.player();
+    sim.app.world.get_mut::<Equipment>(player).unwrap().unequip(ToolKind::Axe);
+    apply_save(&mut sim.app.world, &save).unwrap();
+
+    let axe = sim.equipment().tool(ToolKind::Axe).unwrap();
+    assert_eq!(axe.durability, ToolTier::Iron.max_durability() - 1);
+}
+
+#[test]
+fn received_tools_replace_only_worse_ones_and_spares_replace_broken_ones() {
+    let (mut sim, rock) = harness_facing(ResourceType::STONE);
+    let player = sim.player();
+    let mut equipment = sim.app.world.get_mut::<Equipment>(player).unwrap();
+    assert!(equipment.receive(Tool { kind: ToolKind::Pickaxe, tier: ToolTier::Iron, durability: 1 }));
+    // A worse tool is kept as a spare rather than swapped in
+    assert!(!equipment.receive(Tool::new(ToolKind::Pickaxe, ToolTier::Stone)));
+    assert_eq!(equipment.tool(ToolKind::Pickaxe).unwrap().tier, ToolTier::Iron);
+    assert_eq!(equipment.spares().len(), 1);
+
+    hit(&mut sim, rock);
+    assert_eq!(sim.events::<ToolBroken>().len(), 1);
+    assert_eq!(sim.equipment().tool(ToolKind::Pickaxe), Some(&Tool::new(ToolKind::Pickaxe, ToolTier::Stone)));
+    assert!(sim.equipment().spares().is_empty());
+}
@@ -0,0 +1,74 @@
+use bevy::input::gamepad::{GamepadConnection, GamepadConnectionEvent, GamepadEvent, GamepadInfo};
+use bevy::prelude::*;
+use bevy_game_demo::components::types::ResourceType;
+use bevy_game_demo::systems::input::{apply_deadzone, ActiveGamepad};
+use bevy_game_demo::testing::SimulationHarness;
+use bevy_game_demo::HeadlessGamePlugin;
+
+#[test]
+fn deadzone_drops_small_tilts_and_rescales_the_rest() {
+    assert_eq!(apply_deadzone(Vec2::new(0.1, 0.1), 0.2), Vec2::ZERO);
+    assert!((apply_deadzone(Vec2::new(0.0, 0.6), 0.2) - Vec2::new(0.0, 0.5)).length() < 1e-5);
+    assert!((apply_deadzone(Vec2::new(-1.0, 0.0), 0.2) - Vec2::new(-1.0, 0.0)).length() < 1e-5);
+    // Diagonals past the edge of the stick's square are clamped to full tilt
+    assert!((apply_deadzone(Vec2::ONE, 0.2).length() - 1.0).abs() < 1e-5);
+}
+
+#[test]
+fn half_tilt_walks_at_half_speed() {
+    let distance = |tilt: f32| {
+        let mut sim = SimulationHarness::new();
+        sim.set_movement(Vec2::Y * tilt);
+        sim.step(60);
+        -sim.player_transform().translation.z
+    };
+    let full = distance(1.0);
+    let half = distance(0.5);
+    assert!((half / full - 0.5).abs() < 0.05, "full {full}, half {half}");
+}
+
+#[test]
+fn face_button_gathers() {
+    let mut sim = SimulationHarness::new();
+    let gamepad = Gamepad::new(0);
+    sim.app.insert_resource(ActiveGamepad(Some(gamepad)));
+    sim.spawn_resource(ResourceType::WOOD, Vec3::new(0.0, 0.0, -6.0));
+    sim.set_movement(Vec2::Y);
+    sim.step(60);
+    sim.set_movement(Vec2::ZERO);
+    sim.step(10);
+
+    sim.app
+        .world
+        .resource_mut::<ButtonInput<GamepadButton>>()
+        .press(GamepadButton::new(gamepad, GamepadButtonType::South));
+    sim.tick();
+    assert_eq!(sim.inventory_count(ResourceType::WOOD), 1);
+}
+
+fn connection(app: &mut App, id: usize, connection: GamepadConnection) {
+    app.world.send_event(GamepadEvent::Connection(GamepadConnectionEvent::new(Gamepad::new(id), connection)));
+    app.update();
+}
+
+fn connected() -> GamepadConnection {
+    GamepadConnection::Connected(GamepadInfo { name: "Test pad".to_string() })
+}
+
+#[test]
+fn active_gamepad_follows_hot_plugging() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, HeadlessGamePlugin));
+    app.update();
+    assert_eq!(app.world.resource::<ActiveGamepad>().0, None);
+
+    connection(&mut app, 0, connected());
+    connection(&mut app, 1, connected());
+    assert_eq!(app.world.resource::<ActiveGamepad>().0, Some(Gamepad::new(0)));
+
+    connection(&mut app, 0, GamepadConnection::Disconnected);
+    assert_eq!(app.world.resource::<ActiveGamepad>().0, Some(Gamepad::new(1)));
+
+    connection(&mut app, 1, GamepadConnection::Disconnected);
+    assert_eq!(app.world.resource::<ActiveGamepad>().0, None);
+}
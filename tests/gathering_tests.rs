@@ -0,0 +1,78 @@
+use bevy::prelude::*;
+use bevy_game_demo::components::types::{Gatherable, PlayerInventory, ResourceType};
+use bevy_game_demo::systems::resources::{GatherError, GatherRejected, GatherRequest, GatherTarget, ResourceGathered};
+use bevy_game_demo::testing::SimulationHarness;
+
+/// Sends a gather request for one tick, the way any input source would.
+fn request(sim: &mut SimulationHarness, target: GatherTarget) {
+    sim.app.world.send_event(GatherRequest { target });
+    sim.tick();
+}
+
+/// A harness past the initial gathering cooldown.
+fn ready_harness() -> SimulationHarness {
+    let mut sim = SimulationHarness::new();
+    sim.step(64);
+    sim
+}
+
+#[test]
+fn requests_from_code_gather_and_report_the_hit() {
+    let mut sim = ready_harness();
+    let tree = sim.spawn_resource(ResourceType::WOOD, Vec3::new(0.0, 0.0, -1.5));
+    sim.tick();
+    request(&mut sim, GatherTarget::Node(tree));
+    sim.step(64);
+    request(&mut sim, GatherTarget::Facing);
+
+    assert_eq!(sim.inventory_count(ResourceType::WOOD), 2);
+    assert_eq!(
+        sim.events::<ResourceGathered>(),
+        vec![
+            ResourceGathered { node: tree, resource: ResourceType::WOOD, gained: 1, total: 1, depleted: false },
+            ResourceGathered { node: tree, resource: ResourceType::WOOD, gained: 1, total: 2, depleted: true },
+        ]
+    );
+    assert!(sim.events::<GatherRejected>().is_empty());
+}
+
+#[test]
+fn second_hit_within_the_cooldown_is_rejected() {
+    let mut sim = ready_harness();
+    let tree = sim.spawn_resource(ResourceType::WOOD, Vec3::new(0.0, 0.0, -1.5));
+    sim.tick();
+    request(&mut sim, GatherTarget::Node(tree));
+    sim.step(10);
+    request(&mut sim, GatherTarget::Node(tree));
+
+    assert_eq!(sim.inventory_count(ResourceType::WOOD), 1);
+    assert_eq!(sim.app.world.get::<Gatherable>(tree).unwrap().health, 50);
+    assert_eq!(sim.events::<GatherRejected>(), vec![GatherRejected(GatherError::OnCooldown)]);
+}
+
+#[test]
+fn facing_request_without_a_node_in_front_is_rejected() {
+    let mut sim = ready_harness();
+    sim.spawn_resource(ResourceType::WOOD, Vec3::new(1.5, 0.0, 0.0));
+    sim.tick();
+    request(&mut sim, GatherTarget::Facing);
+
+    assert_eq!(sim.inventory_count(ResourceType::WOOD), 0);
+    assert_eq!(sim.events::<GatherRejected>(), vec![GatherRejected(GatherError::NothingInReach)]);
+}
+
+#[test]
+fn full_inventory_rejects_without_damaging_the_node() {
+    let mut sim = ready_harness();
+    let room = sim.app.world.resource::<PlayerInventory>().room_for(ResourceType::WOOD);
+    sim.give(ResourceType::WOOD, room);
+    let tree = sim.spawn_resource(ResourceType::WOOD, Vec3::new(0.0, 0.0, -1.5));
+    sim.tick();
+    request(&mut sim, GatherTarget::Node(tree));
+
+    assert_eq!(sim.app.world.get::<Gatherable>(tree).unwrap().health, 100);
+    assert_eq!(
+        sim.events::<GatherRejected>(),
+        vec![GatherRejected(GatherError::StackFull { resource: ResourceType::WOOD })]
+    );
+}
@@ -0,0 +1,68 @@
+use std::time::Duration;
+use bevy::prelude::*;
+use bevy_game_demo::components::types::{
+    CraftingStation, Gatherable, Player, Position, ResourceNode, ResourceType, WorldSeed,
+};
+use bevy_game_demo::HeadlessGamePlugin;
+
+/// Updates `app` until the resource nodes have spawned, which happens once the
+/// definitions file has loaded in the background. Returns the number of nodes.
+fn wait_for_nodes(app: &mut App) -> usize {
+    let mut nodes = 0;
+    for _ in 0..200 {
+        app.update();
+        nodes = app.world.query_filtered::<Entity, With<ResourceNode>>().iter(&app.world).count();
+        if nodes > 0 {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    nodes
+}
+
+#[test]
+fn headless_game_spawns_world() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, HeadlessGamePlugin));
+    let nodes = wait_for_nodes(&mut app);
+
+    let players = app.world.query_filtered::<Entity, With<Player>>().iter(&app.world).count();
+    assert_eq!(players, 1);
+    assert_eq!(nodes, 30);
+
+    // Ore and a furnace to smelt it are part of the world
+    let ore = app
+        .world
+        .query::<&Gatherable>()
+        .iter(&app.world)
+        .filter(|gatherable| gatherable.resource_type == ResourceType::ORE)
+        .count();
+    assert!(ore > 0);
+    let mut stations = app.world.query::<&CraftingStation>();
+    assert!(stations.iter(&app.world).any(|station| station.kind == "furnace"));
+}
+
+/// Positions of every resource node in a headless game started from `seed`.
+fn node_positions(seed: u64) -> Vec<Vec3> {
+    let mut app = App::new();
+    app.insert_resource(WorldSeed(seed))
+        .add_plugins((MinimalPlugins, HeadlessGamePlugin));
+    wait_for_nodes(&mut app);
+
+    let mut positions: Vec<Vec3> = app
+        .world
+        .query_filtered::<&Position, With<ResourceNode>>()
+        .iter(&app.world)
+        .map(|position| position.value)
+        .collect();
+    positions.sort_by(|a, b| a.x.total_cmp(&b.x));
+    positions
+}
+
+#[test]
+fn same_seed_generates_same_map() {
+    let map = node_positions(1234);
+    assert_eq!(map.len(), 30);
+    assert_eq!(map, node_positions(1234));
+    assert_ne!(map, node_positions(4321));
+}
@@ -0,0 +1,93 @@
+use bevy::prelude::*;
+use bevy_game_demo::components::config::{FromConfig, RonAsset};
+use bevy_game_demo::components::bindings::{Action, ActionMap, Binding, InputBindings};
+use bevy_game_demo::components::types::ResourceType;
+use bevy_game_demo::components::workers::Worker;
+use bevy_game_demo::testing::{read_config, read_config_file, SimulationHarness};
+
+#[test]
+fn bindings_file_matches_built_in_defaults() {
+    let defaults = ActionMap::default();
+    let from_file = read_config::<ActionMap>();
+    for action in read_config_file::<ActionMap>().bindings.keys() {
+        assert_eq!(from_file.bindings(*action), defaults.bindings(*action), "{action:?}");
+    }
+    assert_eq!(from_file.stick_deadzone, defaults.stick_deadzone);
+}
+
+#[test]
+fn partial_config_only_changes_listed_actions() {
+    let config = InputBindings::from_ron(
+        "(bindings: { Gather: [Key(KeyF), Mouse(Middle)] }, stick_deadzone: Some(0.3))",
+    )
+    .unwrap();
+    let map = ActionMap::from_config(&config);
+
+    assert_eq!(map.bindings(Action::Gather), &[Binding::Key(KeyCode::KeyF), Binding::Mouse(MouseButton::Middle)]);
+    assert_eq!(map.bindings(Action::MoveForward), &[Binding::Key(KeyCode::KeyW)]);
+    assert_eq!(map.stick_deadzone, 0.3);
+}
+
+#[test]
+fn bind_rebind_and_unbind() {
+    let mut map = ActionMap::default();
+    map.bind(Action::Gather, Binding::Key(KeyCode::KeyF));
+    map.bind(Action::Gather, Binding::Key(KeyCode::KeyF));
+    assert_eq!(
+        map.bindings(Action::Gather),
+        &[
+            Binding::Key(KeyCode::KeyE),
+            Binding::Gamepad(GamepadButtonType::South),
+            Binding::Key(KeyCode::KeyF),
+        ]
+    );
+
+    map.rebind(Action::Gather, Binding::Key(KeyCode::KeyG));
+    assert_eq!(map.bindings(Action::Gather), &[Binding::Key(KeyCode::KeyG)]);
+
+    map.unbind(Action::Gather);
+    assert!(map.bindings(Action::Gather).is_empty());
+}
+
+#[test]
+fn gathering_follows_runtime_rebinding() {
+    let mut sim = SimulationHarness::new();
+    sim.spawn_resource(ResourceType::WOOD, Vec3::new(0.0, 0.0, -6.0));
+    sim.set_movement(Vec2::Y);
+    sim.step(60);
+    sim.set_movement(Vec2::ZERO);
+    sim.step(10);
+    sim.app.world.resource_mut::<ActionMap>().rebind(Action::Gather, Binding::Key(KeyCode::KeyF));
+
+    sim.press(KeyCode::KeyE);
+    sim.tick();
+    sim.release(KeyCode::KeyE);
+    assert_eq!(sim.inventory_count(ResourceType::WOOD), 0);
+
+    sim.press(KeyCode::KeyF);
+    sim.tick();
+    assert_eq!(sim.inventory_count(ResourceType::WOOD), 1);
+}
+
+#[test]
+fn each_press_reaches_exactly_one_tick_however_many_a_frame_runs() {
+    let mut sim = SimulationHarness::new();
+    let ada = sim.spawn_worker("ada", Vec3::new(1.5, 0.8, 0.0));
+    let job = |sim: &SimulationHarness| sim.app.world.get::<Worker>(ada).unwrap().job;
+
+    // A frame running two ticks hands the press to the first one only
+    sim.press(KeyCode::KeyJ);
+    sim.frame(2);
+    sim.release(KeyCode::KeyJ);
+    assert_eq!(job(&sim), Some(ResourceType::WOOD));
+
+    // A frame running none keeps the press for the next tick
+    sim.press(KeyCode::KeyJ);
+    sim.frame(0);
+    sim.release(KeyCode::KeyJ);
+    assert_eq!(job(&sim), Some(ResourceType::WOOD));
+    sim.frame(1);
+    assert_eq!(job(&sim), Some(ResourceType::STONE));
+    sim.step(4);
+    assert_eq!(job(&sim), Some(ResourceType::STONE));
+}
@@ -0,0 +1,84 @@
+use bevy_game_demo::components::inventory::{Inventory, InventoryError, ItemStack};
+use bevy_game_demo::components::types::ResourceType;
+
+const WOOD: ResourceType = ResourceType::WOOD;
+const STONE: ResourceType = ResourceType::STONE;
+
+#[test]
+fn adding_tops_off_stacks_then_overflows_into_empty_slots() {
+    let mut inventory = Inventory::new(3, 10);
+    inventory.try_add(WOOD, 7).unwrap();
+    inventory.try_add(STONE, 2).unwrap();
+    inventory.try_add(WOOD, 8).unwrap();
+
+    assert_eq!(
+        inventory.slots(),
+        &[
+            Some(ItemStack { item: WOOD, amount: 10 }),
+            Some(ItemStack { item: STONE, amount: 2 }),
+            Some(ItemStack { item: WOOD, amount: 5 }),
+        ]
+    );
+    assert_eq!(inventory.count(WOOD), 15);
+    assert_eq!(inventory.room_for(WOOD), 5);
+}
+
+#[test]
+fn adding_more_than_fits_changes_nothing() {
+    let mut inventory = Inventory::new(2, 10);
+    inventory.set_stack_size(WOOD, 20);
+    inventory.try_add(STONE, 1).unwrap();
+
+    assert_eq!(
+        inventory.try_add(WOOD, 21),
+        Err(InventoryError::NoRoom { item: WOOD, amount: 21, room: 20 })
+    );
+    assert_eq!(inventory.count(WOOD), 0);
+    assert_eq!(inventory.add_up_to(WOOD, 21), 20);
+}
+
+#[test]
+fn removing_takes_from_the_last_stacks_and_frees_their_slots() {
+    let mut inventory = Inventory::new(3, 5);
+    inventory.try_add(WOOD, 12).unwrap();
+
+    assert_eq!(
+        inventory.try_remove(WOOD, 13),
+        Err(InventoryError::NotEnough { item: WOOD, needed: 13, available: 12 })
+    );
+    inventory.try_remove(WOOD, 4).unwrap();
+
+    assert_eq!(inventory.slots(), &[Some(ItemStack { item: WOOD, amount: 5 }), Some(ItemStack { item: WOOD, amount: 3 }), None]);
+}
+
+#[test]
+fn transfer_is_refused_when_the_receiver_is_full() {
+    let mut from = Inventory::new(2, 10);
+    let mut to = Inventory::new(1, 10);
+    from.try_add(WOOD, 8).unwrap();
+    to.try_add(WOOD, 5).unwrap();
+
+    assert_eq!(
+        from.transfer_to(&mut to, WOOD, 6),
+        Err(InventoryError::NoRoom { item: WOOD, amount: 6, room: 5 })
+    );
+    assert_eq!((from.count(WOOD), to.count(WOOD)), (8, 5));
+
+    from.transfer_to(&mut to, WOOD, 5).unwrap();
+    assert_eq!((from.count(WOOD), to.count(WOOD)), (3, 10));
+}
+
+#[test]
+fn failed_transaction_rolls_back_every_change() {
+    let mut inventory = Inventory::new(2, 10);
+    inventory.try_add(WOOD, 4).unwrap();
+    let before = inventory.clone();
+
+    let result = inventory.transaction(|inventory| {
+        inventory.try_remove(WOOD, 4)?;
+        inventory.try_add(STONE, 25)
+    });
+
+    assert_eq!(result, Err(InventoryError::NoRoom { item: STONE, amount: 25, room: 20 }));
+    assert_eq!(inventory, before);
+}
@@ -0,0 +1,177 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::ColliderDisabled;
+use bevy_game_demo::components::types::{Gatherable, ResourceType};
+use bevy_game_demo::components::workers::{Worker, WorkerState};
+use bevy_game_demo::systems::navigation::{NavGrid, PathFollower, WalkRequest};
+use bevy_game_demo::systems::resources::{GatherRequest, GatherTarget};
+use bevy_game_demo::systems::workers::{AssignJob, WorkerGathered};
+use bevy_game_demo::testing::SimulationHarness;
+
+/// Spawns a wall of rocks along x = `x`, from z = -`half_length` to z = `half_length`.
+fn rock_wall(sim: &mut SimulationHarness, x: f32, half_length: i32) {
+    for z in -half_length..=half_length {
+        sim.spawn_resource(ResourceType::STONE, Vec3::new(x, 0.0, z as f32));
+    }
+}
+
+/// Whether every straight leg of `path`, starting at `from`, stays on walkable ground.
+fn path_is_clear(grid: &NavGrid, from: Vec3, path: &[Vec3]) -> bool {
+    std::iter::once(from).chain(path.iter().copied()).collect::<Vec<_>>().windows(2).all(|leg| {
+        (0..=40).all(|step| grid.is_walkable(leg[0].lerp(leg[1], step as f32 / 40.0)) || step == 0)
+    })
+}
+
+#[test]
+fn static_colliders_block_the_grid_but_the_ground_does_not() {
+    let mut sim = SimulationHarness::new();
+    sim.spawn_resource(ResourceType::WOOD, Vec3::new(5.0, 0.0, 5.0));
+    sim.spawn_stockpile(Vec3::new(-5.0, 0.25, 0.0));
+    sim.tick();
+
+    let grid = sim.nav_grid();
+    // The tree and the stockpile; the ground is too low and the player is dynamic, so neither is an obstacle
+    assert_eq!(grid.obstacle_count(), 2);
+    assert!(!grid.is_walkable(Vec3::new(5.0, 0.0, 5.0)));
+    // Obstacles are grown by the walker's radius
+    assert!(!grid.is_walkable(Vec3::new(5.8, 0.0, 5.0)));
+    assert!(grid.is_walkable(Vec3::new(6.6, 0.0, 5.0)));
+    assert!(!grid.is_walkable(Vec3::new(-5.0, 0.0, 0.0)));
+    assert!(grid.is_walkable(Vec3::ZERO));
+    // Off the edge of the ground
+    assert!(!grid.is_walkable(Vec3::new(60.0, 0.0, 0.0)));
+}
+
+#[test]
+fn depleting_and_respawning_a_node_updates_the_grid() {
+    let mut sim = SimulationHarness::new();
+    sim.step(64);
+    let log = sim.spawn_resource(ResourceType::new("log"), Vec3::new(0.0, 0.0, -1.5));
+    let tree = sim.spawn_resource(ResourceType::WOOD, Vec3::new(5.0, 0.0, 5.0));
+    sim.tick();
+    let version = sim.nav_grid().version();
+    assert!(!sim.nav_grid().is_walkable(Vec3::new(0.0, 0.0, -1.5)));
+
+    // Chopping the log clears it away for good
+    sim.app.world.send_event(GatherRequest { target: GatherTarget::Node(log) });
+    sim.tick();
+    sim.tick();
+    assert!(!sim.exists(log));
+    assert!(sim.nav_grid().is_walkable(Vec3::new(0.0, 0.0, -1.5)));
+
+    // A depleted tree can be walked through until it grows back
+    sim.app.world.get_mut::<Gatherable>(tree).unwrap().health = 0;
+    sim.app.world.get_mut::<Gatherable>(tree).unwrap().respawn_timer = Some(Timer::from_seconds(0.5, TimerMode::Once));
+    sim.app.world.entity_mut(tree).insert(ColliderDisabled);
+    sim.tick();
+    assert!(sim.nav_grid().is_walkable(Vec3::new(5.0, 0.0, 5.0)));
+    sim.step(64);
+    assert!(!sim.app.world.get::<Gatherable>(tree).unwrap().is_depleted());
+    assert!(!sim.nav_grid().is_walkable(Vec3::new(5.0, 0.0, 5.0)));
+    assert!(sim.nav_grid().version() > version);
+}
+
+#[test]
+fn paths_go_around_a_wall() {
+    let mut sim = SimulationHarness::new();
+    rock_wall(&mut sim, 5.0, 4);
+    sim.tick();
+    let from = Vec3::new(0.0, 0.5, 0.0);
+    let to = Vec3::new(10.0, 0.0, 0.0);
+
+    let path = sim.nav_grid().find_path(from, to).unwrap();
+    assert_eq!(*path.last().unwrap(), Vec3::new(10.0, 0.5, 0.0));
+    assert!(path_is_clear(sim.nav_grid(), from, &path));
+    // The wall reaches z = 4 plus the rocks' radius and the walker's, so the path rounds its end
+    assert!(path.iter().any(|waypoint| waypoint.z.abs() > 5.0));
+    // Cut corners leave only a few waypoints
+    assert!(path.len() <= 4, "{:?}", path);
+}
+
+#[test]
+fn walkers_only_plan_again_when_their_way_changes() {
+    let mut grid = NavGrid::default();
+    let from = Vec3::ZERO;
+    let to = Vec3::new(10.0, 0.0, 0.0);
+    let mut follower = PathFollower::default();
+    follower.steer(&grid, from, to);
+    // Mark the path, so a new plan would show
+    let detour = vec![Vec3::new(5.0, 0.0, 1.0), to];
+    follower.waypoints = detour.clone();
+
+    // An obstacle far from the way leaves the path alone
+    grid.set_obstacle(Entity::from_raw(1), Vec3::new(-10.0, 0.0, 10.0), Vec3::new(-9.0, 1.0, 11.0));
+    assert_eq!(follower.steer(&grid, from, to), detour[0]);
+    assert_eq!(follower.waypoints, detour);
+
+    // One across it means finding a way around
+    grid.set_obstacle(Entity::from_raw(2), Vec3::new(2.0, 0.0, -0.5), Vec3::new(2.5, 1.0, 1.5));
+    follower.steer(&grid, from, to);
+    assert_ne!(follower.waypoints, detour);
+    assert!(path_is_clear(&grid, from, &follower.waypoints));
+}
+
+#[test]
+fn enclosed_spots_have_no_path() {
+    let mut sim = SimulationHarness::new();
+    for x in -3..=3 {
+        for z in [-3, 3] {
+            sim.spawn_resource(ResourceType::STONE, Vec3::new(10.0 + x as f32, 0.0, z as f32));
+            sim.spawn_resource(ResourceType::STONE, Vec3::new(10.0 + z as f32, 0.0, x as f32));
+        }
+    }
+    sim.tick();
+
+    assert_eq!(sim.nav_grid().find_path(Vec3::ZERO, Vec3::new(10.0, 0.0, 0.0)), None);
+    // Spots inside an obstacle lead to the closest walkable spot next to it on the near side instead
+    let path = sim.nav_grid().find_path(Vec3::new(10.0, 0.0, -10.0), Vec3::new(10.0, 0.0, -3.0)).unwrap();
+    let end = *path.last().unwrap();
+    assert!(end.distance(Vec3::new(10.0, 0.0, -3.0)) < 2.0);
+    assert!(end.z < -3.0);
+    assert!(sim.nav_grid().is_walkable(end));
+}
+
+#[test]
+fn workers_walk_around_obstacles_to_their_node() {
+    let mut sim = SimulationHarness::new();
+    rock_wall(&mut sim, 8.0, 3);
+    let ada = sim.spawn_worker("ada", Vec3::new(5.0, 0.8, 0.0));
+    let tree = sim.spawn_resource(ResourceType::WOOD, Vec3::new(11.0, 0.0, 0.0));
+    sim.tick();
+    sim.app.world.send_event(AssignJob { worker: ada, job: Some(ResourceType::WOOD) });
+    sim.step(2);
+    assert_eq!(sim.app.world.get::<Worker>(ada).unwrap().state, WorkerState::MoveTo(tree));
+
+    // Track the worker's walk and make sure it never cuts through the wall
+    let mut farthest_aside: f32 = 0.0;
+    for _ in 0..8 * 64 {
+        sim.tick();
+        let position = sim.app.world.get::<Transform>(ada).unwrap().translation;
+        assert!(sim.nav_grid().is_walkable(position), "walked into the wall at {}", position);
+        farthest_aside = farthest_aside.max(position.z.abs());
+    }
+
+    assert!(farthest_aside > 3.0);
+    let hits = sim.events::<WorkerGathered>();
+    assert!(!hits.is_empty());
+    assert!(hits.iter().all(|hit| hit.node == tree));
+}
+
+#[test]
+fn walk_requests_take_the_player_around_obstacles() {
+    let mut sim = SimulationHarness::new();
+    rock_wall(&mut sim, 3.0, 2);
+    sim.step(32);
+    sim.app.world.send_event(WalkRequest { destination: Vec3::new(6.0, 0.0, 0.0) });
+    sim.step(4 * 64);
+
+    let position = sim.player_transform().translation;
+    assert!(position.xz().distance(Vec2::new(6.0, 0.0)) < 0.3, "stopped at {}", position);
+    assert_eq!(sim.app.world.get::<PathFollower>(sim.player()).unwrap().destination, None);
+
+    // Movement input takes over from a path being walked
+    sim.app.world.send_event(WalkRequest { destination: Vec3::ZERO });
+    sim.step(8);
+    sim.set_movement(Vec2::Y);
+    sim.tick();
+    assert_eq!(sim.app.world.get::<PathFollower>(sim.player()).unwrap().destination, None);
+}
@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+use bevy_game_demo::components::types::{Gatherable, ResourceType};
+use bevy_game_demo::systems::resources::{pick_gatherable, GatherError, GatherRejected, HoveredNode};
+use bevy_game_demo::testing::SimulationHarness;
+use bevy_rapier3d::prelude::*;
+
+/// Casts a ray from the harness camera's spot through `target`.
+fn pick(sim: &mut SimulationHarness, target: Vec3) -> Option<Entity> {
+    let origin = Vec3::new(0.0, 5.0, 10.0);
+    let ray = Ray3d::new(origin, target - origin);
+    let world = &mut sim.app.world;
+    let gatherables: Vec<Entity> = world.query_filtered::<Entity, With<Gatherable>>().iter(world).collect();
+    pick_gatherable(world.resource::<RapierContext>(), ray, |entity| gatherables.contains(&entity))
+}
+
+/// Clicks `node` once the initial gathering cooldown is over.
+fn click(sim: &mut SimulationHarness, node: Entity) {
+    sim.step(64);
+    sim.app.insert_resource(HoveredNode(Some(node)));
+    sim.app.world.resource_mut::<ButtonInput<MouseButton>>().press(MouseButton::Left);
+    sim.tick();
+    sim.app.world.resource_mut::<ButtonInput<MouseButton>>().release(MouseButton::Left);
+}
+
+#[test]
+fn ray_picks_the_node_under_the_cursor_not_the_nearest() {
+    let mut sim = SimulationHarness::new();
+    let near = sim.spawn_resource(ResourceType::WOOD, Vec3::new(0.0, 0.0, -2.0));
+    let far = sim.spawn_resource(ResourceType::STONE, Vec3::new(6.0, 0.0, -6.0));
+    sim.tick();
+
+    assert_eq!(pick(&mut sim, Vec3::new(6.0, 0.2, -6.0)), Some(far));
+    assert_eq!(pick(&mut sim, Vec3::new(0.0, 0.5, -2.0)), Some(near));
+    // The ground and the player are looked through, so a ray over the player's
+    // shoulder lands on the node behind them
+    assert_eq!(pick(&mut sim, Vec3::new(-8.0, 0.0, 0.0)), None);
+    assert_eq!(pick(&mut sim, Vec3::new(0.0, 0.5, 0.0)), Some(near));
+}
+
+#[test]
+fn depleted_nodes_cannot_be_picked() {
+    let mut sim = SimulationHarness::new();
+    let node = sim.spawn_resource(ResourceType::WOOD, Vec3::new(0.0, 0.0, -2.0));
+    sim.app.world.entity_mut(node).insert(ColliderDisabled);
+    sim.tick();
+
+    assert_eq!(pick(&mut sim, Vec3::new(0.0, 0.5, -2.0)), None);
+}
+
+#[test]
+fn clicking_a_node_in_range_gathers_it() {
+    let mut sim = SimulationHarness::new();
+    let node = sim.spawn_resource(ResourceType::WOOD, Vec3::new(0.0, 0.0, -1.5));
+    sim.tick();
+    click(&mut sim, node);
+
+    assert_eq!(sim.inventory_count(ResourceType::WOOD), 1);
+    assert_eq!(sim.app.world.get::<Gatherable>(node).unwrap().health, 50);
+}
+
+#[test]
+fn clicking_a_node_out_of_range_or_behind_does_nothing() {
+    let mut sim = SimulationHarness::new();
+    let far = sim.spawn_resource(ResourceType::WOOD, Vec3::new(0.0, 0.0, -6.0));
+    let behind = sim.spawn_resource(ResourceType::WOOD, Vec3::new(0.0, 0.0, 1.5));
+    sim.tick();
+    click(&mut sim, far);
+    click(&mut sim, behind);
+
+    assert_eq!(sim.inventory_count(ResourceType::WOOD), 0);
+    assert_eq!(sim.app.world.get::<Gatherable>(far).unwrap().health, 100);
+    assert_eq!(sim.app.world.get::<Gatherable>(behind).unwrap().health, 100);
+    assert_eq!(
+        sim.events::<GatherRejected>(),
+        vec![GatherRejected(GatherError::OutOfRange), GatherRejected(GatherError::NotFacing)]
+    );
+}
@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+use bevy_game_demo::components::types::WorldSeed;
+use bevy_game_demo::systems::placement::{scatter_points, world_rng, PlacementRules};
+
+fn scatter(seed: u64) -> Vec<Vec2> {
+    scatter_points(&mut world_rng(WorldSeed(seed)), 30, &PlacementRules::default())
+}
+
+#[test]
+fn same_seed_gives_same_points() {
+    assert_eq!(scatter(7), scatter(7));
+    assert_ne!(scatter(7), scatter(8));
+}
+
+#[test]
+fn points_keep_their_spacing_and_clear_zone() {
+    let rules = PlacementRules::default();
+    for seed in 0..20 {
+        let points = scatter(seed);
+        assert_eq!(points.len(), 30);
+        for (i, point) in points.iter().enumerate() {
+            assert!(point.x.abs() <= rules.half_extent && point.y.abs() <= rules.half_extent);
+            assert!(point.distance(rules.clear_center) >= rules.clear_radius, "{point} is in the spawn zone");
+            for other in &points[i + 1..] {
+                assert!(point.distance(*other) >= rules.min_spacing, "{point} and {other} overlap");
+            }
+        }
+    }
+}
+
+#[test]
+fn crowded_area_returns_fewer_points() {
+    let rules = PlacementRules { half_extent: 2.0, clear_radius: 0.0, ..default() };
+    let points = scatter_points(&mut world_rng(WorldSeed(1)), 30, &rules);
+    assert!(!points.is_empty() && points.len() < 30);
+}
+
+#[test]
+fn seed_is_read_from_command_line() {
+    let args = |list: &[&str]| list.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+    assert_eq!(WorldSeed::from_args(args(&["game", "--seed", "42"])), Some(WorldSeed(42)));
+    assert_eq!(WorldSeed::from_args(args(&["game", "--seed=9"])), Some(WorldSeed(9)));
+    assert_eq!(WorldSeed::from_args(args(&["game", "--seedless"])), None);
+    assert_eq!(WorldSeed::from_args(args(&["game"])), None);
+}
@@ -0,0 +1,154 @@
+use bevy::prelude::*;
+use bevy_game_demo::components::config::RonAsset;
+use bevy_game_demo::components::equipment::{Tool, ToolKind, ToolTier};
+use bevy_game_demo::components::quests::{QuestActivity, QuestBook, QuestLog, QuestState, Quests, Reward};
+use bevy_game_demo::components::types::ResourceType;
+use bevy_game_demo::systems::quests::{QuestCompleted, QuestStarted};
+use bevy_game_demo::systems::resources::{GatherRequest, GatherTarget};
+use bevy_game_demo::systems::save::{apply_save, capture_save};
+use bevy_game_demo::testing::SimulationHarness;
+
+fn book(text: &str) -> QuestBook {
+    QuestBook::new(Quests::from_ron(text).expect("could not parse quests"))
+}
+
+fn started(sim: &SimulationHarness) -> Vec<String> {
+    sim.events::<QuestStarted>().into_iter().map(|event| event.quest).collect()
+}
+
+fn completed(sim: &SimulationHarness) -> Vec<String> {
+    sim.events::<QuestCompleted>().into_iter().map(|event| event.quest).collect()
+}
+
+#[test]
+fn quests_start_once_their_prerequisites_are_completed() {
+    let book = book(
+        r#"(quests: [
+            (id: "a", title: "A", objectives: [Gather(resource: "wood", amount: 3)]),
+            (id: "b", title: "B", prerequisites: ["a"], objectives: [TalkTo(npc: "guide")]),
+        ])"#,
+    );
+    let mut log = QuestLog::default();
+    assert_eq!(log.start_available(&book), vec!["a"]);
+
+    let wood = |amount| QuestActivity::Gathered { resource: ResourceType::WOOD, amount };
+    assert!(log.record(&book, &wood(2)).is_empty());
+    assert!(log.record(&book, &QuestActivity::Gathered { resource: ResourceType::STONE, amount: 5 }).is_empty());
+    assert_eq!(log.state("a"), Some(&QuestState::Active { progress: vec![2] }));
+    assert_eq!(log.record(&book, &wood(4)), vec!["a"]);
+
+    assert_eq!(log.start_available(&book), vec!["b"]);
+    assert_eq!(log.start_available(&book), Vec::<String>::new());
+}
+
+#[test]
+fn talking_to_the_guide_opens_the_next_quests() {
+    let mut sim = SimulationHarness::new();
+    sim.spawn_npc("guide", Vec3::new(0.0, 0.5, -2.0));
+    sim.tick();
+    sim.press(KeyCode::KeyF);
+    sim.step(2);
+
+    assert_eq!(completed(&sim), vec!["welcome"]);
+    assert_eq!(started(&sim), vec!["welcome", "firewood", "scouting"]);
+}
+
+#[test]
+fn workers_nearby_do_not_stand_in_for_the_guide() {
+    let mut sim = SimulationHarness::new();
+    sim.spawn_npc("guide", Vec3::new(0.0, 0.5, -2.5));
+    sim.spawn_worker("ada", Vec3::new(0.5, 0.8, -1.0));
+    sim.tick();
+    sim.press(KeyCode::KeyF);
+    sim.step(2);
+
+    assert_eq!(completed(&sim), vec!["welcome"]);
+}
+
+#[test]
+fn the_default_quests_lead_to_an_iron_pickaxe() {
+    let sim = SimulationHarness::new();
+    let book = sim.app.world.resource::<QuestBook>();
+    let mut log = QuestLog::default();
+    log.start_available(book);
+    let activities = [
+        QuestActivity::TalkedTo { npc: "guide" },
+        QuestActivity::Gathered { resource: ResourceType::WOOD, amount: 5 },
+        QuestActivity::Crafted { recipe: "planks" },
+        QuestActivity::Crafted { recipe: "planks" },
+        QuestActivity::At { position: Vec3::new(15.0, 0.0, 15.0) },
+        QuestActivity::TalkedTo { npc: "guide" },
+        QuestActivity::Crafted { recipe: "bricks" },
+        QuestActivity::Crafted { recipe: "bricks" },
+    ];
+    for activity in &activities {
+        log.record(book, activity);
+        log.start_available(book);
+    }
+
+    let iron_pickaxe = Reward::Tool(ToolKind::Pickaxe, ToolTier::Iron);
+    let rewarded_by = book.iter().find(|quest| quest.rewards.contains(&iron_pickaxe)).unwrap();
+    assert!(log.is_completed(&rewarded_by.id));
+}
+
+#[test]
+fn gathering_completes_a_quest_and_hands_out_its_rewards() {
+    let mut sim = SimulationHarness::new();
+    sim.app.insert_resource(book(
+        r#"(quests: [(
+            id: "chop",
+            title: "Chop",
+            objectives: [Gather(resource: "wood", amount: 2)],
+            rewards: [Items("stone", 3), Tool(Axe, Iron)],
+        )])"#,
+    ));
+    sim.step(64);
+    let tree = sim.spawn_resource(ResourceType::WOOD, Vec3::new(0.0, 0.0, -1.5));
+    for _ in 0..2 {
+        sim.app.world.send_event(GatherRequest { target: GatherTarget::Node(tree) });
+        sim.step(65);
+    }
+
+    assert_eq!(completed(&sim), vec!["chop"]);
+    assert_eq!(sim.inventory_count(ResourceType::STONE), 3);
+    assert_eq!(sim.equipment().tool(ToolKind::Axe), Some(&Tool::new(ToolKind::Axe, ToolTier::Iron)));
+}
+
+#[test]
+fn reach_and_craft_objectives_count_toward_the_same_quest() {
+    let mut sim = SimulationHarness::new();
+    sim.app.insert_resource(book(
+        r#"(quests: [(
+            id: "build",
+            title: "Build",
+            objectives: [Reach(position: (0.0, 0.0, 0.0), radius: 1.0), Craft(recipe: "planks", count: 1)],
+        )])"#,
+    ));
+    sim.give(ResourceType::WOOD, 2);
+    sim.tick();
+    assert_eq!(
+        sim.app.world.resource::<QuestLog>().state("build"),
+        Some(&QuestState::Active { progress: vec![1, 0] })
+    );
+
+    sim.press(KeyCode::Digit1);
+    sim.step(3 * 64);
+
+    assert_eq!(completed(&sim), vec!["build"]);
+}
+
+#[test]
+fn save_keeps_quest_progress() {
+    let mut sim = SimulationHarness::new();
+    sim.spawn_npc("guide", Vec3::new(0.0, 0.5, -2.0));
+    sim.press(KeyCode::KeyF);
+    sim.step(2);
+    let save = capture_save(&mut sim.app.world).unwrap();
+
+    sim.app.insert_resource(QuestLog::default());
+    apply_save(&mut sim.app.world, &save).unwrap();
+
+    let log = sim.app.world.resource::<QuestLog>();
+    assert!(log.is_completed("welcome"));
+    assert_eq!(log.state("firewood"), Some(&QuestState::Active { progress: vec![0] }));
+}
@@ -0,0 +1,132 @@
+use bevy::prelude::*;
+use bevy_game_demo::components::types::ResourceType;
+use bevy_game_demo::systems::camera::{CameraMode, MainCamera};
+use bevy_game_demo::systems::navigation::HoveredGround;
+use bevy_game_demo::systems::replay::{replay, start_recording, stop_recording, InputRecording};
+use bevy_game_demo::systems::resources::HoveredNode;
+use bevy_game_demo::testing::SimulationHarness;
+
+fn set_camera_mode(sim: &mut SimulationHarness, mode: CameraMode) {
+    sim.app.world.query::<&mut MainCamera>().single_mut(&mut sim.app.world).mode = mode;
+}
+
+fn hit(sim: &mut SimulationHarness) {
+    sim.press(KeyCode::KeyE);
+    sim.tick();
+    sim.release(KeyCode::KeyE);
+}
+
+/// Plays a short session: walks up to a tree, chops it down, crafts planks,
+/// turns the camera and wanders off, switching camera modes on the way.
+/// Recording starts partway through the walk.
+fn recorded_session() -> InputRecording {
+    let mut sim = SimulationHarness::new();
+    sim.spawn_resource(ResourceType::WOOD, Vec3::new(0.0, 0.0, -6.0));
+    sim.spawn_resource(ResourceType::STONE, Vec3::new(4.0, 0.0, -9.0));
+    sim.set_movement(Vec2::Y);
+    sim.step(20);
+
+    start_recording(&mut sim.app.world).unwrap();
+    sim.step(40);
+    sim.set_movement(Vec2::ZERO);
+    sim.step(10);
+    hit(&mut sim);
+    sim.step(64);
+    hit(&mut sim);
+    sim.press(KeyCode::Digit1);
+    sim.tick();
+    sim.release(KeyCode::Digit1);
+    sim.app.world.query::<&mut MainCamera>().single_mut(&mut sim.app.world).angle += 0.8;
+    sim.set_movement(Vec2::new(0.6, 0.3));
+    sim.step(150);
+    set_camera_mode(&mut sim, CameraMode::TopDown);
+    sim.step(40);
+    set_camera_mode(&mut sim, CameraMode::FirstPerson);
+    sim.set_movement(Vec2::new(-0.5, 1.0));
+    sim.step(40);
+    let recording = stop_recording(&mut sim.app.world).unwrap();
+
+    assert_eq!(sim.inventory_count(ResourceType::new("planks")), 1);
+    recording
+}
+
+#[test]
+fn replay_reproduces_recorded_session() {
+    let recording = recorded_session();
+    let report = replay(&recording).unwrap();
+    assert!(report.matches(), "{report:?}");
+}
+
+#[test]
+fn recording_round_trips_through_ron() {
+    let recording = recorded_session();
+    let parsed = InputRecording::from_ron(&recording.to_ron().unwrap()).unwrap();
+    assert_eq!(parsed, recording);
+    assert!(replay(&parsed).unwrap().matches());
+}
+
+#[test]
+fn replay_reproduces_click_gathering() {
+    let mut sim = SimulationHarness::new();
+    let tree = sim.spawn_resource(ResourceType::WOOD, Vec3::new(0.0, 0.0, -1.5));
+    sim.tick();
+    start_recording(&mut sim.app.world).unwrap();
+    for _ in 0..2 {
+        sim.step(64);
+        sim.app.insert_resource(HoveredNode(Some(tree)));
+        sim.app.world.resource_mut::<ButtonInput<MouseButton>>().press(MouseButton::Left);
+        sim.tick();
+        sim.app.world.resource_mut::<ButtonInput<MouseButton>>().release(MouseButton::Left);
+        sim.app.insert_resource(HoveredNode(None));
+    }
+    sim.step(8);
+    let recording = stop_recording(&mut sim.app.world).unwrap();
+    assert_eq!(sim.inventory_count(ResourceType::WOOD), 2);
+
+    let picks: Vec<Vec3> = recording.frames.iter().filter_map(|frame| frame.pick).collect();
+    assert_eq!(picks, vec![Vec3::new(0.0, 0.0, -1.5); 2]);
+    let parsed = InputRecording::from_ron(&recording.to_ron().unwrap()).unwrap();
+    assert_eq!(parsed, recording);
+    let report = replay(&parsed).unwrap();
+    assert!(report.matches(), "{report:?}");
+}
+
+#[test]
+fn replay_reproduces_click_to_move() {
+    let mut sim = SimulationHarness::new();
+    for z in -2..=2 {
+        sim.spawn_resource(ResourceType::STONE, Vec3::new(3.0, 0.0, z as f32));
+    }
+    sim.step(32);
+    let walk_to = |sim: &mut SimulationHarness, spot: Vec3| {
+        sim.app.insert_resource(HoveredGround(Some(spot)));
+        sim.app.world.resource_mut::<ButtonInput<MouseButton>>().press(MouseButton::Middle);
+        sim.tick();
+        sim.app.world.resource_mut::<ButtonInput<MouseButton>>().release(MouseButton::Middle);
+        sim.app.insert_resource(HoveredGround(None));
+    };
+    walk_to(&mut sim, Vec3::new(6.0, 0.0, 0.0));
+    // Recording starts partway around the wall
+    sim.step(40);
+    start_recording(&mut sim.app.world).unwrap();
+    sim.step(3 * 64);
+    walk_to(&mut sim, Vec3::new(6.0, 0.0, -6.0));
+    sim.step(3 * 64);
+    let recording = stop_recording(&mut sim.app.world).unwrap();
+    assert!(sim.player_transform().translation.xz().distance(Vec2::new(6.0, -6.0)) < 0.3);
+
+    let walks: Vec<Vec3> = recording.frames.iter().filter_map(|frame| frame.walk_to).collect();
+    assert_eq!(walks, vec![Vec3::new(6.0, 0.0, -6.0)]);
+    let parsed = InputRecording::from_ron(&recording.to_ron().unwrap()).unwrap();
+    let report = replay(&parsed).unwrap();
+    assert!(report.matches(), "{report:?}");
+}
+
+#[test]
+fn tampered_recording_does_not_match() {
+    let mut recording = recorded_session();
+    for frame in recording.frames.iter_mut().rev().take(50) {
+        frame.movement = Vec2::new(-1.0, 0.0);
+    }
+    assert!(!replay(&recording).unwrap().matches());
+}
@@ -1,16 +1,71 @@
-use bevy_game_demo::components::types::ResourceType;
+use bevy_game_demo::components::config::RonAsset;
+use bevy_game_demo::components::definitions::{ResourceDefinitions, ResourceRegistry};
+use bevy_game_demo::components::types::{ResourceType, YieldRule};
+use bevy_game_demo::testing::read_config;
+
+fn default_registry() -> ResourceRegistry {
+    read_config()
+}
 
 #[test]
 fn wood_get_name() {
-    assert_eq!(ResourceType::Wood.get_name(), "Wood");
+    assert_eq!(default_registry().display_name(ResourceType::WOOD), "Wood");
 }
 
 #[test]
 fn stone_get_name() {
-    assert_eq!(ResourceType::Stone.get_name(), "Stone");
+    assert_eq!(default_registry().display_name(ResourceType::STONE), "Stone");
 }
 
 #[test]
 fn ore_get_name() {
-    assert_eq!(ResourceType::Ore.get_name(), "Ore");
+    assert_eq!(default_registry().display_name(ResourceType::ORE), "Ore");
+}
+
+#[test]
+fn resource_type_from_id_matches_constant() {
+    assert_eq!(ResourceType::new("wood"), ResourceType::WOOD);
+    assert_eq!(ResourceType::new("fiber").id(), "fiber");
+}
+
+#[test]
+fn new_resource_type_is_defined_by_data_alone() {
+    let registry = ResourceRegistry::new(
+        ResourceDefinitions::from_ron(
+            r#"(resources: [(
+                id: "berries",
+                display_name: "Berries",
+                model: "models/bush.glb#Scene0",
+                collider: Ball(radius: 0.4),
+                health: 20,
+                yield_rule: PerHit(2),
+                respawn_seconds: 10.0,
+                spawn_weight: 1.0,
+            )])"#,
+        )
+        .unwrap(),
+    );
+
+    let berries = registry.get(ResourceType::new("berries")).unwrap();
+    assert_eq!(berries.display_name, "Berries");
+    assert_eq!(berries.yield_rule, YieldRule::PerHit(2));
+    assert_eq!(registry.display_name(ResourceType::new("unknown")), "unknown");
+}
+
+#[test]
+fn spawn_counts_follow_weights() {
+    let counts: Vec<(ResourceType, u32)> = default_registry()
+        .spawn_counts(30)
+        .into_iter()
+        .map(|(definition, count)| (definition.id, count))
+        .collect();
+    assert_eq!(
+        counts,
+        vec![
+            (ResourceType::WOOD, 17),
+            (ResourceType::STONE, 9),
+            (ResourceType::ORE, 4),
+            (ResourceType::new("log"), 0),
+        ]
+    );
 }
@@ -0,0 +1,162 @@
+use bevy::prelude::*;
+use bevy_game_demo::components::types::{Gatherable, ResourceNode, ResourceType, WorldSeed};
+use bevy_game_demo::systems::crafting::{CraftRequested, CraftingQueue};
+use bevy_game_demo::systems::save::{apply_save, capture_save, SaveError, SaveGame, SAVE_VERSION};
+use bevy_game_demo::testing::SimulationHarness;
+use bevy_rapier3d::prelude::ColliderDisabled;
+
+/// Chops down a tree 6 units ahead, with an untouched rock off to the side.
+fn played_game() -> SimulationHarness {
+    let mut sim = SimulationHarness::new();
+    let tree = sim.spawn_resource(ResourceType::WOOD, Vec3::new(0.0, 0.0, -6.0));
+    sim.spawn_resource(ResourceType::STONE, Vec3::new(8.0, 0.0, 3.0));
+    sim.set_movement(Vec2::Y);
+    sim.step(60);
+    sim.set_movement(Vec2::ZERO);
+    sim.step(10);
+    for _ in 0..2 {
+        sim.press(KeyCode::KeyE);
+        sim.tick();
+        sim.release(KeyCode::KeyE);
+        sim.step(64);
+    }
+    assert!(sim.app.world.get::<Gatherable>(tree).unwrap().is_depleted());
+    sim
+}
+
+fn sorted_nodes(mut save: SaveGame) -> SaveGame {
+    save.nodes.sort_by(|a, b| a.position.x.total_cmp(&b.position.x));
+    save
+}
+
+#[test]
+fn save_round_trips_through_a_fresh_world() {
+    let mut sim = played_game();
+    let saved = sorted_nodes(capture_save(&mut sim.app.world).unwrap());
+    let text = saved.to_ron().unwrap();
+
+    let mut loaded_sim = SimulationHarness::new();
+    loaded_sim.spawn_resource(ResourceType::ORE, Vec3::new(-5.0, 0.0, -5.0));
+    apply_save(&mut loaded_sim.app.world, &SaveGame::from_ron(&text).unwrap()).unwrap();
+
+    assert_eq!(sorted_nodes(capture_save(&mut loaded_sim.app.world).unwrap()), saved);
+    assert_eq!(loaded_sim.app.world.resource::<WorldSeed>().0, saved.world_seed);
+    assert_eq!(loaded_sim.inventory_count(ResourceType::WOOD), 2);
+    assert_eq!(loaded_sim.player_transform().translation, sim.player_transform().translation);
+
+    // The depleted tree comes back hidden and out of physics
+    let world = &mut loaded_sim.app.world;
+    let mut nodes = world.query_filtered::<(&Gatherable, &Visibility, Option<&ColliderDisabled>), With<ResourceNode>>();
+    let (tree, visibility, disabled) = nodes
+        .iter(world)
+        .find(|(gatherable, ..)| gatherable.resource_type == ResourceType::WOOD)
+        .unwrap();
+    assert!(tree.is_depleted());
+    assert_eq!(visibility, &Visibility::Hidden);
+    assert!(disabled.is_some());
+}
+
+#[test]
+fn loaded_game_keeps_playing() {
+    let mut sim = played_game();
+    let saved = capture_save(&mut sim.app.world).unwrap();
+
+    let mut loaded_sim = SimulationHarness::new();
+    apply_save(&mut loaded_sim.app.world, &saved).unwrap();
+    // The tree's 30 second respawn timer carries on from where it was saved
+    loaded_sim.step(64 * 30);
+
+    let world = &mut loaded_sim.app.world;
+    let mut nodes = world.query::<&Gatherable>();
+    assert!(nodes.iter(world).all(|gatherable| !gatherable.is_depleted()));
+}
+
+#[test]
+fn save_file_round_trips_on_disk() {
+    let mut sim = played_game();
+    let saved = capture_save(&mut sim.app.world).unwrap();
+    let path = std::env::temp_dir()
+        .join(format!("bevy_game_demo_save_test_{}", std::process::id()))
+        .join("quicksave.ron");
+
+    saved.write(&path).unwrap();
+    let read = SaveGame::read(&path).unwrap();
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+    assert_eq!(read, saved);
+}
+
+#[test]
+fn save_from_another_version_is_rejected() {
+    let mut sim = SimulationHarness::new();
+    let mut save = capture_save(&mut sim.app.world).unwrap();
+    save.version = SAVE_VERSION + 1;
+
+    let result = SaveGame::from_ron(&save.to_ron().unwrap());
+    assert!(matches!(result, Err(SaveError::UnsupportedVersion { .. })));
+}
+
+#[test]
+fn older_saves_load_with_defaults_for_newer_fields() {
+    // Version 1 kept a count per item rather than slots
+    let v1 = "(version: 1, world_seed: 7, inventory: {\"wood\": 25, \"stone\": 3}, \
+        player: (translation: (1.0, 0.5, 2.0), rotation: (0.0, 0.0, 0.0, 1.0)), nodes: [])";
+    let save = SaveGame::from_ron(v1).unwrap();
+    assert_eq!(save.version, SAVE_VERSION);
+    assert!(save.workers.is_empty());
+    let weather = save.weather;
+
+    let mut sim = SimulationHarness::new();
+    apply_save(&mut sim.app.world, &save).unwrap();
+    assert_eq!(sim.inventory_count(ResourceType::WOOD), 25);
+    assert_eq!(sim.inventory_count(ResourceType::STONE), 3);
+    assert_eq!(sim.player_transform().translation, Vec3::new(1.0, 0.5, 2.0));
+
+    // Version 2 had slots but none of the later fields
+    let v2 = "(version: 2, world_seed: 7, inventory: [Some((item: \"wood\", amount: 4)), None], \
+        player: (translation: (0.0, 0.5, 0.0), rotation: (0.0, 0.0, 0.0, 1.0)), nodes: [])";
+    let save = SaveGame::from_ron(v2).unwrap();
+    // Both start the weather from the world seed, as a new game would
+    assert_eq!(save.weather, weather);
+    assert_eq!(save.inventory.len(), 2);
+    assert_eq!(save.quests, Default::default());
+}
+
+#[test]
+fn saving_or_loading_without_a_player_fails() {
+    let mut sim = SimulationHarness::new();
+    let save = capture_save(&mut sim.app.world).unwrap();
+    let player = sim.player();
+    sim.app.world.despawn(player);
+
+    assert!(matches!(capture_save(&mut sim.app.world), Err(SaveError::NoPlayer)));
+    assert!(matches!(apply_save(&mut sim.app.world, &save), Err(SaveError::NoPlayer)));
+}
+
+#[test]
+fn loading_restores_the_crafting_queue() {
+    let planks = ResourceType::new("planks");
+    let mut sim = SimulationHarness::new();
+    sim.give(ResourceType::WOOD, 4);
+    let before_craft = capture_save(&mut sim.app.world).unwrap();
+
+    // Loading a save from before a craft gives back its inputs and drops the craft
+    sim.app.world.send_event(CraftRequested { recipe: "planks".to_string() });
+    sim.tick();
+    assert_eq!(sim.inventory_count(ResourceType::WOOD), 2);
+    apply_save(&mut sim.app.world, &before_craft).unwrap();
+    sim.step(3 * 64);
+    assert_eq!(sim.inventory_count(ResourceType::WOOD), 4);
+    assert_eq!(sim.inventory_count(planks), 0);
+
+    // A craft queued before saving finishes after loading
+    sim.app.world.send_event(CraftRequested { recipe: "planks".to_string() });
+    sim.step(64);
+    let mid_craft = SaveGame::from_ron(&capture_save(&mut sim.app.world).unwrap().to_ron().unwrap()).unwrap();
+    let mut loaded_sim = SimulationHarness::new();
+    apply_save(&mut loaded_sim.app.world, &mid_craft).unwrap();
+    assert_eq!(loaded_sim.app.world.resource::<CraftingQueue>().jobs.len(), 1);
+    loaded_sim.step(70);
+    assert_eq!(loaded_sim.inventory_count(ResourceType::WOOD), 2);
+    assert_eq!(loaded_sim.inventory_count(planks), 1);
+}
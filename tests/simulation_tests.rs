@@ -0,0 +1,195 @@
+use bevy::prelude::*;
+use bevy_game_demo::components::definitions::ResourceRegistry;
+use bevy_game_demo::components::types::{Gatherable, ResourceType};
+use bevy_rapier3d::prelude::ColliderDisabled;
+use bevy_game_demo::testing::SimulationHarness;
+
+#[test]
+fn walking_forward_moves_player_away_from_camera() {
+    let mut sim = SimulationHarness::new();
+    sim.set_movement(Vec2::Y);
+    sim.step(60);
+
+    // The camera starts behind the player on +Z, so forward is -Z
+    let position = sim.player_transform().translation;
+    assert!(position.z < -3.0, "player did not walk forward: {position:?}");
+    assert!(position.x.abs() < 0.1);
+}
+
+/// Spawns a node 6 units ahead, walks up to it and waits out the initial gathering cooldown.
+fn walk_to_node_ahead(sim: &mut SimulationHarness, resource_type: ResourceType) -> Entity {
+    let node = sim.spawn_resource(resource_type, Vec3::new(0.0, 0.0, -6.0));
+    sim.set_movement(Vec2::Y);
+    sim.step(60);
+    sim.set_movement(Vec2::ZERO);
+    sim.step(10);
+    node
+}
+
+/// Presses E for one tick.
+fn hit(sim: &mut SimulationHarness) {
+    sim.press(KeyCode::KeyE);
+    sim.tick();
+    sim.release(KeyCode::KeyE);
+}
+
+/// Waits out the one second gathering cooldown.
+fn wait_cooldown(sim: &mut SimulationHarness) {
+    sim.step(64);
+}
+
+fn health(sim: &SimulationHarness, node: Entity) -> u32 {
+    sim.app.world.get::<Gatherable>(node).unwrap().health
+}
+
+fn is_depleted(sim: &SimulationHarness, node: Entity) -> bool {
+    let world = &sim.app.world;
+    world.get::<Gatherable>(node).unwrap().is_depleted()
+        && world.get::<Visibility>(node) == Some(&Visibility::Hidden)
+        && world.get::<ColliderDisabled>(node).is_some()
+}
+
+/// Walks up to a tree 6 units ahead and chops it down.
+fn gather_tree_ahead(sim: &mut SimulationHarness) -> Entity {
+    let tree = walk_to_node_ahead(sim, ResourceType::WOOD);
+    hit(sim);
+    wait_cooldown(sim);
+    hit(sim);
+    tree
+}
+
+#[test]
+fn walking_to_tree_and_pressing_e_gathers_wood() {
+    let mut sim = SimulationHarness::new();
+    let tree = walk_to_node_ahead(&mut sim, ResourceType::WOOD);
+    hit(&mut sim);
+
+    assert_eq!(sim.inventory_count(ResourceType::WOOD), 1);
+    assert_eq!(health(&sim, tree), 50);
+    assert!(!is_depleted(&sim, tree));
+}
+
+#[test]
+fn tree_yields_wood_per_hit_until_depleted() {
+    let mut sim = SimulationHarness::new();
+    let tree = gather_tree_ahead(&mut sim);
+
+    assert_eq!(sim.inventory_count(ResourceType::WOOD), 2);
+    assert_eq!(health(&sim, tree), 0);
+    assert!(is_depleted(&sim, tree));
+}
+
+#[test]
+fn rock_yields_stone_only_when_depleted() {
+    let mut sim = SimulationHarness::new();
+    let rock = walk_to_node_ahead(&mut sim, ResourceType::STONE);
+
+    hit(&mut sim);
+    wait_cooldown(&mut sim);
+    hit(&mut sim);
+    assert_eq!(sim.inventory_count(ResourceType::STONE), 0);
+    assert_eq!(health(&sim, rock), 50);
+
+    wait_cooldown(&mut sim);
+    hit(&mut sim);
+    assert_eq!(sim.inventory_count(ResourceType::STONE), 3);
+    assert!(is_depleted(&sim, rock));
+}
+
+#[test]
+fn hits_during_cooldown_do_no_damage() {
+    let mut sim = SimulationHarness::new();
+    let tree = walk_to_node_ahead(&mut sim, ResourceType::WOOD);
+    hit(&mut sim);
+    sim.step(10);
+    hit(&mut sim);
+
+    assert_eq!(sim.inventory_count(ResourceType::WOOD), 1);
+    assert_eq!(health(&sim, tree), 50);
+}
+
+fn set_tree_respawn(sim: &mut SimulationHarness, seconds: f32) {
+    let mut registry = sim.app.world.resource_mut::<ResourceRegistry>();
+    registry.get_mut(ResourceType::WOOD).unwrap().respawn_seconds = seconds;
+}
+
+#[test]
+fn gathered_node_respawns_after_its_timer() {
+    let mut sim = SimulationHarness::new();
+    set_tree_respawn(&mut sim, 2.0);
+    let tree = gather_tree_ahead(&mut sim);
+    assert!(is_depleted(&sim, tree));
+
+    // A depleted node cannot be gathered again
+    sim.step(70);
+    hit(&mut sim);
+    assert_eq!(sim.inventory_count(ResourceType::WOOD), 2);
+    assert!(is_depleted(&sim, tree));
+
+    // Back away so the spot is free, then wait out the rest of the timer
+    sim.set_movement(-Vec2::Y);
+    sim.step(40);
+    sim.set_movement(Vec2::ZERO);
+    sim.step(30);
+
+    let world = &sim.app.world;
+    assert!(!world.get::<Gatherable>(tree).unwrap().is_depleted());
+    assert_eq!(world.get::<Visibility>(tree), Some(&Visibility::Inherited));
+    assert!(world.get::<ColliderDisabled>(tree).is_none());
+    assert_eq!(world.get::<Transform>(tree).unwrap().translation, Vec3::new(0.0, 0.0, -6.0));
+    assert_eq!(health(&sim, tree), 100);
+}
+
+#[test]
+fn blocked_node_respawns_at_nearby_spot() {
+    let mut sim = SimulationHarness::new();
+    set_tree_respawn(&mut sim, 1.0);
+    let tree = gather_tree_ahead(&mut sim);
+
+    // Walk onto the depleted tree's spot and stand there
+    sim.set_movement(Vec2::Y);
+    sim.step(18);
+    sim.set_movement(Vec2::ZERO);
+    sim.step(60);
+
+    let world = &sim.app.world;
+    assert!(!world.get::<Gatherable>(tree).unwrap().is_depleted());
+    let spot = world.get::<Transform>(tree).unwrap().translation;
+    assert_ne!(spot, Vec3::new(0.0, 0.0, -6.0));
+    assert!(spot.distance(Vec3::new(0.0, 0.0, -6.0)) <= 3.0);
+}
+
+#[test]
+fn pressing_e_out_of_range_gathers_nothing() {
+    let mut sim = SimulationHarness::new();
+    let rock = sim.spawn_resource(ResourceType::STONE, Vec3::new(0.0, 0.0, -10.0));
+
+    sim.step(70);
+    sim.press(KeyCode::KeyE);
+    sim.tick();
+
+    assert_eq!(sim.inventory_count(ResourceType::STONE), 0);
+    assert!(sim.exists(rock));
+}
+
+#[test]
+fn player_turns_to_face_movement_direction() {
+    let mut sim = SimulationHarness::new();
+    sim.set_movement(Vec2::Y);
+    sim.step(30);
+
+    let forward = sim.player_transform().forward();
+    assert!(forward.z < -0.9, "player faces {forward:?} while walking toward -Z");
+}
+
+#[test]
+fn simulation_is_deterministic() {
+    let run = || {
+        let mut sim = SimulationHarness::new();
+        sim.spawn_resource(ResourceType::WOOD, Vec3::new(0.5, 0.0, -4.0));
+        sim.set_movement(Vec2::new(0.3, 1.0).normalize());
+        sim.step(90);
+        sim.player_transform()
+    };
+    assert_eq!(run(), run());
+}
@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+use bevy_game_demo::components::config::{FromConfig, RonAsset};
+use bevy_game_demo::components::equipment::{Tool, ToolKind, ToolTier};
+use bevy_game_demo::components::skills::{Skill, SkillCurve, SkillCurveConfig, Skills};
+use bevy_game_demo::components::types::ResourceType;
+use bevy_game_demo::systems::resources::{GatherError, GatherRejected, GatherRequest, GatherTarget, ResourceGathered};
+use bevy_game_demo::systems::skills::SkillLevelUp;
+use bevy_game_demo::testing::SimulationHarness;
+
+/// A harness past the initial gathering cooldown with a node of `resource` in front of the player.
+fn harness_facing(resource: ResourceType) -> (SimulationHarness, Entity) {
+    let mut sim = SimulationHarness::new();
+    sim.step(64);
+    let node = sim.spawn_resource(resource, Vec3::new(0.0, 0.0, -1.5));
+    sim.tick();
+    (sim, node)
+}
+
+/// Sends a gather request for `node` for one tick.
+fn hit(sim: &mut SimulationHarness, node: Entity) {
+    sim.app.world.send_event(GatherRequest { target: GatherTarget::Node(node) });
+    sim.tick();
+}
+
+/// Gives the player `xp` XP in `skill` without gathering.
+fn give_xp(sim: &mut SimulationHarness, skill: Skill, xp: u32) {
+    let curve = sim.app.world.resource::<SkillCurve>().clone();
+    let player = sim.player();
+    sim.app.world.get_mut::<Skills>(player).unwrap().add_xp(skill, xp, &curve);
+}
+
+fn skills(sim: &SimulationHarness) -> &Skills {
+    sim.app.world.get::<Skills>(sim.player()).unwrap()
+}
+
+#[test]
+fn curve_turns_xp_into_levels_and_bonuses() {
+    let curve = SkillCurve::from_config(&SkillCurveConfig::from_ron("(growth: Some(2.0), max_level: Some(4))").unwrap());
+
+    assert_eq!(curve.base_xp, SkillCurve::default().base_xp);
+    assert_eq!([1, 2, 3, 4].map(|level| curve.xp_to_next(level)), [Some(50), Some(100), Some(200), None]);
+    assert_eq!(curve.xp_for_level(3), 150);
+    assert_eq!([0, 49, 50, 149, 150, 350, 10_000].map(|xp| curve.level_for(xp)), [1, 1, 2, 2, 3, 4, 4]);
+    assert_eq!(curve.cooldown_factor(1), 1.0);
+    assert_eq!(SkillCurve { levels_per_yield_bonus: 2, ..curve }.yield_bonus(4), 1);
+}
+
+#[test]
+fn gathering_earns_xp_and_announces_level_ups() {
+    let (mut sim, tree) = harness_facing(ResourceType::WOOD);
+    give_xp(&mut sim, Skill::Woodcutting, 45);
+    hit(&mut sim, tree);
+
+    assert_eq!(skills(&sim).xp(Skill::Woodcutting), 55);
+    assert_eq!(skills(&sim).xp(Skill::Mining), 0);
+    assert_eq!(sim.events::<SkillLevelUp>(), vec![SkillLevelUp { skill: Skill::Woodcutting, level: 2 }]);
+}
+
+#[test]
+fn ore_needs_a_mining_level() {
+    let (mut sim, ore) = harness_facing(ResourceType::ORE);
+    sim.equip(Tool::new(ToolKind::Pickaxe, ToolTier::Iron));
+    hit(&mut sim, ore);
+    assert_eq!(
+        sim.events::<GatherRejected>(),
+        vec![GatherRejected(GatherError::SkillTooLow { skill: Skill::Mining, level: 3 })]
+    );
+
+    let needed = sim.app.world.resource::<SkillCurve>().xp_for_level(3);
+    give_xp(&mut sim, Skill::Mining, needed);
+    hit(&mut sim, ore);
+
+    assert_eq!(sim.events::<ResourceGathered>().len(), 1);
+    assert_eq!(sim.events::<GatherRejected>().len(), 1);
+}
+
+#[test]
+fn higher_levels_gather_faster_and_yield_more() {
+    let (mut sim, tree) = harness_facing(ResourceType::WOOD);
+    let needed = sim.app.world.resource::<SkillCurve>().xp_for_level(6);
+    give_xp(&mut sim, Skill::Woodcutting, needed);
+    hit(&mut sim, tree);
+    // Level 6 takes 15% off the one second bare-handed cooldown
+    sim.step(55);
+    hit(&mut sim, tree);
+
+    assert!(sim.events::<GatherRejected>().is_empty());
+    let gained: Vec<u32> = sim.events::<ResourceGathered>().iter().map(|hit| hit.gained).collect();
+    assert_eq!(gained, vec![2, 2]);
+}
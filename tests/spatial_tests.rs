@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+use bevy_game_demo::components::types::{Gatherable, Position, ResourceType};
+use bevy_game_demo::systems::resources::{GatherRequest, GatherTarget};
+use bevy_game_demo::systems::spatial::{NodeIndex, SpatialGrid};
+use bevy_game_demo::testing::SimulationHarness;
+
+/// Entities with distinct ids for filling a grid by hand.
+fn entities(count: u32) -> Vec<Entity> {
+    (0..count).map(Entity::from_raw).collect()
+}
+
+#[test]
+fn nearest_in_radius_finds_the_closest_across_cells() {
+    let mut grid = SpatialGrid::new(1.0);
+    let [a, b, c] = entities(3)[..] else { unreachable!() };
+    grid.insert(a, Vec3::new(2.5, 0.0, 0.0));
+    grid.insert(b, Vec3::new(-1.5, 0.0, 0.2));
+    grid.insert(c, Vec3::new(0.0, 0.0, 9.0));
+
+    assert_eq!(grid.nearest_in_radius(Vec3::ZERO, 3.0, |_| true), Some((b, Vec3::new(-1.5, 0.0, 0.2))));
+    assert_eq!(grid.nearest_in_radius(Vec3::ZERO, 3.0, |entity| entity != b).map(|(e, _)| e), Some(a));
+    assert_eq!(grid.nearest_in_radius(Vec3::ZERO, 1.0, |_| true), None);
+    let mut found: Vec<Entity> = grid.within_radius(Vec3::ZERO, 10.0).map(|(entity, _)| entity).collect();
+    found.sort();
+    assert_eq!(found, vec![a, b, c]);
+}
+
+#[test]
+fn nearest_in_cone_ignores_nodes_outside_the_cone() {
+    let mut grid = SpatialGrid::default();
+    let [ahead, beside, far_ahead] = entities(3)[..] else { unreachable!() };
+    grid.insert(ahead, Vec3::new(0.3, 0.0, -1.8));
+    grid.insert(beside, Vec3::new(1.0, 0.0, 0.0));
+    grid.insert(far_ahead, Vec3::new(0.0, 0.0, -5.0));
+
+    let nearest = |grid: &SpatialGrid| grid.nearest_in_cone(Vec3::ZERO, Vec3::NEG_Z, 0.7, 2.0, |_| true).map(|(e, _)| e);
+    assert_eq!(nearest(&grid), Some(ahead));
+    grid.remove(ahead);
+    assert_eq!(nearest(&grid), None);
+}
+
+#[test]
+fn moving_and_removing_entities_updates_their_cells() {
+    let mut grid = SpatialGrid::new(2.0);
+    let [node] = entities(1)[..] else { unreachable!() };
+    grid.insert(node, Vec3::new(1.0, 0.0, 1.0));
+    grid.insert(node, Vec3::new(30.0, 0.0, -30.0));
+
+    assert_eq!(grid.len(), 1);
+    assert_eq!(grid.position(node), Some(Vec3::new(30.0, 0.0, -30.0)));
+    assert_eq!(grid.nearest_in_radius(Vec3::ZERO, 5.0, |_| true), None);
+    assert!(grid.nearest_in_radius(Vec3::new(30.0, 0.0, -30.0), 1.0, |_| true).is_some());
+    assert!(grid.remove(node));
+    assert!(!grid.remove(node));
+    assert!(grid.is_empty());
+}
+
+#[test]
+fn node_index_follows_spawns_depletion_respawns_and_despawns() {
+    let mut sim = SimulationHarness::new();
+    let tree = sim.spawn_resource(ResourceType::WOOD, Vec3::new(0.0, 0.0, -1.5));
+    let rock = sim.spawn_resource(ResourceType::STONE, Vec3::new(8.0, 0.0, 8.0));
+    sim.tick();
+    let indexed = |sim: &SimulationHarness, node| sim.app.world.resource::<NodeIndex>().position(node);
+    assert_eq!(indexed(&sim, tree), Some(Vec3::new(0.0, 0.0, -1.5)));
+    assert_eq!(indexed(&sim, rock), Some(Vec3::new(8.0, 0.0, 8.0)));
+
+    // Chop the tree down
+    for _ in 0..2 {
+        sim.step(64);
+        sim.app.world.send_event(GatherRequest { target: GatherTarget::Facing });
+        sim.tick();
+    }
+    sim.tick();
+    assert!(sim.app.world.get::<Gatherable>(tree).unwrap().is_depleted());
+    assert_eq!(indexed(&sim, tree), None);
+
+    // It comes back where it stood
+    while sim.app.world.get::<Gatherable>(tree).unwrap().is_depleted() {
+        sim.tick();
+    }
+    sim.tick();
+    let respawned = sim.app.world.get::<Position>(tree).unwrap().value;
+    assert_eq!(indexed(&sim, tree), Some(respawned));
+
+    sim.app.world.despawn(rock);
+    sim.tick();
+    assert_eq!(indexed(&sim, rock), None);
+    assert_eq!(sim.app.world.resource::<NodeIndex>().len(), 1);
+}
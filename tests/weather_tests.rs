@@ -0,0 +1,161 @@
+use bevy::prelude::*;
+use bevy_game_demo::components::config::RonAsset;
+use bevy_game_demo::components::types::{Gatherable, Position, ResourceNode, ResourceType};
+use bevy_game_demo::components::weather::{Weather, WeatherDefinitions, WeatherKind, WeatherRegistry, START_CLEAR_SECONDS};
+use bevy_game_demo::systems::resources::{GatherRequest, GatherTarget, ResourceGathered};
+use bevy_game_demo::systems::placement::PlacementRules;
+use bevy_game_demo::systems::save::{apply_save, capture_save};
+use bevy_game_demo::systems::weather::{NodeKnockedDown, WeatherChanged};
+use bevy_game_demo::testing::{read_config, SimulationHarness};
+
+fn default_registry() -> WeatherRegistry {
+    read_config()
+}
+
+/// Kinds of weather a clock starting from `seed` goes through in an hour.
+fn hour_of_weather(seed: u64, registry: &WeatherRegistry) -> Vec<WeatherKind> {
+    let mut weather = Weather::new(seed);
+    (0..3600).filter_map(|_| weather.advance(1.0, registry)).collect()
+}
+
+/// Walks straight ahead for one second and returns how far the player got.
+fn walk_one_second(sim: &mut SimulationHarness) -> f32 {
+    let start = sim.player_transform().translation;
+    sim.set_movement(Vec2::Y);
+    sim.step(64);
+    sim.set_movement(Vec2::ZERO);
+    sim.player_transform().translation.distance(start)
+}
+
+#[test]
+fn same_seed_gives_the_same_weather() {
+    let registry = default_registry();
+    let weather = hour_of_weather(7, &registry);
+
+    assert!(weather.len() > 3);
+    assert_eq!(hour_of_weather(7, &registry), weather);
+    assert_ne!(hour_of_weather(8, &registry), weather);
+}
+
+#[test]
+fn weather_moves_on_along_its_transitions() {
+    let registry = WeatherRegistry::new(
+        WeatherDefinitions::from_ron(
+            "(weather: [
+                (kind: Clear, min_seconds: 10.0, max_seconds: 10.0, next: [(Fog, 1.0), (Rain, 0.0)]),
+                (kind: Fog, min_seconds: 5.0, max_seconds: 8.0),
+            ])",
+        )
+        .unwrap(),
+    );
+    let mut weather = Weather::new(1);
+
+    assert_eq!(weather.advance(START_CLEAR_SECONDS - 1.0, &registry), None);
+    assert_eq!(weather.advance(1.0, &registry), Some(WeatherKind::Fog));
+    assert!((5.0..=8.0).contains(&weather.remaining_seconds()));
+    // Weather with nothing to follow it repeats
+    assert_eq!(weather.advance(8.0, &registry), None);
+    assert_eq!(weather.kind(), WeatherKind::Fog);
+}
+
+#[test]
+fn rain_slows_the_player_down() {
+    let mut sim = SimulationHarness::new();
+    let clear = walk_one_second(&mut sim);
+    sim.set_weather(WeatherKind::Rain, 60.0);
+    let rain = walk_one_second(&mut sim);
+
+    assert!((rain / clear - 0.8).abs() < 0.05, "walked {} in rain and {} when clear", rain, clear);
+}
+
+#[test]
+fn rain_speeds_up_tree_regrowth() {
+    let mut sim = SimulationHarness::new();
+    let tree = sim.spawn_resource(ResourceType::WOOD, Vec3::new(5.0, 0.0, 5.0));
+    let rock = sim.spawn_resource(ResourceType::STONE, Vec3::new(-5.0, 0.0, 5.0));
+    sim.set_weather(WeatherKind::Rain, 60.0);
+    for node in [tree, rock] {
+        sim.app.world.get_mut::<Gatherable>(node).unwrap().respawn_timer = Some(Timer::from_seconds(30.0, TimerMode::Once));
+    }
+    // Sixteen seconds of rain count double for trees only
+    sim.step(16 * 64);
+
+    assert!(!sim.app.world.get::<Gatherable>(tree).unwrap().is_depleted());
+    assert!(sim.app.world.get::<Gatherable>(rock).unwrap().is_depleted());
+}
+
+#[test]
+fn storms_knock_trees_down_into_logs() {
+    let mut sim = SimulationHarness::new();
+    let tree = sim.spawn_resource(ResourceType::WOOD, Vec3::new(5.0, 0.0, 5.0));
+    let rock = sim.spawn_resource(ResourceType::STONE, Vec3::new(-5.0, 0.0, 5.0));
+    sim.set_weather(WeatherKind::Storm, 60.0);
+    // Knock something down every tick
+    let mut registry = sim.app.world.resource_mut::<WeatherRegistry>();
+    registry.get_mut(WeatherKind::Storm).unwrap().effects.knockdowns_per_minute = 60.0 * 64.0;
+    sim.tick();
+
+    let knocked_down = sim.events::<NodeKnockedDown>();
+    assert_eq!(knocked_down.len(), 1);
+    assert_eq!(knocked_down[0].node, tree);
+    assert!(sim.app.world.get::<Gatherable>(tree).unwrap().is_depleted());
+    let log = sim.app.world.get::<Gatherable>(knocked_down[0].fallen).unwrap();
+    assert_eq!(log.resource_type, ResourceType::new("log"));
+    assert!(!log.is_depleted());
+    let log_position = sim.app.world.get::<Position>(knocked_down[0].fallen).unwrap().value;
+    let rock_position = sim.app.world.get::<Position>(rock).unwrap().value;
+    assert!(log_position.distance(rock_position) >= PlacementRules::default().min_spacing);
+
+    // Only standing trees fall, so the storm has nothing left to knock down
+    sim.tick();
+    assert_eq!(sim.events::<NodeKnockedDown>().len(), 1);
+    assert!(sim.events::<WeatherChanged>().is_empty());
+}
+
+#[test]
+fn trees_with_no_room_to_fall_stay_standing() {
+    let mut sim = SimulationHarness::new();
+    let tree = sim.spawn_resource(ResourceType::WOOD, Vec3::new(10.0, 0.0, 10.0));
+    // Rocks all around leave no spot a log could fall into without crowding one
+    for offset in [Vec3::X, Vec3::NEG_X, Vec3::Z, Vec3::NEG_Z] {
+        sim.spawn_resource(ResourceType::STONE, Vec3::new(10.0, 0.0, 10.0) + offset * 3.0);
+    }
+    sim.set_weather(WeatherKind::Storm, 60.0);
+    let mut registry = sim.app.world.resource_mut::<WeatherRegistry>();
+    registry.get_mut(WeatherKind::Storm).unwrap().effects.knockdowns_per_minute = 60.0 * 64.0;
+    sim.step(8);
+
+    assert!(sim.events::<NodeKnockedDown>().is_empty());
+    assert!(!sim.app.world.get::<Gatherable>(tree).unwrap().is_depleted());
+}
+
+#[test]
+fn chopping_a_log_gives_wood_and_clears_it_away() {
+    let mut sim = SimulationHarness::new();
+    sim.step(64);
+    let log = sim.spawn_resource(ResourceType::new("log"), Vec3::new(0.0, 0.0, -1.5));
+    sim.tick();
+    sim.app.world.send_event(GatherRequest { target: GatherTarget::Node(log) });
+    sim.tick();
+
+    assert_eq!(sim.inventory_count(ResourceType::WOOD), 4);
+    assert_eq!(
+        sim.events::<ResourceGathered>(),
+        vec![ResourceGathered { node: log, resource: ResourceType::WOOD, gained: 4, total: 4, depleted: true }]
+    );
+    assert!(!sim.exists(log));
+    let mut nodes = sim.app.world.query_filtered::<(), With<ResourceNode>>();
+    assert_eq!(nodes.iter(&sim.app.world).count(), 0);
+}
+
+#[test]
+fn save_keeps_the_weather() {
+    let mut sim = SimulationHarness::new();
+    sim.set_weather(WeatherKind::Fog, 42.0);
+    let save = capture_save(&mut sim.app.world).unwrap();
+
+    let mut loaded_sim = SimulationHarness::new();
+    apply_save(&mut loaded_sim.app.world, &save).unwrap();
+    assert_eq!(loaded_sim.weather(), sim.weather());
+    assert_eq!(loaded_sim.weather().kind(), WeatherKind::Fog);
+}
@@ -0,0 +1,197 @@
+use bevy::prelude::*;
+use bevy_game_demo::components::skills::{Skill, SkillCurve, Skills};
+use bevy_game_demo::components::types::{Gatherable, ResourceType};
+use bevy_game_demo::components::equipment::{Equipment, Tool, ToolKind, ToolTier};
+use bevy_game_demo::components::workers::{Stockpile, Worker, WorkerState, WORKER_RETRY_SECONDS};
+use bevy_game_demo::systems::save::{apply_save, capture_save};
+use bevy_game_demo::systems::workers::{AssignJob, WorkerDeposited, WorkerGathered};
+use bevy_game_demo::testing::SimulationHarness;
+
+fn worker(sim: &SimulationHarness, entity: Entity) -> &Worker {
+    sim.app.world.get::<Worker>(entity).unwrap()
+}
+
+fn stockpiled(sim: &SimulationHarness, stockpile: Entity, resource: ResourceType) -> u32 {
+    sim.app.world.get::<Stockpile>(stockpile).unwrap().inventory.count(resource)
+}
+
+fn assign(sim: &mut SimulationHarness, worker: Entity, job: ResourceType) {
+    sim.app.world.send_event(AssignJob { worker, job: Some(job) });
+    sim.tick();
+}
+
+#[test]
+fn worker_gathers_the_nearest_node_and_hauls_it_to_the_stockpile() {
+    let mut sim = SimulationHarness::new();
+    let stockpile = sim.spawn_stockpile(Vec3::new(5.0, 0.25, 5.0));
+    let ada = sim.spawn_worker("ada", Vec3::new(5.0, 0.8, 0.0));
+    let near = sim.spawn_resource(ResourceType::WOOD, Vec3::new(9.0, 0.0, 0.0));
+    let far = sim.spawn_resource(ResourceType::WOOD, Vec3::new(5.0, 0.0, -15.0));
+    let rock = sim.spawn_resource(ResourceType::STONE, Vec3::new(6.0, 0.0, 1.0));
+    sim.tick();
+    assign(&mut sim, ada, ResourceType::WOOD);
+    sim.step(2);
+    assert_eq!(worker(&sim, ada).state, WorkerState::MoveTo(near));
+
+    sim.step(12 * 64);
+
+    // Both trees felled two units at a time, the rock left alone, everything unloaded
+    let hits: Vec<Entity> = sim.events::<WorkerGathered>().iter().map(|hit| hit.node).collect();
+    assert_eq!(hits, vec![near, near, far, far]);
+    assert_eq!(sim.app.world.get::<Gatherable>(rock).unwrap().health, 150);
+    assert_eq!(stockpiled(&sim, stockpile, ResourceType::WOOD), 4);
+    assert_eq!(worker(&sim, ada).carried(), 0);
+    assert_eq!(sim.inventory_count(ResourceType::WOOD), 0);
+    assert!(!sim.events::<WorkerDeposited>().is_empty());
+}
+
+#[test]
+fn worker_heads_back_once_its_load_is_full() {
+    let mut sim = SimulationHarness::new();
+    let stockpile = sim.spawn_stockpile(Vec3::new(-5.0, 0.25, 0.0));
+    let ada = sim.spawn_worker("ada", Vec3::new(5.0, 0.8, 0.0));
+    let tree = sim.spawn_resource(ResourceType::WOOD, Vec3::new(6.5, 0.0, 0.0));
+    sim.app.world.get_mut::<Worker>(ada).unwrap().load.add_up_to(ResourceType::WOOD, 9);
+    sim.tick();
+    assign(&mut sim, ada, ResourceType::WOOD);
+    sim.step(8);
+
+    assert_eq!(sim.events::<WorkerGathered>().len(), 1);
+    assert_eq!(worker(&sim, ada).state, WorkerState::Return);
+    assert_eq!(sim.app.world.get::<Gatherable>(tree).unwrap().health, 50);
+
+    sim.step(4 * 64);
+    assert_eq!(stockpiled(&sim, stockpile, ResourceType::WOOD), 10);
+    // Back to the tree for the rest
+    assert_eq!(worker(&sim, ada).state, WorkerState::MoveTo(tree));
+}
+
+#[test]
+fn worker_with_no_stockpile_to_return_to_goes_idle() {
+    let mut sim = SimulationHarness::new();
+    let ada = sim.spawn_worker("ada", Vec3::new(5.0, 0.8, 0.0));
+    sim.spawn_resource(ResourceType::WOOD, Vec3::new(6.5, 0.0, 0.0));
+    sim.app.world.get_mut::<Worker>(ada).unwrap().load.add_up_to(ResourceType::WOOD, 9);
+    sim.tick();
+    assign(&mut sim, ada, ResourceType::WOOD);
+    sim.step(8);
+
+    assert_eq!(sim.events::<WorkerGathered>().len(), 1);
+    assert_eq!(worker(&sim, ada).state, WorkerState::Idle);
+    // It keeps its load and stays put rather than looking for a stockpile every tick
+    let position = sim.app.world.get::<Transform>(ada).unwrap().translation;
+    sim.step(64);
+    assert_eq!(worker(&sim, ada).state, WorkerState::Idle);
+    assert_eq!(worker(&sim, ada).carried(), 10);
+    assert_eq!(sim.app.world.get::<Transform>(ada).unwrap().translation, position);
+
+    // Once there is a stockpile, it brings its load there when it next tries
+    let stockpile = sim.spawn_stockpile(Vec3::new(-5.0, 0.25, 0.0));
+    sim.step((WORKER_RETRY_SECONDS * 64.0) as u32 + 8 * 64);
+    assert!(stockpiled(&sim, stockpile, ResourceType::WOOD) >= 10);
+}
+
+#[test]
+fn workers_follow_the_players_gathering_rules() {
+    let mut sim = SimulationHarness::new();
+    sim.spawn_stockpile(Vec3::new(-5.0, 0.25, 0.0));
+    let ada = sim.spawn_worker("ada", Vec3::new(5.0, 0.8, 0.0));
+    let ore = sim.spawn_resource(ResourceType::ORE, Vec3::new(6.5, 0.0, 0.0));
+    sim.tick();
+    assign(&mut sim, ada, ResourceType::ORE);
+    sim.step(64);
+
+    // Ore needs an iron pickaxe, which the worker does not have
+    assert!(sim.events::<WorkerGathered>().is_empty());
+    assert_eq!(sim.app.world.get::<Gatherable>(ore).unwrap().health, 200);
+    assert_eq!(worker(&sim, ada).state, WorkerState::Idle);
+
+    // Handed the right tool and skill, it gets back to work without being given the job again
+    sim.app.world.get_mut::<Equipment>(ada).unwrap().equip(Tool::new(ToolKind::Pickaxe, ToolTier::Iron));
+    let curve = sim.app.world.resource::<SkillCurve>().clone();
+    sim.app.world.get_mut::<Skills>(ada).unwrap().add_xp(Skill::Mining, curve.xp_for_level(3), &curve);
+    sim.step((WORKER_RETRY_SECONDS * 64.0) as u32 + 64);
+    assert!(sim.app.world.get::<Gatherable>(ore).unwrap().health < 200);
+}
+
+#[test]
+fn workers_split_up_between_free_nodes() {
+    let mut sim = SimulationHarness::new();
+    let ada = sim.spawn_worker("ada", Vec3::new(5.0, 0.8, 0.0));
+    let bram = sim.spawn_worker("bram", Vec3::new(5.0, 0.8, 1.0));
+    let near = sim.spawn_resource(ResourceType::WOOD, Vec3::new(9.0, 0.0, 0.0));
+    let far = sim.spawn_resource(ResourceType::WOOD, Vec3::new(12.0, 0.0, 0.0));
+    sim.tick();
+    sim.app.world.send_event(AssignJob { worker: ada, job: Some(ResourceType::WOOD) });
+    assign(&mut sim, bram, ResourceType::WOOD);
+    sim.tick();
+
+    // Ada goes first by id and claims the nearest tree, so Bram takes the other one
+    assert_eq!(worker(&sim, ada).state, WorkerState::MoveTo(near));
+    assert_eq!(worker(&sim, bram).state, WorkerState::MoveTo(far));
+}
+
+#[test]
+fn assign_job_action_cycles_the_closest_workers_job() {
+    let mut sim = SimulationHarness::new();
+    let ada = sim.spawn_worker("ada", Vec3::new(1.5, 0.8, 0.0));
+    let bram = sim.spawn_worker("bram", Vec3::new(20.0, 0.8, 0.0));
+    let mut jobs = Vec::new();
+    for _ in 0..2 {
+        sim.press(KeyCode::KeyJ);
+        sim.tick();
+        sim.release(KeyCode::KeyJ);
+        sim.tick();
+        jobs.push(worker(&sim, ada).job);
+    }
+
+    assert_eq!(jobs, vec![Some(ResourceType::WOOD), Some(ResourceType::STONE)]);
+    assert_eq!(worker(&sim, bram).job, None);
+}
+
+#[test]
+fn save_keeps_workers_and_the_stockpile() {
+    let mut sim = SimulationHarness::new();
+    let stockpile = sim.spawn_stockpile(Vec3::new(-5.0, 0.25, 0.0));
+    let ada = sim.spawn_worker("ada", Vec3::new(5.0, 0.8, 0.0));
+    sim.app.world.get_mut::<Worker>(ada).unwrap().load.add_up_to(ResourceType::WOOD, 3);
+    sim.app.world.get_mut::<Stockpile>(stockpile).unwrap().inventory.add_up_to(ResourceType::STONE, 7);
+    assign(&mut sim, ada, ResourceType::WOOD);
+    let save = capture_save(&mut sim.app.world).unwrap();
+
+    let mut loaded_sim = SimulationHarness::new();
+    apply_save(&mut loaded_sim.app.world, &save).unwrap();
+    let loaded = capture_save(&mut loaded_sim.app.world).unwrap();
+    assert_eq!(loaded.workers, save.workers);
+    assert_eq!(loaded.stockpiles, save.stockpiles);
+    assert_eq!(save.workers[0].job, Some(ResourceType::WOOD));
+    assert_eq!(save.workers[0].load.iter().flatten().map(|stack| stack.amount).sum::<u32>(), 3);
+}
+
+#[test]
+fn loaded_workers_carry_on_where_they_left_off() {
+    // Saved while walking around a wall, then while hitting the tree
+    for saved_after in [64, 3 * 64] {
+        let mut sim = SimulationHarness::new();
+        for z in -3..=3 {
+            sim.spawn_resource(ResourceType::STONE, Vec3::new(8.0, 0.0, z as f32));
+        }
+        sim.spawn_stockpile(Vec3::new(5.0, 0.25, 8.0));
+        let ada = sim.spawn_worker("ada", Vec3::new(5.0, 0.8, 0.0));
+        sim.spawn_resource(ResourceType::WOOD, Vec3::new(11.0, 0.0, 0.0));
+        sim.tick();
+        assign(&mut sim, ada, ResourceType::WOOD);
+        sim.step(saved_after);
+        let save = capture_save(&mut sim.app.world).unwrap();
+
+        let mut loaded = SimulationHarness::new();
+        apply_save(&mut loaded.app.world, &save).unwrap();
+        assert_eq!(capture_save(&mut loaded.app.world).unwrap().workers, save.workers);
+        sim.step(6 * 64);
+        loaded.step(6 * 64);
+        let expected = capture_save(&mut sim.app.world).unwrap();
+        let actual = capture_save(&mut loaded.app.world).unwrap();
+        assert_eq!(actual.workers, expected.workers, "saved after {saved_after} ticks");
+        assert_eq!(actual.stockpiles, expected.stockpiles);
+    }
+}